@@ -0,0 +1,119 @@
+// Precompiles the bundled dictionaries in src/assets into static word buckets,
+// front-coded to keep Wasm bundles small, so that loading a language at runtime
+// never parses anything.
+// Words of the built-in blocklist of each language are dropped on the way.
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+#[allow(dead_code)]
+#[path = "src/prelude/dict_compiler.rs"]
+mod dict_compiler;
+
+const ASSETS_DIR: &str = "src/assets";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", ASSETS_DIR);
+    println!("cargo:rerun-if-changed=src/prelude/dict_compiler.rs");
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    let entries = fs::read_dir(ASSETS_DIR).expect("unable to list bundled dictionaries");
+
+    for entry in entries {
+        let path = entry.expect("unable to read bundled dictionary").path();
+
+        let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(name) if name.starts_with("dict_") && path.extension() == Some("txt".as_ref()) => {
+                name
+            }
+            _ => continue,
+        };
+
+        let dict_str = fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("unable to read {}: {}", path.display(), err));
+        let blocklist_str = read_blocklist(name);
+        let blocklist = parse_blocklist(&blocklist_str);
+
+        let words: Vec<&str> = parse_dict(&path, &dict_str)
+            .into_iter()
+            .filter(|word| !blocklist.contains(word))
+            .collect();
+        let code = generate_dict(&words);
+
+        fs::write(Path::new(&out_dir).join(format!("{}.rs", name)), code)
+            .unwrap_or_else(|err| panic!("unable to write precompiled {}: {}", name, err));
+    }
+}
+
+// bundled dictionaries are trusted to be well-formed, any broken line fails the build
+fn parse_dict<'a>(path: &Path, dict_str: &'a str) -> Vec<&'a str> {
+    let mut words: Vec<&str> = vec![];
+
+    for line in dict_str
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        let (len, words_csv) = line
+            .split_once(':')
+            .and_then(|(len_str, words_csv)| Some((len_str.parse::<usize>().ok()?, words_csv)))
+            .unwrap_or_else(|| panic!("invalid line in {}: {}", path.display(), line));
+
+        for word in words_csv.split(',') {
+            assert_eq!(
+                len,
+                word.chars().count(),
+                "wrong length for '{}' in {}",
+                word,
+                path.display()
+            );
+            words.push(word);
+        }
+    }
+
+    words
+}
+
+// native dictionaries share the blocklist of their language, e.g. dict_fr_native & dict_fr
+fn read_blocklist(dict_name: &str) -> String {
+    let language = dict_name
+        .trim_start_matches("dict_")
+        .trim_end_matches("_native");
+    let path = Path::new(ASSETS_DIR).join(format!("blocklist_{}.txt", language));
+
+    if !path.exists() {
+        return String::new();
+    }
+
+    fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("unable to read {}: {}", path.display(), err))
+}
+
+// one word per line, lines starting with # are comments
+fn parse_blocklist(blocklist_str: &str) -> HashSet<&str> {
+    blocklist_str
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+fn generate_dict(words: &[&str]) -> String {
+    let (blob, buckets) = dict_compiler::compile_words(words);
+    let coded = dict_compiler::front_code(&blob, &buckets);
+    let mut code = format!("Dict::from_front_coded(\n    {:?},\n    &[\n", coded);
+
+    for bucket in buckets {
+        writeln!(
+            code,
+            "        Bucket {{ char_len: {}, byte_len: {}, offset: {}, first: {}, count: {} }},",
+            bucket.char_len, bucket.byte_len, bucket.offset, bucket.first, bucket.count
+        )
+        .unwrap();
+    }
+
+    code.push_str("    ],\n)\n");
+    code
+}
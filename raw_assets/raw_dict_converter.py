@@ -1,21 +1,23 @@
 #!/usr/bin/env python3.9
 
-from typing import Dict, Iterable, Set
+from typing import Callable, Dict, Iterable, List, Set
 import re
 
 LANGUAGES = ["de", "en", "es", "fr", "pt"]
+# languages with accented words, also bundled in native spelling
+NATIVE_LANGUAGES = ["de", "es", "fr", "pt"]
 MIN_WORD_LENGTH = 4
 MAX_WORD_LENGTH = 10
 MAX_WORDS_PER_LENGTH = 1500
 
 
-def read_dict_file(lang: str) -> Iterable[str]:
+def read_dict_file(lang: str) -> List[str]:
     file_name = f"raw_dict_{lang}.txt"
 
     with open(file_name, "r") as file:
         lines = file.readlines()
         print(f"Read {len(lines)} lines from {file_name}")
-        return filter(lambda line: not line.startswith("#"), lines)
+        return [line for line in lines if not line.startswith("#")]
 
 
 def remove_accents(original: str) -> str:
@@ -38,11 +40,17 @@ def remove_accents(original: str) -> str:
     return word
 
 
-def group_words_by_length(all_words: Iterable[str]) -> Dict[int, Set[str]]:
+def keep_accents(original: str) -> str:
+    return original.strip().lower()
+
+
+def group_words_by_length(
+    all_words: Iterable[str], normalize: Callable[[str], str]
+) -> Dict[int, Set[str]]:
     grouped: Dict[int, Set[str]] = dict()
 
     for line in all_words:
-        word = remove_accents(line)
+        word = normalize(line)
         length = len(word)
 
         if length < MIN_WORD_LENGTH or length > MAX_WORD_LENGTH:
@@ -59,16 +67,27 @@ def group_words_by_length(all_words: Iterable[str]) -> Dict[int, Set[str]]:
     return grouped
 
 
+def write_dict_file(file_name: str, grouped_words: Dict[int, Set[str]]):
+    with open(file_name, "w") as file:
+        for length, words_set in grouped_words.items():
+            words = list(words_set.copy())
+            words.sort()
+            print(f"Writing {len(words)} words with length of {length}")
+            file.write(f"{length}:{','.join(words)}\n")
+
+
 if __name__ == "__main__":
     for lang in LANGUAGES:
         print(f"\nReading raw file for '{lang}'")
 
         all_words = read_dict_file(lang)
-        grouped_words = group_words_by_length(all_words)
-
-        with open(f"dict_{lang}.txt", "w") as file:
-            for length, words_set in grouped_words.items():
-                words = list(words_set.copy())
-                words.sort()
-                print(f"Writing {len(words)} words with length of {length}")
-                file.write(f"{length}:{','.join(words)}\n")
+        write_dict_file(
+            f"dict_{lang}.txt", group_words_by_length(all_words, remove_accents)
+        )
+
+        if lang in NATIVE_LANGUAGES:
+            print(f"Keeping native spelling for '{lang}'")
+            write_dict_file(
+                f"dict_{lang}_native.txt",
+                group_words_by_length(all_words, keep_accents),
+            )
@@ -2,21 +2,30 @@ use std::fmt;
 use std::ops::*;
 use wasm_bindgen::prelude::*;
 
-pub type FieldSize = u8;
+pub type FieldSize = u16;
 
 #[wasm_bindgen]
-#[repr(u8)]
+#[repr(u16)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum WordTransform {
-    // single transforms - possible to combine with each other
-    Lowercase = 0b00000001,
-    Titlecase = 0b00000010,
-    Uppercase = 0b00000100,
-    InversedTitlecase = 0b00001000,
-
-    // group transforms - overriding other single ones
-    AltercaseLowerFirst = 0b01000000,
-    AltercaseUpperFirst = 0b10000000,
+    // Bits are laid out in declaration order, a flag keeping its bit once released:
+    // 0-5 & 8-9 are single transforms, possible to combine with each other,
+    // 6-7 are group transforms, overriding single ones.
+    Lowercase = 0b00_00000001,
+    Titlecase = 0b00_00000010,
+    Uppercase = 0b00_00000100,
+    InversedTitlecase = 0b00_00001000,
+    Leetspeak = 0b00_00010000,
+    RandomCapital = 0b00_00100000,
+    AltercaseLowerFirst = 0b00_01000000,
+    AltercaseUpperFirst = 0b00_10000000,
+    LastCapital = 0b01_00000000,
+    Reversed = 0b10_00000000,
 }
 
 impl fmt::Display for WordTransform {
@@ -26,6 +35,10 @@ impl fmt::Display for WordTransform {
             Self::Uppercase => "UPPERCASE",
             Self::Titlecase => "Titlecase",
             Self::InversedTitlecase => "iNVERSED tITLECASE",
+            Self::Leetspeak => "l3375p34k",
+            Self::RandomCapital => "random cApital",
+            Self::LastCapital => "last capitaL",
+            Self::Reversed => "desrever",
             Self::AltercaseLowerFirst => "altercase LOWER first",
             Self::AltercaseUpperFirst => "ALTERCASE upper FIRST",
         };
@@ -83,6 +96,10 @@ impl BitFlags for FieldSize {
             WordTransform::Titlecase,
             WordTransform::Uppercase,
             WordTransform::InversedTitlecase,
+            WordTransform::Leetspeak,
+            WordTransform::RandomCapital,
+            WordTransform::LastCapital,
+            WordTransform::Reversed,
         ] {
             if self & flag {
                 flags.push(flag)
@@ -149,6 +166,36 @@ mod tests {
             0b10000000,
             FieldSize::from_flag(WordTransform::AltercaseUpperFirst)
         );
+        assert_eq!(0b00010000, FieldSize::from_flag(WordTransform::Leetspeak));
+        assert_eq!(
+            0b00100000,
+            FieldSize::from_flag(WordTransform::RandomCapital)
+        );
+        assert_eq!(
+            0b1_00000000,
+            FieldSize::from_flag(WordTransform::LastCapital)
+        );
+        assert_eq!(0b10_00000000, FieldSize::from_flag(WordTransform::Reversed));
+    }
+
+    #[test]
+    fn test_flags_layout() {
+        // contiguous bits in declaration order
+        let flags = [
+            WordTransform::Lowercase,
+            WordTransform::Titlecase,
+            WordTransform::Uppercase,
+            WordTransform::InversedTitlecase,
+            WordTransform::Leetspeak,
+            WordTransform::RandomCapital,
+            WordTransform::AltercaseLowerFirst,
+            WordTransform::AltercaseUpperFirst,
+            WordTransform::LastCapital,
+            WordTransform::Reversed,
+        ];
+        for (bit, flag) in flags.into_iter().enumerate() {
+            assert_eq!(1 << bit, FieldSize::from_flag(flag), "{:?}", flag);
+        }
     }
 
     #[test]
@@ -183,6 +230,12 @@ mod tests {
             ],
             transforms.to_flags()
         );
+
+        let transforms = WordTransform::Reversed | WordTransform::Leetspeak;
+        assert_eq!(
+            vec![WordTransform::Leetspeak, WordTransform::Reversed],
+            transforms.to_flags()
+        );
     }
 
     #[test]
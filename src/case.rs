@@ -0,0 +1,36 @@
+// Unicode case conversions of words. Wasm builds leave non-ASCII text to the JS engine,
+// which applies the same Unicode rules, sparing the bundles the case tables of std
+// that would weigh ~15KB once compressed.
+
+pub fn to_lowercase(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_ascii_lowercase();
+    }
+
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    return js_sys::JsString::from(text).to_lower_case().into();
+
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    text.to_lowercase()
+}
+
+pub fn to_uppercase(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_ascii_uppercase();
+    }
+
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    return js_sys::JsString::from(text).to_upper_case().into();
+
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    text.to_uppercase()
+}
+
+// e.g. 'ß' giving "SS" once uppercased
+pub fn char_to_lowercase(char: char) -> String {
+    to_lowercase(char.encode_utf8(&mut [0; 4]))
+}
+
+pub fn char_to_uppercase(char: char) -> String {
+    to_uppercase(char.encode_utf8(&mut [0; 4]))
+}
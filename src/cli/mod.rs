@@ -1,22 +1,41 @@
+mod output;
+mod serve;
 #[cfg(test)]
 mod tests;
 mod toml_conf;
 
 use crate::bit_flags::*;
+use crate::optimizer::*;
+use crate::policy::*;
 use crate::prelude::*;
+use crate::readout::Readout;
+use crate::settings::Settings;
+use output::*;
 use toml_conf::*;
 
 use clap::builder::PossibleValue;
 use clap::error::ErrorKind;
-use clap::{ArgAction, CommandFactory, Parser, ValueEnum};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
 
 #[derive(Clone, Copy, Debug)]
 pub enum CliPadding {
     Fixed,
     Adaptive,
+    Exact,
 }
 
-#[derive(Parser, Debug)]
+#[derive(Clone, Debug)]
+pub enum CliPreset {
+    BuiltIn(Preset),
+    // name of a [presets.<name>] table, resolved when loading xkpasswd.toml
+    Custom(String),
+}
+
+// accepts any name, but only lists built-in presets as possible values
+#[derive(Clone)]
+struct CliPresetParser;
+
+#[derive(Parser, Debug, Default)]
 #[command(version)]
 pub struct Cli {
     #[arg(
@@ -40,6 +59,20 @@ pub struct Cli {
     )]
     word_transforms: Option<Vec<WordTransform>>,
 
+    #[arg(
+        long = "ascii-fold",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        help = "Replace accented letters of words with plain ASCII ones, e.g. français -> francais"
+    )]
+    ascii_fold: Option<bool>,
+
+    #[arg(
+        long = "unique-prefix",
+        help = "Never pick words sharing their first N chars in the same password, 0 to allow any"
+    )]
+    unique_prefix: Option<u8>,
+
     #[arg(
         short = 's',
         long = "separators",
@@ -47,6 +80,13 @@ pub struct Cli {
     )]
     separators: Option<String>,
 
+    #[arg(
+        long = "separator-mode",
+        value_enum,
+        help = "How separators between words & digits are randomized"
+    )]
+    separator_mode: Option<SeparatorMode>,
+
     #[arg(
         long = "digits-before",
         help = "How many digits to be padded before the words"
@@ -78,18 +118,104 @@ pub struct Cli {
     )]
     padding_symbols_after: Option<u8>,
 
+    #[arg(
+        long = "symbol-mode",
+        value_enum,
+        help = "How padding symbols are randomized"
+    )]
+    symbol_mode: Option<SymbolMode>,
+
     #[arg(short = 'p', long = "padding", help = "Padding strategy", value_enum)]
     padding: Option<CliPadding>,
 
     #[arg(
         short = 'a',
         long = "adaptive-length",
-        help = "Pad or trim the final output to fit a length. Required for --padding=adaptive"
+        help = "Pad or trim the final output to fit a length. Required for --padding=adaptive & --padding=exact"
     )]
     adaptive_length: Option<usize>,
 
-    #[arg(short = 'P', long = "preset", value_enum)]
-    preset: Option<Preset>,
+    #[arg(
+        long = "substitute",
+        value_name = "FROM=TO",
+        help = "Replace a char of generated passwords by a string, empty to drop it, e.g. ' =_', can be combined with multiple occurrences"
+    )]
+    substitutions: Option<Vec<String>>,
+
+    #[arg(
+        long = "min-length",
+        help = "Regenerate passwords shorter than this, accounted for in the entropy"
+    )]
+    min_length: Option<usize>,
+
+    #[arg(
+        long = "max-length",
+        help = "Regenerate passwords longer than this, accounted for in the entropy"
+    )]
+    max_length: Option<usize>,
+
+    #[arg(
+        long = "policy-min-length",
+        help = "Regenerate until the password is at least this long"
+    )]
+    policy_min_length: Option<usize>,
+
+    #[arg(
+        long = "policy-max-length",
+        help = "Regenerate until the password is at most this long"
+    )]
+    policy_max_length: Option<usize>,
+
+    #[arg(
+        long = "policy-require",
+        value_enum,
+        help = "Character classes the password must contain, can be combined with multiple occurrences"
+    )]
+    policy_require: Option<Vec<CharClass>>,
+
+    #[arg(
+        long = "policy-forbid",
+        help = "List of characters the password must not contain"
+    )]
+    policy_forbid: Option<String>,
+
+    #[arg(
+        long = "policy-max-repeat",
+        help = "How many times a character may be repeated in a row"
+    )]
+    policy_max_repeat: Option<usize>,
+
+    #[arg(
+        long = "policy-attempts",
+        help = "How many passwords to try before giving up on the policy [default: 1000]"
+    )]
+    policy_attempts: Option<usize>,
+
+    #[arg(
+        long = "attacker",
+        value_enum,
+        help = "Attacker profile the time to break passwords is estimated for [default: online-throttled]"
+    )]
+    attacker: Option<AttackerProfile>,
+
+    #[arg(
+        long = "guesses-per-sec",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Guessing speed of the attacker, overriding the profile default one"
+    )]
+    guesses_per_sec: Option<u64>,
+
+    #[arg(
+        short = 'P',
+        long = "preset",
+        value_parser = CliPresetParser,
+        help = "Built-in preset, or one defined as a [presets.<name>] table of xkpasswd.toml"
+    )]
+    preset: Option<CliPreset>,
+
+    // [presets.<name>] tables of xkpasswd.toml, kept for serve mode requests
+    #[arg(skip)]
+    presets: Option<toml::Table>,
 
     #[arg(short = 'v', long = "verbose", help = "Verbosity: 1 = info, 2+ = debug", action = ArgAction::Count)]
     verbosity: u8,
@@ -97,8 +223,97 @@ pub struct Cli {
     #[arg(short = 'z', long = "lang", help = "Language of generated words")]
     language: Option<Language>,
 
+    #[arg(
+        short = 'd',
+        long = "dict",
+        help = "Path to a custom dictionary, either one word per line or in bundled format. Overrides --lang"
+    )]
+    dict_file: Option<String>,
+
+    #[arg(
+        long = "exclude-words",
+        help = "Path to a list of words never to pick, one per line, on top of the built-in blocklist"
+    )]
+    exclude_words: Option<String>,
+
     #[arg(short = 'c', long = "config", help = "Path to .toml config file")]
     config_file: Option<String>,
+
+    #[arg(
+        short = 'n',
+        long = "count",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "How many passwords to be generated, one per line"
+    )]
+    count: Option<u32>,
+
+    #[arg(
+        short = 'f',
+        long = "format",
+        value_enum,
+        help = "Output format of generated passwords"
+    )]
+    format: Option<OutputFormat>,
+
+    #[arg(
+        long = "readout",
+        help = "Follow each password with its spoken form in the --lang language, e.g. to read it over the phone"
+    )]
+    readout: bool,
+
+    #[arg(
+        long = "seed",
+        help = "Seed for deterministic generation, same seed & settings give the same password"
+    )]
+    seed: Option<u64>,
+
+    #[arg(
+        long = "dump-config",
+        help = "Print effective settings as a ready-to-use xkpasswd.toml instead of generating passwords"
+    )]
+    dump_config: bool,
+
+    #[arg(
+        long = "serve-stdin",
+        help = "Answer newline-delimited JSON requests from stdin with JSON lines on stdout until EOF, requests override xkpasswd.toml keys & may set a count"
+    )]
+    serve_stdin: bool,
+
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CliCommand {
+    #[command(
+        about = "Search the settings with the highest entropy within constraints & print them as xkpasswd.toml"
+    )]
+    Optimize(OptimizeArgs),
+}
+
+#[derive(Args, Debug, Default)]
+pub struct OptimizeArgs {
+    #[arg(long = "min-length", help = "Minimum length of generated passwords")]
+    min_length: Option<usize>,
+
+    #[arg(
+        long = "max-length",
+        help = "Maximum length of generated passwords [default: 32]"
+    )]
+    max_length: Option<usize>,
+
+    #[arg(
+        long = "require",
+        value_enum,
+        help = "Character classes generated passwords must contain, can be combined with multiple occurrences"
+    )]
+    required_classes: Option<Vec<CharClass>>,
+
+    #[arg(
+        long = "symbols",
+        help = "Symbols allowed as separators & padding, empty for none [default: ~@$%^&*-_+=:|?/.;]"
+    )]
+    symbols: Option<String>,
 }
 
 impl Cli {
@@ -115,18 +330,134 @@ impl Cli {
         }
     }
 
+    pub fn pass_generator(&self) -> Xkpasswd {
+        match self.try_pass_generator() {
+            Ok(pass_generator) => pass_generator,
+            Err(err) => {
+                Self::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!("Invalid dictionary: {}", err),
+                    )
+                    .exit();
+            }
+        }
+    }
+
+    fn try_pass_generator(&self) -> Result<Xkpasswd, XkpasswdError> {
+        let pass_generator = match &self.dict_file {
+            Some(dict_file) => {
+                log::info!("using custom dictionary at {}", dict_file);
+                Xkpasswd::from_dict_file(dict_file)?
+            }
+            None => {
+                log::info!("using bundled dictionary in {:?}", self.language());
+                Xkpasswd::for_language(self.language())
+            }
+        };
+
+        match &self.exclude_words {
+            Some(exclude_words) => {
+                log::info!("excluding words listed at {}", exclude_words);
+                pass_generator.with_excluded_words_file(exclude_words)
+            }
+            None => Ok(pass_generator),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count.unwrap_or(1) as usize
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn dump_config(&self) -> bool {
+        self.dump_config
+    }
+
+    pub fn serve_stdin(&self) -> bool {
+        self.serve_stdin
+    }
+
+    pub fn render_config(
+        &self,
+        settings: &Settings,
+        policy: Option<&PasswordPolicy>,
+        attacker: Option<&Attacker>,
+    ) -> String {
+        // a custom dictionary overrides the language, no need to keep both
+        let language = match self.dict_file {
+            Some(_) => None,
+            None => Some(self.language()),
+        };
+
+        render_config(
+            settings,
+            policy,
+            attacker,
+            language,
+            self.dict_file.as_deref(),
+            self.exclude_words.as_deref(),
+            self.format,
+        )
+    }
+
+    pub fn gen_passes(
+        &self,
+        pass_generator: &Xkpasswd,
+        settings: &Settings,
+        policy: Option<&PasswordPolicy>,
+    ) -> Vec<(String, Entropy)> {
+        let result = match (policy, self.seed()) {
+            (Some(policy), Some(seed)) => {
+                pass_generator.gen_passes_matching_with_seed(settings, policy, self.count(), seed)
+            }
+            (Some(policy), None) => {
+                pass_generator.gen_passes_matching(settings, policy, self.count())
+            }
+            (None, Some(seed)) => pass_generator.gen_passes_with_seed(settings, self.count(), seed),
+            (None, None) => pass_generator.gen_passes(settings, self.count()),
+        };
+
+        match result {
+            Ok(passwds) => passwds,
+            Err(err) => {
+                Self::command()
+                    .error(
+                        ErrorKind::ValueValidation,
+                        format!("Unable to generate password: {}", err),
+                    )
+                    .exit();
+            }
+        }
+    }
+
+    pub fn render_passwds(&self, settings: &Settings, passwds: &[(String, Entropy)]) -> String {
+        // custom dictionaries aren't tied to any language
+        let language = match self.dict_file {
+            Some(_) => None,
+            None => Some(self.language()),
+        };
+
+        let readout = self.readout.then(|| Readout::for_language(self.language()));
+
+        render_passwds(
+            self.format.unwrap_or_default(),
+            language,
+            settings,
+            readout.as_ref(),
+            passwds,
+        )
+    }
+
     pub fn parse_settings<B: Builder + Randomizer>(&mut self) -> B {
         let parse_result = match self.parse_config_file() {
-            Ok(_) => Ok(()),
-            Err(err) => match err {
-                ConfigParseError::Ignore => Ok(()),
-                ConfigParseError::InvalidFile(err) => {
-                    Err(format!("Error parsing config file: {}", err))
-                }
-                ConfigParseError::InvalidConfig(field, err) => {
-                    Err(format!("Error parsing config file at '{}': {}", field, err))
-                }
-            },
+            Ok(_) | Err(ConfigParseError::Ignore) => Ok(()),
+            Err(ConfigParseError::Invalid(err)) => {
+                Err(format!("Error parsing config file: {}", err))
+            }
         };
 
         let result = match parse_result {
@@ -147,11 +478,152 @@ impl Cli {
         }
     }
 
-    fn build_settings<B: Builder + Randomizer>(&self) -> Result<B, String> {
-        let mut settings = if let Some(preset) = self.preset {
-            B::from_preset(preset)
-        } else {
-            B::default()
+    /// Must be called after `parse_settings`, which loads policy keys from the config file
+    pub fn parse_policy(&self) -> Option<PasswordPolicy> {
+        match self.build_policy() {
+            Ok(policy) => policy,
+            Err(err) => {
+                Self::command()
+                    .error(ErrorKind::InvalidValue, format!("Invalid policy: {}", err))
+                    .exit();
+            }
+        }
+    }
+
+    // no policy at all unless at least one rule is set
+    fn build_policy(&self) -> Result<Option<PasswordPolicy>, XkpasswdError> {
+        let has_rules = self.policy_min_length.is_some()
+            || self.policy_max_length.is_some()
+            || self.policy_require.is_some()
+            || self.policy_forbid.is_some()
+            || self.policy_max_repeat.is_some();
+
+        if !has_rules {
+            return Ok(None);
+        }
+
+        let mut policy = PasswordPolicy::default()
+            .with_lengths(self.policy_min_length, self.policy_max_length)?;
+
+        if let Some(classes) = &self.policy_require {
+            policy = policy.with_required_classes(classes);
+        }
+
+        if let Some(chars) = &self.policy_forbid {
+            policy = policy.with_forbidden_chars(chars);
+        }
+
+        if let Some(max_repeat) = self.policy_max_repeat {
+            policy = policy.with_max_repeated_chars(max_repeat)?;
+        }
+
+        if let Some(attempts) = self.policy_attempts {
+            policy = policy.with_max_attempts(attempts)?;
+        }
+
+        Ok(Some(policy))
+    }
+
+    /// Constraints of the `optimize` subcommand, none unless it's invoked
+    pub fn parse_constraints(&self) -> Option<Constraints> {
+        match self.build_constraints() {
+            Ok(constraints) => constraints,
+            Err(err) => {
+                Self::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!("Invalid constraints: {}", err),
+                    )
+                    .exit();
+            }
+        }
+    }
+
+    fn build_constraints(&self) -> Result<Option<Constraints>, XkpasswdError> {
+        let args = match &self.command {
+            Some(CliCommand::Optimize(args)) => args,
+            None => return Ok(None),
+        };
+
+        let mut constraints =
+            Constraints::default().with_lengths(args.min_length, args.max_length)?;
+
+        if let Some(classes) = &args.required_classes {
+            constraints = constraints.with_required_classes(classes);
+        }
+
+        if let Some(symbols) = &args.symbols {
+            constraints = constraints.with_symbols(symbols)?;
+        }
+
+        Ok(Some(constraints))
+    }
+
+    pub fn optimize_settings(
+        &self,
+        pass_generator: &Xkpasswd,
+        constraints: &Constraints,
+    ) -> (Settings, Entropy) {
+        match optimize(pass_generator, constraints) {
+            Ok(optimized) => optimized,
+            Err(err) => {
+                Self::command()
+                    .error(
+                        ErrorKind::ValueValidation,
+                        format!("Unable to optimize settings: {}", err),
+                    )
+                    .exit();
+            }
+        }
+    }
+
+    /// Optimized settings as `xkpasswd.toml`, headed by their entropy as a comment
+    pub fn render_optimized(&self, settings: &Settings, entropy: &Entropy) -> String {
+        format!(
+            "# {}\n{}",
+            entropy,
+            self.render_config(settings, None, None)
+        )
+    }
+
+    /// Must be called after `parse_settings`, which loads attacker keys from the config file
+    pub fn parse_attacker(&self) -> Option<Attacker> {
+        match self.build_attacker() {
+            Ok(attacker) => attacker,
+            Err(err) => {
+                Self::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!("Invalid attacker: {}", err),
+                    )
+                    .exit();
+            }
+        }
+    }
+
+    // default attacker unless either its profile or speed is set
+    fn build_attacker(&self) -> Result<Option<Attacker>, XkpasswdError> {
+        if self.attacker.is_none() && self.guesses_per_sec.is_none() {
+            return Ok(None);
+        }
+
+        let attacker = Attacker::new(self.attacker.unwrap_or_default());
+
+        match self.guesses_per_sec {
+            Some(guesses_per_sec) => attacker
+                .with_guesses_per_sec(guesses_per_sec as f64)
+                .map(Some),
+            None => Ok(Some(attacker)),
+        }
+    }
+
+    fn build_settings<B: Builder + Randomizer>(&self) -> Result<B, XkpasswdError> {
+        let mut settings = match &self.preset {
+            Some(CliPreset::BuiltIn(preset)) => B::from_preset(*preset),
+            Some(CliPreset::Custom(name)) => {
+                return Err(XkpasswdError::UnknownPreset { name: name.clone() })
+            }
+            None => B::default(),
         };
 
         settings = settings
@@ -170,32 +642,59 @@ impl Cli {
             settings = settings.with_word_transforms(transforms)?;
         }
 
+        if let Some(ascii_fold) = self.ascii_fold {
+            settings = settings.with_ascii_fold(ascii_fold);
+        }
+
+        if let Some(unique_prefix) = self.unique_prefix {
+            settings = settings.with_unique_prefix(unique_prefix);
+        }
+
         if let Some(separators) = &self.separators {
             settings = settings.with_separators(separators);
         }
 
+        if let Some(separator_mode) = self.separator_mode {
+            settings = settings.with_separator_mode(separator_mode);
+        }
+
         if let Some(padding_symbols) = &self.padding_symbols {
             settings = settings.with_padding_symbols(padding_symbols);
         }
 
+        if let Some(symbol_mode) = self.symbol_mode {
+            settings = settings.with_symbol_mode(symbol_mode);
+        }
+
+        if let Some(substitutions) = &self.substitutions {
+            let rules = substitutions
+                .iter()
+                .map(|rule| parse_substitution(rule))
+                .collect::<Result<Vec<_>, _>>()?;
+            settings = settings.with_substitutions(&rules)?;
+        }
+
         if let Some(padding) = &self.padding {
             match padding {
                 CliPadding::Fixed => {
                     settings = settings.with_padding_strategy(PaddingStrategy::Fixed)?
                 }
-                CliPadding::Adaptive => {
-                    if let Some(adaptive_length) = &self.adaptive_length {
-                        settings = settings
-                            .with_padding_strategy(PaddingStrategy::Adaptive(*adaptive_length))?
+                CliPadding::Adaptive | CliPadding::Exact => {
+                    if let Some(adaptive_length) = self.adaptive_length {
+                        let strategy = match padding {
+                            CliPadding::Exact => PaddingStrategy::Exact(adaptive_length),
+                            _ => PaddingStrategy::Adaptive(adaptive_length),
+                        };
+                        settings = settings.with_padding_strategy(strategy)?
                     } else {
-                        return Err(
-                            "adaptive length is required for adaptive padding strategy".to_string()
-                        );
+                        return Err(XkpasswdError::AdaptiveLengthRequired);
                     }
                 }
             }
         }
 
+        settings = settings.with_length_range(self.min_length, self.max_length)?;
+
         Ok(settings)
     }
 
@@ -216,9 +715,42 @@ impl Cli {
     }
 }
 
+impl CliPreset {
+    fn parse(name: &str) -> Self {
+        match Preset::from_str(name, true) {
+            Ok(preset) => Self::BuiltIn(preset),
+            Err(_) => Self::Custom(name.to_string()),
+        }
+    }
+}
+
+impl clap::builder::TypedValueParser for CliPresetParser {
+    type Value = CliPreset;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        _arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<CliPreset, clap::Error> {
+        match value.to_str() {
+            Some(name) => Ok(CliPreset::parse(name)),
+            None => Err(clap::Error::new(ErrorKind::InvalidUtf8).with_cmd(cmd)),
+        }
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            Preset::value_variants()
+                .iter()
+                .filter_map(ValueEnum::to_possible_value),
+        ))
+    }
+}
+
 impl ValueEnum for CliPadding {
     fn value_variants<'a>() -> &'a [Self] {
-        &[Self::Fixed, Self::Adaptive]
+        &[Self::Fixed, Self::Adaptive, Self::Exact]
     }
 
     fn to_possible_value(&self) -> Option<PossibleValue> {
@@ -229,6 +761,79 @@ impl ValueEnum for CliPadding {
                 r#"Pad or trim the final output to fit a length. Requires --adaptive-length.
 Notes: setting this will disable --symbols-before and --symbols-after options"#,
             ),
+            Self::Exact => PossibleValue::new("exact").help(
+                r#"Pick words by length & pad symbols to fit a length exactly, never trimming. Requires --adaptive-length.
+Notes: setting this will disable --symbols-before and --symbols-after options"#,
+            ),
+        })
+    }
+}
+
+impl ValueEnum for SeparatorMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Repeat, Self::Independent]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Repeat => PossibleValue::new("repeat")
+                .help("A single separator for all words, e.g. foo.bar.baz"),
+            Self::Independent => PossibleValue::new("independent")
+                .help("Each gap randomized separately, e.g. foo.bar-baz"),
+        })
+    }
+}
+
+impl ValueEnum for SymbolMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Repeat, Self::Independent]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Repeat => PossibleValue::new("repeat")
+                .help("A single symbol repeated over each padding, e.g. $$$"),
+            Self::Independent => PossibleValue::new("independent")
+                .help("Each padding symbol randomized separately, e.g. $#!"),
+        })
+    }
+}
+
+impl ValueEnum for CharClass {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Lowercase, Self::Uppercase, Self::Digit, Self::Symbol]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Lowercase => PossibleValue::new("lowercase"),
+            Self::Uppercase => PossibleValue::new("uppercase"),
+            Self::Digit => PossibleValue::new("digit"),
+            Self::Symbol => PossibleValue::new("symbol").help("Any character but letters & digits"),
+        })
+    }
+}
+
+impl ValueEnum for AttackerProfile {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::OnlineThrottled,
+            Self::OfflineSlowHash,
+            Self::OfflineFastHash,
+            Self::GpuCluster,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::OnlineThrottled => PossibleValue::new("online-throttled")
+                .help("Login form with rate limiting, 1e3 guesses/sec"),
+            Self::OfflineSlowHash => PossibleValue::new("offline-slow-hash")
+                .help("Leaked hashes of a slow function like bcrypt, 1e5 guesses/sec"),
+            Self::OfflineFastHash => PossibleValue::new("offline-fast-hash")
+                .help("Leaked hashes of a fast function like SHA-1, 1e10 guesses/sec"),
+            Self::GpuCluster => PossibleValue::new("gpu-cluster")
+                .help("Fast hashes cracked by many GPUs at once, 1e12 guesses/sec"),
         })
     }
 }
@@ -274,6 +879,10 @@ impl ValueEnum for WordTransform {
             Self::Titlecase,
             Self::Uppercase,
             Self::InversedTitlecase,
+            Self::Leetspeak,
+            Self::RandomCapital,
+            Self::LastCapital,
+            Self::Reversed,
             Self::AltercaseLowerFirst,
             Self::AltercaseUpperFirst,
         ]
@@ -287,6 +896,10 @@ impl ValueEnum for WordTransform {
             Self::InversedTitlecase => {
                 PossibleValue::new("inversed-titlecase").help(self.to_string())
             }
+            Self::Leetspeak => PossibleValue::new("leetspeak").help(self.to_string()),
+            Self::RandomCapital => PossibleValue::new("random-capital").help(self.to_string()),
+            Self::LastCapital => PossibleValue::new("last-capital").help(self.to_string()),
+            Self::Reversed => PossibleValue::new("reversed").help(self.to_string()),
             Self::AltercaseLowerFirst => {
                 PossibleValue::new("altercase-lower-first").help(self.to_string())
             }
@@ -305,6 +918,10 @@ impl ValueEnum for Language {
             Self::German,
             Self::Portuguese,
             Self::Spanish,
+            Self::FrenchNative,
+            Self::GermanNative,
+            Self::PortugueseNative,
+            Self::SpanishNative,
         ]
     }
 
@@ -315,6 +932,18 @@ impl ValueEnum for Language {
             Self::German => PossibleValue::new("de").help("German"),
             Self::Portuguese => PossibleValue::new("pt").help("Portuguese"),
             Self::Spanish => PossibleValue::new("es").help("Spanish"),
+            Self::FrenchNative => {
+                PossibleValue::new("fr-native").help("French, keeping accents, e.g. français")
+            }
+            Self::GermanNative => {
+                PossibleValue::new("de-native").help("German, keeping umlauts & ß, e.g. straße")
+            }
+            Self::PortugueseNative => {
+                PossibleValue::new("pt-native").help("Portuguese, keeping accents, e.g. coração")
+            }
+            Self::SpanishNative => {
+                PossibleValue::new("es-native").help("Spanish, keeping accents & ñ, e.g. niño")
+            }
         })
     }
 }
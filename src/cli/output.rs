@@ -0,0 +1,494 @@
+use super::*;
+use crate::readout::Readout;
+use crate::settings::Settings;
+use serde_json::{json, Map, Value};
+use toml_conf::{settings_to_config, value_name};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Plain,
+    Json,
+    Tsv,
+}
+
+const TSV_HEADER: [&str; 11] = [
+    "passwd",
+    "blind_min",
+    "blind_max",
+    "seen",
+    "attacker",
+    "guesses_per_sec",
+    "guess_seconds",
+    "guess_years",
+    "guess_months",
+    "guess_days",
+    "language",
+];
+
+pub fn render_passwds(
+    format: OutputFormat,
+    language: Option<Language>,
+    settings: &Settings,
+    readout: Option<&Readout>,
+    passwds: &[(String, Entropy)],
+) -> String {
+    let read = |passwd: &str| readout.map(|readout| readout.read(passwd));
+
+    let lines: Vec<String> = match format {
+        OutputFormat::Plain => passwds
+            .iter()
+            .flat_map(|(passwd, _)| {
+                let spoken = read(passwd).map(|pieces| pieces.join(", "));
+                std::iter::once(passwd.clone()).chain(spoken)
+            })
+            .collect(),
+        OutputFormat::Json => {
+            let settings_json = to_json(&toml::Value::Table(settings_to_config(settings)));
+            passwds
+                .iter()
+                .map(|(passwd, entropy)| {
+                    let json = render_json(
+                        passwd,
+                        entropy,
+                        language,
+                        settings_json.clone(),
+                        read(passwd),
+                    );
+                    json.to_string()
+                })
+                .collect()
+        }
+        OutputFormat::Tsv => {
+            let header = TSV_HEADER
+                .iter()
+                .chain(readout.map(|_| &"readout"))
+                .copied()
+                .collect::<Vec<&str>>()
+                .join("\t");
+            let rows = passwds
+                .iter()
+                .map(|(passwd, entropy)| render_tsv(passwd, entropy, language, read(passwd)));
+            std::iter::once(header).chain(rows).collect()
+        }
+    };
+
+    lines.join("\n")
+}
+
+fn render_json(
+    passwd: &str,
+    entropy: &Entropy,
+    language: Option<Language>,
+    settings_json: Value,
+    readout: Option<Vec<String>>,
+) -> Value {
+    let mut entries = Map::new();
+    entries.insert("passwd".to_string(), Value::from(passwd));
+
+    if let Some(pieces) = readout {
+        entries.insert("readout".to_string(), Value::from(pieces));
+    }
+
+    entries.insert("entropy".to_string(), entropy_json(entropy));
+    entries.insert("language".to_string(), language_json(language));
+    entries.insert("settings".to_string(), settings_json);
+
+    Value::Object(entries)
+}
+
+/// Single line answering a served request, its passwords sharing the same language & settings.
+/// The request id is echoed back if any
+pub fn render_response(
+    id: Option<&Value>,
+    passwds: &[(String, Entropy)],
+    language: Option<Language>,
+    settings: &Settings,
+) -> String {
+    let passwds_json: Vec<Value> = passwds
+        .iter()
+        .map(|(passwd, entropy)| {
+            json!({
+                "passwd": passwd,
+                "entropy": entropy_json(entropy),
+            })
+        })
+        .collect();
+
+    let mut entries = response_id(id);
+    entries.insert("passwds".to_string(), Value::Array(passwds_json));
+    entries.insert("language".to_string(), language_json(language));
+    entries.insert(
+        "settings".to_string(),
+        to_json(&toml::Value::Table(settings_to_config(settings))),
+    );
+
+    Value::Object(entries).to_string()
+}
+
+pub fn render_error(id: Option<&Value>, message: &str) -> String {
+    let mut entries = response_id(id);
+    entries.insert("error".to_string(), Value::from(message));
+    Value::Object(entries).to_string()
+}
+
+fn response_id(id: Option<&Value>) -> Map<String, Value> {
+    let mut entries = Map::new();
+    if let Some(id) = id {
+        entries.insert("id".to_string(), id.clone());
+    }
+    entries
+}
+
+fn entropy_json(entropy: &Entropy) -> Value {
+    let guess_time = &entropy.guess_time;
+
+    json!({
+        "blind_min": json_number(entropy.blind_min),
+        "blind_max": json_number(entropy.blind_max),
+        "seen": json_number(entropy.seen),
+        "guess_time": {
+            "seconds": json_number(guess_time.seconds),
+            "years": json_number(guess_time.years),
+            "months": guess_time.months,
+            "days": guess_time.days,
+        },
+        "attacker": value_name(&entropy.attacker.profile()),
+        "guesses_per_sec": json_number(entropy.attacker.guesses_per_sec()),
+    })
+}
+
+fn language_json(language: Option<Language>) -> Value {
+    language
+        .map(|language| Value::from(value_name(&language)))
+        .unwrap_or(Value::Null)
+}
+
+fn render_tsv(
+    passwd: &str,
+    entropy: &Entropy,
+    language: Option<Language>,
+    readout: Option<Vec<String>>,
+) -> String {
+    let guess_time = &entropy.guess_time;
+
+    let mut fields = vec![
+        tsv_field(passwd),
+        entropy.blind_min.to_string(),
+        entropy.blind_max.to_string(),
+        entropy.seen.to_string(),
+        value_name(&entropy.attacker.profile()),
+        entropy.attacker.guesses_per_sec().to_string(),
+        guess_time.seconds.to_string(),
+        guess_time.years.to_string(),
+        guess_time.months.to_string(),
+        guess_time.days.to_string(),
+        language.map(|lang| value_name(&lang)).unwrap_or_default(),
+    ];
+
+    if let Some(pieces) = readout {
+        fields.push(tsv_field(&pieces.join(", ")));
+    }
+
+    fields.join("\t")
+}
+
+fn to_json(value: &toml::Value) -> Value {
+    match value {
+        toml::Value::String(str) => Value::from(str.as_str()),
+        toml::Value::Integer(number) => Value::from(*number),
+        toml::Value::Float(number) => json_number(*number),
+        toml::Value::Boolean(flag) => Value::from(*flag),
+        toml::Value::Datetime(datetime) => Value::from(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.iter().map(to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.clone(), to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+const MAX_SAFE_INTEGER: f64 = ((1u64 << 53) - 1) as f64;
+
+// JSON has no infinity nor NaN, e.g. guess times past f64 range, null stands for them;
+// whole numbers stay integers as read in the TSV output
+fn json_number(number: f64) -> Value {
+    if !number.is_finite() {
+        Value::Null
+    } else if number.fract() == 0.0 && number.abs() <= MAX_SAFE_INTEGER {
+        Value::from(number as i64)
+    } else {
+        Value::from(number)
+    }
+}
+
+// tabs & line breaks would corrupt the columns, escape them like linear TSV does
+fn tsv_field(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+impl ValueEnum for OutputFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Plain, Self::Json, Self::Tsv]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Plain => PossibleValue::new("plain").help("Only the password, one per line"),
+            Self::Json => PossibleValue::new("json")
+                .help("One JSON object per line with password, entropy, language & settings"),
+            Self::Tsv => PossibleValue::new("tsv").help(
+                "Tab-separated password, entropy & language columns, preceded by a header row",
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWDS: [(&str, Entropy); 2] = [
+        (
+            "foo.BAR.baz",
+            Entropy {
+                blind_min: 50.0,
+                blind_max: 60.0,
+                seen: 40.5,
+                attacker: Attacker::new(AttackerProfile::OnlineThrottled),
+                guess_time: GuessTime {
+                    seconds: 1.1e9,
+                    years: 34.0,
+                    months: 10,
+                    days: 15,
+                },
+            },
+        ),
+        (
+            "foo\t\"bar\"",
+            Entropy {
+                blind_min: 1.0,
+                blind_max: 2.0,
+                seen: 3.0,
+                attacker: Attacker::new(AttackerProfile::GpuCluster),
+                guess_time: GuessTime {
+                    seconds: 8e-12,
+                    years: 0.0,
+                    months: 0,
+                    days: 0,
+                },
+            },
+        ),
+    ];
+
+    fn passwds() -> Vec<(String, Entropy)> {
+        PASSWDS
+            .iter()
+            .map(|(passwd, entropy)| (passwd.to_string(), *entropy))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let output = render_passwds(
+            OutputFormat::Plain,
+            Some(Language::English),
+            &Settings::default(),
+            None,
+            &passwds(),
+        );
+        assert_eq!("foo.BAR.baz\nfoo\t\"bar\"", output);
+    }
+
+    #[test]
+    fn test_render_json() {
+        let settings = Settings::from_preset(Preset::Xkcd);
+        let output = render_passwds(
+            OutputFormat::Json,
+            Some(Language::German),
+            &settings,
+            None,
+            &passwds(),
+        );
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(2, lines.len());
+
+        let settings_json = concat!(
+            r#"{"ascii_fold":false,"digits_after":0,"digits_before":0,"padding":"fixed","separator_mode":"repeat","separators":"-","#,
+            r#""substitutions":[],"symbol_mode":"repeat","symbols":"","symbols_after":0,"symbols_before":0,"#,
+            r#""transforms":["lowercase","uppercase"],"unique_prefix":0,"word_max":8,"word_min":4,"#,
+            r#""words_count":4}"#
+        );
+
+        assert_eq!(
+            format!(
+                "{}{}{}{}",
+                r#"{"passwd":"foo.BAR.baz","entropy":{"blind_min":50,"blind_max":60,"seen":40.5,"#,
+                r#""guess_time":{"seconds":1100000000,"years":34,"months":10,"days":15},"#,
+                r#""attacker":"online-throttled","guesses_per_sec":1000},"#,
+                format_args!(r#""language":"de","settings":{}}}"#, settings_json),
+            ),
+            lines[0]
+        );
+        assert!(lines[1].starts_with(r#"{"passwd":"foo\t\"bar\"","#));
+
+        let output = render_passwds(OutputFormat::Json, None, &settings, None, &passwds());
+        assert!(output.contains(r#""language":null"#));
+    }
+
+    #[test]
+    fn test_render_json_adaptive_padding() {
+        let settings = Settings::from_preset(Preset::Wifi);
+        let output = render_passwds(OutputFormat::Json, None, &settings, None, &passwds()[..1]);
+        assert!(output.contains(r#""adaptive_length":63"#));
+        assert!(output.contains(r#""padding":"adaptive""#));
+    }
+
+    #[test]
+    fn test_render_tsv() {
+        let output = render_passwds(
+            OutputFormat::Tsv,
+            Some(Language::French),
+            &Settings::default(),
+            None,
+            &passwds(),
+        );
+        assert_eq!(
+            [
+                concat!(
+                    "passwd\tblind_min\tblind_max\tseen\tattacker\tguesses_per_sec\t",
+                    "guess_seconds\tguess_years\tguess_months\tguess_days\tlanguage"
+                ),
+                "foo.BAR.baz\t50\t60\t40.5\tonline-throttled\t1000\t1100000000\t34\t10\t15\tfr",
+                "foo\\t\"bar\"\t1\t2\t3\tgpu-cluster\t1000000000000\t0.000000000008\t0\t0\t0\tfr",
+            ]
+            .join("\n"),
+            output
+        );
+    }
+
+    #[test]
+    fn test_render_readout() {
+        let readout = Readout::for_language(Language::English);
+        let settings = Settings::default();
+
+        let output = render_passwds(
+            OutputFormat::Plain,
+            None,
+            &settings,
+            Some(&readout),
+            &passwds()[..1],
+        );
+        assert_eq!(
+            concat!(
+                "foo.BAR.baz\nfoxtrot, oscar, oscar, dot, capital bravo, capital alfa, ",
+                "capital romeo, dot, bravo, alfa, zulu"
+            ),
+            output
+        );
+
+        let output = render_passwds(
+            OutputFormat::Json,
+            None,
+            &settings,
+            Some(&readout),
+            &passwds()[1..],
+        );
+        assert!(output.starts_with(concat!(
+            r#"{"passwd":"foo\t\"bar\"","readout":["foxtrot","oscar","oscar","\t","#,
+            r#""double quote","bravo","alfa","romeo","double quote"],"entropy":{"#
+        )));
+
+        let output = render_passwds(
+            OutputFormat::Tsv,
+            None,
+            &settings,
+            Some(&readout),
+            &passwds()[1..],
+        );
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].ends_with("\tlanguage\treadout"));
+        assert!(lines[1].ends_with(
+            "\t\tfoxtrot, oscar, oscar, \\t, double quote, bravo, alfa, romeo, double quote"
+        ));
+    }
+
+    #[test]
+    fn test_render_response() {
+        let settings = Settings::from_preset(Preset::Xkcd);
+        let output = render_response(
+            Some(&json!("req-1")),
+            &passwds()[..1],
+            Some(Language::Spanish),
+            &settings,
+        );
+
+        assert!(!output.contains('\n'));
+        assert!(output.starts_with(concat!(
+            r#"{"id":"req-1","passwds":[{"passwd":"foo.BAR.baz","entropy":{"blind_min":50,"#,
+            r#""blind_max":60,"seen":40.5,"guess_time":{"seconds":1100000000,"#,
+        )));
+        assert!(output.contains(r#""guesses_per_sec":1000}}],"language":"es","settings":{"#));
+        assert!(output.ends_with(r#""word_min":4,"words_count":4}}"#));
+
+        let output = render_response(None, &passwds(), None, &settings);
+        assert!(output.starts_with(r#"{"passwds":[{"passwd":"foo.BAR.baz","#));
+        assert!(output.contains(r#"{"passwd":"foo\t\"bar\"","#));
+        assert!(output.contains(r#""language":null"#));
+    }
+
+    #[test]
+    fn test_render_error() {
+        assert_eq!(
+            r#"{"id":{"n":1},"error":"Invalid \"lang\""}"#,
+            render_error(Some(&json!({"n": 1})), r#"Invalid "lang""#)
+        );
+        assert_eq!(r#"{"error":"oops"}"#, render_error(None, "oops"));
+    }
+
+    #[test]
+    fn test_render_non_finite() {
+        let mut passwds = passwds();
+        passwds[0].1.guess_time.seconds = f64::INFINITY;
+        passwds[0].1.guess_time.years = f64::INFINITY;
+        passwds[1].1.seen = f64::NAN;
+
+        let settings = Settings::default();
+        let output = render_passwds(OutputFormat::Json, None, &settings, None, &passwds);
+        let lines: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(Value::Null, lines[0]["entropy"]["guess_time"]["seconds"]);
+        assert_eq!(Value::Null, lines[0]["entropy"]["guess_time"]["years"]);
+        assert_eq!(json!(10), lines[0]["entropy"]["guess_time"]["months"]);
+        assert_eq!(Value::Null, lines[1]["entropy"]["seen"]);
+        assert_eq!(json!(8e-12), lines[1]["entropy"]["guess_time"]["seconds"]);
+
+        let output = render_response(Some(&json!(7)), &passwds, None, &settings);
+        let response: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json!(7), response["id"]);
+        assert_eq!(
+            Value::Null,
+            response["passwds"][0]["entropy"]["guess_time"]["seconds"]
+        );
+        assert_eq!(json!("foo\t\"bar\""), response["passwds"][1]["passwd"]);
+    }
+
+    #[test]
+    fn test_render_escaped() {
+        assert_eq!(r#"{"error":"foo"}"#, render_error(None, "foo"));
+        assert_eq!(r#"{"error":"a\"b\\c"}"#, render_error(None, r#"a"b\c"#));
+        assert_eq!(
+            r#"{"error":"\n\r\t\u0001"}"#,
+            render_error(None, "\n\r\t\u{1}")
+        );
+        assert_eq!(r#"{"error":"⟪é⟫"}"#, render_error(None, "⟪é⟫"));
+    }
+}
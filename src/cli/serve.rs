@@ -0,0 +1,577 @@
+use super::*;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum DictKey {
+    Language(Language),
+    File(String),
+}
+
+// the same dictionary with other excluded words is another generator
+type GeneratorKey = (DictKey, Option<String>);
+
+// passwords a single request may ask for
+const MAX_COUNT: u32 = 1000;
+
+// requests naming many dictionary or excluded words files would otherwise keep them all loaded
+const MAX_CACHED_GENERATORS: usize = 16;
+
+// state kept across requests, the startup options act as defaults for every request
+struct Server {
+    base_config: toml::Table,
+    settings_keys: Vec<String>,
+    generators: HashMap<GeneratorKey, Xkpasswd>,
+}
+
+impl Cli {
+    /// Answers every JSON line read from `input` with a JSON line on `output` until EOF.
+    /// Requests override startup options with `xkpasswd.toml` keys,
+    /// dictionaries are loaded once then reused by later requests.
+    pub fn serve<R: BufRead, W: Write>(
+        &self,
+        settings: &Settings,
+        policy: Option<&PasswordPolicy>,
+        attacker: Option<&Attacker>,
+        input: R,
+        mut output: W,
+    ) -> io::Result<()> {
+        let language = match self.dict_file {
+            Some(_) => None,
+            None => Some(self.language()),
+        };
+
+        let mut server = Server {
+            base_config: options_to_config(
+                settings,
+                policy,
+                attacker,
+                language,
+                self.dict_file.as_deref(),
+                self.exclude_words.as_deref(),
+                None,
+            ),
+            settings_keys: settings_to_config(settings).keys().cloned().collect(),
+            generators: HashMap::new(),
+        };
+
+        // user presets stay selectable by requests
+        if let Some(presets) = &self.presets {
+            server
+                .base_config
+                .insert("presets".into(), presets.clone().into());
+        }
+
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            writeln!(output, "{}", server.answer(&line))?;
+            output.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Server {
+    fn answer(&mut self, line: &str) -> String {
+        let request: serde_json::Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(err) => return render_error(None, &format!("Invalid request: {}", err)),
+        };
+
+        let id = request.get("id");
+
+        match self.answer_request(&request, id) {
+            Ok(response) => response,
+            Err(message) => render_error(id, &message),
+        }
+    }
+
+    fn answer_request(
+        &mut self,
+        request: &serde_json::Value,
+        id: Option<&serde_json::Value>,
+    ) -> Result<String, String> {
+        let fields = request
+            .as_object()
+            .ok_or("Invalid request: expected a JSON object")?;
+
+        let mut count = 1;
+        let mut overrides: Vec<(&str, Option<toml::Value>)> = vec![];
+
+        for (key, value) in fields {
+            match key.as_str() {
+                "id" => {}
+                "count" => {
+                    count = value
+                        .as_u64()
+                        .and_then(|count| u32::try_from(count).ok())
+                        .filter(|count| (1..=MAX_COUNT).contains(count))
+                        .ok_or_else(|| {
+                            format!(
+                                "Invalid request: count must be from 1 to {}, got {}",
+                                MAX_COUNT, value
+                            )
+                        })?;
+                }
+                _ => {
+                    let value = json_to_toml(value);
+                    check_config_value(key, value.as_ref())
+                        .map_err(|err| format!("Invalid request: {}", err))?;
+
+                    overrides.push((key, value));
+                }
+            }
+        }
+
+        let mut cli = Cli::default();
+        let config = self.merge_config(&overrides);
+        if let Err(ConfigParseError::Invalid(err)) = cli.load_config(&toml::Value::Table(config)) {
+            return Err(format!("Invalid settings: {}", err));
+        }
+
+        let settings: Settings = cli
+            .build_settings()
+            .map_err(|err| format!("Invalid settings: {}", err))?;
+        let policy = cli
+            .build_policy()
+            .map_err(|err| format!("Invalid policy: {}", err))?;
+        let attacker = cli
+            .build_attacker()
+            .map_err(|err| format!("Invalid attacker: {}", err))?
+            .unwrap_or_default();
+
+        let (key, language) = match &cli.dict_file {
+            Some(dict_file) => (DictKey::File(dict_file.clone()), None),
+            None => (DictKey::Language(cli.language()), Some(cli.language())),
+        };
+
+        let key = (key, cli.exclude_words.clone());
+        if self.generators.len() >= MAX_CACHED_GENERATORS && !self.generators.contains_key(&key) {
+            self.generators.clear();
+        }
+
+        let pass_generator = match self.generators.entry(key) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(
+                cli.try_pass_generator()
+                    .map_err(|err| format!("Invalid dictionary: {}", err))?,
+            ),
+        };
+
+        let passwds = match &policy {
+            Some(policy) => pass_generator.gen_passes_matching(&settings, policy, count as usize),
+            None => pass_generator.gen_passes(&settings, count as usize),
+        }
+        .map_err(|err| format!("Unable to generate password: {}", err))?;
+
+        let passwds: Vec<(String, Entropy)> = passwds
+            .into_iter()
+            .map(|(passwd, entropy)| (passwd, entropy.with_attacker(attacker)))
+            .collect();
+
+        Ok(render_response(id, &passwds, language, &settings))
+    }
+
+    // null values unset startup options, falling back to defaults
+    fn merge_config(&self, overrides: &[(&str, Option<toml::Value>)]) -> toml::Table {
+        let mut config = self.base_config.clone();
+        let overridden = |key: &str| overrides.iter().any(|(field, _)| *field == key);
+
+        // a preset replaces all startup settings, not only the overridden ones
+        if overridden("preset") {
+            config.retain(|key, _| !self.settings_keys.contains(&key.to_string()));
+        }
+
+        // a custom dictionary wins over the language, only keep the requested one
+        if overridden("lang") {
+            config.remove("dict");
+        }
+
+        // the speed of a requested profile shouldn't be the startup one
+        if overridden("attacker") {
+            config.remove("guesses_per_sec");
+        }
+
+        for (key, value) in overrides {
+            match value {
+                Some(value) => config.insert(key.to_string(), value.clone()),
+                None => config.remove(*key),
+            };
+        }
+
+        config
+    }
+}
+
+fn json_to_toml(value: &serde_json::Value) -> Option<toml::Value> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::Bool(value) => Some((*value).into()),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(number) => Some(number.into()),
+            None => number.as_f64().map(toml::Value::from),
+        },
+        serde_json::Value::String(value) => Some(value.as_str().into()),
+        serde_json::Value::Array(values) => Some(toml::Value::Array(
+            values.iter().filter_map(json_to_toml).collect(),
+        )),
+        serde_json::Value::Object(fields) => Some(toml::Value::Table(
+            fields
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), json_to_toml(value)?)))
+                .collect(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "lang_en")]
+    fn serve_lines(cli: &Cli, settings: &Settings, input: &str) -> Vec<serde_json::Value> {
+        let mut output: Vec<u8> = vec![];
+        cli.serve(settings, None, None, io::Cursor::new(input), &mut output)
+            .unwrap();
+
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[cfg(feature = "lang_en")]
+    fn passwds(response: &serde_json::Value) -> Vec<&str> {
+        response["passwds"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|passwd| passwd["passwd"].as_str().unwrap())
+            .collect()
+    }
+
+    #[cfg(all(feature = "lang_en", feature = "lang_fr"))]
+    #[test]
+    fn test_serve() {
+        let cli = Cli::default();
+        let settings = Settings::default()
+            .with_words_count(2)
+            .unwrap()
+            .with_separators("-")
+            .with_padding_digits(Some(0), Some(0))
+            .with_padding_symbol_lengths(Some(0), Some(0));
+
+        let input = [
+            r#"{"id": 1}"#,
+            "",
+            r#"{"id": "two", "count": 3, "words_count": 3}"#,
+            r#"{"separators": "+", "lang": "fr"}"#,
+            r#"{"preset": "xkcd", "separators": "."}"#,
+        ]
+        .join("\n");
+
+        let responses = serve_lines(&cli, &settings, &input);
+        // blank lines are skipped
+        assert_eq!(4, responses.len());
+
+        // startup settings as defaults
+        assert_eq!(1, responses[0]["id"]);
+        assert_eq!("en", responses[0]["language"]);
+        assert_eq!(1, passwds(&responses[0]).len());
+        assert_eq!(2, passwds(&responses[0])[0].split('-').count());
+        assert_eq!(
+            "online-throttled",
+            responses[0]["passwds"][0]["entropy"]["attacker"]
+        );
+
+        assert_eq!("two", responses[1]["id"]);
+        assert_eq!(3, responses[1]["settings"]["words_count"]);
+        assert_eq!(3, passwds(&responses[1]).len());
+        for passwd in passwds(&responses[1]) {
+            assert_eq!(3, passwd.split('-').count());
+        }
+
+        assert!(responses[2].get("id").is_none());
+        assert_eq!("fr", responses[2]["language"]);
+        assert_eq!(2, passwds(&responses[2])[0].split('+').count());
+
+        // presets replace startup settings, except the overridden ones
+        let xkcd_config = settings_to_config(&Settings::from_preset(Preset::Xkcd));
+        assert_eq!(".", responses[3]["settings"]["separators"]);
+        assert_eq!(
+            xkcd_config["words_count"].as_integer(),
+            responses[3]["settings"]["words_count"].as_i64()
+        );
+    }
+
+    #[cfg(feature = "lang_en")]
+    #[test]
+    fn test_serve_bounds_generators() {
+        let mut server = Server {
+            base_config: options_to_config(
+                &Settings::default(),
+                None,
+                None,
+                Some(Language::English),
+                None,
+                None,
+                None,
+            ),
+            settings_keys: vec![],
+            generators: HashMap::new(),
+        };
+
+        let blocklists: Vec<tempfile::NamedTempFile> = (0..=MAX_CACHED_GENERATORS)
+            .map(|_| tempfile::NamedTempFile::new().unwrap())
+            .collect();
+
+        for blocklist in &blocklists {
+            let request = format!(r#"{{"exclude_words": {:?}}}"#, blocklist.path());
+            assert!(!server.answer(&request).contains("error"));
+            assert!(server.generators.len() <= MAX_CACHED_GENERATORS);
+        }
+
+        assert_eq!(1, server.generators.len());
+    }
+
+    #[cfg(feature = "lang_en")]
+    #[test]
+    fn test_serve_user_preset() {
+        let presets: toml::Table = toml::from_str(
+            r#"
+[mine]
+extends = "xkcd"
+separators = "+"
+"#,
+        )
+        .unwrap();
+        let cli = Cli {
+            presets: Some(presets),
+            ..Cli::default()
+        };
+
+        let input = [
+            r#"{"preset": "mine"}"#,
+            r#"{"preset": "mine", "words_count": 2}"#,
+            r#"{"preset": "other"}"#,
+        ]
+        .join("\n");
+
+        let responses = serve_lines(&cli, &Settings::default(), &input);
+        assert_eq!(3, responses.len());
+
+        let xkcd_config = settings_to_config(&Settings::from_preset(Preset::Xkcd));
+        assert_eq!("+", responses[0]["settings"]["separators"]);
+        assert_eq!(
+            xkcd_config["words_count"].as_integer(),
+            responses[0]["settings"]["words_count"].as_i64()
+        );
+
+        assert_eq!(2, responses[1]["settings"]["words_count"]);
+        assert_eq!(2, passwds(&responses[1])[0].split('+').count());
+
+        assert_eq!(
+            "Invalid settings: unknown preset other, neither built-in nor defined as [presets.other] in xkpasswd.toml",
+            responses[2]["error"]
+        );
+    }
+
+    #[cfg(all(feature = "lang_en", feature = "lang_de"))]
+    #[test]
+    fn test_serve_reuses_generators() {
+        let mut server = Server {
+            base_config: options_to_config(
+                &Settings::default(),
+                None,
+                None,
+                Some(Language::English),
+                None,
+                None,
+                None,
+            ),
+            settings_keys: vec![],
+            generators: HashMap::new(),
+        };
+
+        let mut blocklist = tempfile::NamedTempFile::new().unwrap();
+        writeln!(blocklist, "house").unwrap();
+        let blocklist = blocklist.path().to_str().unwrap().to_string();
+
+        server.answer(r#"{"lang": "de"}"#);
+        server.answer(r#"{"lang": "de", "count": 2}"#);
+        server.answer(r#"{}"#);
+        server.answer(&format!(r#"{{"exclude_words": {:?}}}"#, blocklist));
+
+        let mut keys: Vec<&GeneratorKey> = server.generators.keys().collect();
+        keys.sort_by_key(|key| format!("{:?}", key));
+        assert_eq!(
+            vec![
+                &(DictKey::Language(Language::English), None),
+                &(
+                    DictKey::Language(Language::English),
+                    Some(blocklist.clone())
+                ),
+                &(DictKey::Language(Language::German), None),
+            ],
+            keys
+        );
+    }
+
+    #[cfg(feature = "lang_en")]
+    #[test]
+    fn test_serve_errors() {
+        let cli = Cli::default();
+        let input = [
+            "not json",
+            "[1, 2]",
+            r#"{"id": 7, "count": 0}"#,
+            r#"{"id": 8, "words_count": 0}"#,
+            r#"{"id": 9, "policy_min_length": 100, "policy_max_length": 10}"#,
+            r#"{"id": 10, "dict": "/no/such/dict.txt"}"#,
+            r#"{"id": 11, "lang": "klingon"}"#,
+            r#"{"id": 12, "padding": "exact", "adaptive_length": 8}"#,
+            r#"{"id": 13, "word_count": 9}"#,
+            r#"{"id": 14, "words_count": "x"}"#,
+            r#"{"id": 15, "transforms": ["lowercase", 1]}"#,
+            r#"{"id": 16, "count": 1001}"#,
+            r#"{"id": 18, "words_count": 257}"#,
+            r#"{"id": 19, "word_min": 260}"#,
+            r#"{"id": 17, "words_count": null}"#,
+        ]
+        .join("\n");
+
+        let responses = serve_lines(&cli, &Settings::default(), &input);
+        assert_eq!(15, responses.len());
+
+        let errors: Vec<(Option<i64>, &str)> = responses[..14]
+            .iter()
+            .map(|response| (response["id"].as_i64(), response["error"].as_str().unwrap()))
+            .collect();
+
+        assert_eq!(None, errors[0].0);
+        assert!(errors[0].1.starts_with("Invalid request: expected ident"));
+        assert_eq!((None, "Invalid request: expected a JSON object"), errors[1]);
+        assert_eq!(
+            (
+                Some(7),
+                "Invalid request: count must be from 1 to 1000, got 0"
+            ),
+            errors[2]
+        );
+        assert_eq!(
+            (
+                Some(8),
+                "Invalid settings: words count must be 1 or higher, got 0"
+            ),
+            errors[3]
+        );
+        assert_eq!(Some(9), errors[4].0);
+        assert!(errors[4].1.starts_with("Invalid policy: policy max length"));
+        assert_eq!(Some(10), errors[5].0);
+        assert!(errors[5].1.starts_with("Invalid dictionary:"));
+        assert_eq!(Some(11), errors[6].0);
+        assert!(errors[6].1.starts_with("Invalid settings: lang"));
+        assert_eq!(Some(12), errors[7].0);
+        assert!(errors[7]
+            .1
+            .starts_with("Unable to generate password: no 3 word(s)"));
+
+        assert_eq!(
+            (Some(13), "Invalid request: word_count: unknown key"),
+            errors[8]
+        );
+        assert_eq!(
+            (
+                Some(14),
+                r#"Invalid request: words_count: expected an integer from 0 to 255, got "x""#
+            ),
+            errors[9]
+        );
+        assert_eq!(
+            (
+                Some(15),
+                r#"Invalid request: transforms: expected an array of strings, got ["lowercase", 1]"#
+            ),
+            errors[10]
+        );
+        assert_eq!(
+            (
+                Some(16),
+                "Invalid request: count must be from 1 to 1000, got 1001"
+            ),
+            errors[11]
+        );
+
+        // out of range rather than truncated
+        assert_eq!(
+            (
+                Some(18),
+                "Invalid request: words_count: expected an integer from 0 to 255, got 257"
+            ),
+            errors[12]
+        );
+        assert_eq!(
+            (
+                Some(19),
+                "Invalid request: word_min: expected an integer from 0 to 255, got 260"
+            ),
+            errors[13]
+        );
+
+        // still serving after errors
+        assert_eq!(17, responses[14]["id"]);
+        assert_eq!(1, passwds(&responses[14]).len());
+    }
+
+    #[test]
+    fn test_merge_config() {
+        let settings = Settings::default();
+        let attacker = Attacker::new(AttackerProfile::GpuCluster);
+        let server = Server {
+            base_config: options_to_config(
+                &settings,
+                None,
+                Some(&attacker),
+                None,
+                Some("words.txt"),
+                None,
+                None,
+            ),
+            settings_keys: settings_to_config(&settings).keys().cloned().collect(),
+            generators: HashMap::new(),
+        };
+
+        let config = server.merge_config(&[
+            ("preset", Some("wifi".into())),
+            ("symbols", Some("!".into())),
+            ("lang", Some("es".into())),
+            ("attacker", Some("offline-slow-hash".into())),
+            ("dict", None),
+        ]);
+
+        let mut keys: Vec<&str> = config.keys().map(|key| key.as_str()).collect();
+        keys.sort();
+        assert_eq!(vec!["attacker", "lang", "preset", "symbols"], keys);
+        assert_eq!(Some("offline-slow-hash"), config["attacker"].as_str());
+    }
+
+    #[test]
+    fn test_json_to_toml() {
+        let json: serde_json::Value = serde_json::from_str(
+            r#"{"a": null, "b": true, "c": -3, "d": 1.5, "e": "x", "f": [1, null], "g": {"h": null}}"#,
+        )
+        .unwrap();
+
+        let expected: toml::Value =
+            toml::from_str("b = true\nc = -3\nd = 1.5\ne = \"x\"\nf = [1]\ng = {}").unwrap();
+        assert_eq!(Some(expected), json_to_toml(&json));
+        assert_eq!(None, json_to_toml(&serde_json::Value::Null));
+    }
+}
@@ -6,18 +6,43 @@ const DEFAULT_CLI: Cli = Cli {
     word_length_min: None,
     word_length_max: None,
     word_transforms: None,
+    ascii_fold: None,
+    unique_prefix: None,
     separators: None,
+    separator_mode: None,
     padding_digits_before: None,
     padding_digits_after: None,
     padding_symbols: None,
     padding_symbols_before: None,
     padding_symbols_after: None,
+    symbol_mode: None,
     padding: None,
     adaptive_length: None,
+    substitutions: None,
+    min_length: None,
+    max_length: None,
     preset: None,
+    presets: None,
     verbosity: 0,
     language: None,
+    dict_file: None,
+    exclude_words: None,
     config_file: None,
+    count: None,
+    format: None,
+    readout: false,
+    policy_min_length: None,
+    policy_max_length: None,
+    policy_require: None,
+    policy_forbid: None,
+    policy_max_repeat: None,
+    policy_attempts: None,
+    attacker: None,
+    guesses_per_sec: None,
+    serve_stdin: false,
+    seed: None,
+    dump_config: false,
+    command: None,
 };
 
 #[test]
@@ -51,7 +76,7 @@ fn test_build_settings_from_preset() {
 
     for preset in presets {
         let cli = Cli {
-            preset: Some(preset),
+            preset: Some(CliPreset::BuiltIn(preset)),
             ..DEFAULT_CLI
         };
 
@@ -60,6 +85,23 @@ fn test_build_settings_from_preset() {
     }
 }
 
+#[test]
+fn test_build_settings_user_preset() {
+    let cli = Cli::try_parse_from(["xkpasswd", "-P", "XKCD"]).unwrap();
+    assert!(matches!(cli.preset, Some(CliPreset::BuiltIn(Preset::Xkcd))));
+
+    // names out of the built-in ones are resolved from xkpasswd.toml
+    let cli = Cli::try_parse_from(["xkpasswd", "--preset", "mine"]).unwrap();
+    assert!(matches!(&cli.preset, Some(CliPreset::Custom(name)) if name == "mine"));
+
+    let err = cli.build_settings::<Settings>().err().unwrap();
+    assert_eq!("preset", err.field());
+    assert_eq!(
+        "unknown preset mine, neither built-in nor defined as [presets.mine] in xkpasswd.toml",
+        err.to_string()
+    );
+}
+
 #[test]
 fn test_build_settings_custom() {
     let cli = Cli {
@@ -70,14 +112,21 @@ fn test_build_settings_custom() {
             WordTransform::Lowercase,
             WordTransform::InversedTitlecase,
         ]),
+        ascii_fold: Some(true),
+        unique_prefix: Some(3),
         separators: Some("~@#".to_string()),
+        separator_mode: Some(SeparatorMode::Independent),
         padding_digits_before: Some(1),
         padding_digits_after: Some(3),
         padding_symbols: Some("$%^".to_string()),
         padding_symbols_before: Some(3),
         padding_symbols_after: Some(1),
+        symbol_mode: Some(SymbolMode::Independent),
         padding: Some(CliPadding::Adaptive),
         adaptive_length: Some(17),
+        substitutions: Some(vec![" =_".to_string(), "~=".to_string()]),
+        min_length: Some(16),
+        max_length: Some(24),
         ..DEFAULT_CLI
     };
 
@@ -88,11 +137,19 @@ fn test_build_settings_custom() {
         .unwrap()
         .with_word_transforms(WordTransform::Lowercase | WordTransform::InversedTitlecase)
         .unwrap()
+        .with_ascii_fold(true)
+        .with_unique_prefix(3)
         .with_separators("~@#")
+        .with_separator_mode(SeparatorMode::Independent)
         .with_padding_digits(Some(1), Some(3))
         .with_padding_symbols("$%^")
         .with_padding_symbol_lengths(Some(3), Some(1))
+        .with_symbol_mode(SymbolMode::Independent)
         .with_padding_strategy(PaddingStrategy::Adaptive(17))
+        .unwrap()
+        .with_substitutions(&[(' ', "_".to_string()), ('~', "".to_string())])
+        .unwrap()
+        .with_length_range(Some(16), Some(24))
         .unwrap();
 
     assert_eq!(expected_settings, cli.build_settings::<Settings>().unwrap());
@@ -104,7 +161,7 @@ fn test_language_value_enum() {
 
     // Test value_variants returns all languages
     let variants = Language::value_variants();
-    assert_eq!(5, variants.len());
+    assert_eq!(9, variants.len());
 
     // Test to_possible_value for each language
     let test_cases = [
@@ -113,6 +170,10 @@ fn test_language_value_enum() {
         (Language::German, "de"),
         (Language::Portuguese, "pt"),
         (Language::Spanish, "es"),
+        (Language::FrenchNative, "fr-native"),
+        (Language::GermanNative, "de-native"),
+        (Language::PortugueseNative, "pt-native"),
+        (Language::SpanishNative, "es-native"),
     ];
 
     for (language, expected_name) in test_cases {
@@ -134,3 +195,293 @@ fn test_cli_language_default() {
     };
     assert_eq!(Language::French, cli.language());
 }
+
+#[test]
+fn test_cli_count_default() {
+    assert_eq!(1, DEFAULT_CLI.count());
+
+    let cli = Cli {
+        count: Some(5),
+        ..DEFAULT_CLI
+    };
+    assert_eq!(5, cli.count());
+}
+
+#[test]
+fn test_render_passwds_readout() {
+    let passwds = [("Haus-42".to_string(), Entropy::default())];
+    let settings = Settings::default();
+    assert_eq!("Haus-42", DEFAULT_CLI.render_passwds(&settings, &passwds));
+
+    let cli = Cli {
+        readout: true,
+        language: Some(Language::German),
+        ..DEFAULT_CLI
+    };
+    assert_eq!(
+        "Haus-42\nHaus (erster Buchstabe groß), Bindestrich, vier, zwei",
+        cli.render_passwds(&settings, &passwds)
+    );
+}
+
+#[test]
+fn test_build_settings_exact_padding() {
+    let cli =
+        Cli::try_parse_from(["xkpasswd", "--padding", "exact", "--adaptive-length", "24"]).unwrap();
+
+    let expected_settings = Settings::default()
+        .with_padding_strategy(PaddingStrategy::Exact(24))
+        .unwrap();
+    assert_eq!(expected_settings, cli.build_settings::<Settings>().unwrap());
+}
+
+#[test]
+fn test_build_settings_errors() {
+    let cli = Cli {
+        word_length_min: Some(2),
+        ..DEFAULT_CLI
+    };
+    let err = cli.build_settings::<Settings>().err().unwrap();
+    assert_eq!("word_min", err.field());
+
+    let cli = Cli {
+        padding: Some(CliPadding::Adaptive),
+        ..DEFAULT_CLI
+    };
+    let err = cli.build_settings::<Settings>().err().unwrap();
+    assert_eq!("adaptive_length", err.field());
+    assert_eq!(
+        "adaptive padding length is required for adaptive & exact padding strategies",
+        err.to_string()
+    );
+
+    let cli = Cli {
+        padding: Some(CliPadding::Exact),
+        ..DEFAULT_CLI
+    };
+    let err = cli.build_settings::<Settings>().err().unwrap();
+    assert_eq!("adaptive_length", err.field());
+
+    let cli = Cli {
+        min_length: Some(24),
+        max_length: Some(16),
+        ..DEFAULT_CLI
+    };
+    let err = cli.build_settings::<Settings>().err().unwrap();
+    assert_eq!("max length must be 24 or higher, got 16", err.to_string());
+
+    let cli = Cli {
+        substitutions: Some(vec!["||".to_string()]),
+        ..DEFAULT_CLI
+    };
+    let err = cli.build_settings::<Settings>().err().unwrap();
+    assert_eq!("substitutions", err.field());
+
+    let cli = Cli {
+        substitutions: Some(vec!["|=!".to_string(), "|=?".to_string()]),
+        ..DEFAULT_CLI
+    };
+    let err = cli.build_settings::<Settings>().err().unwrap();
+    assert_eq!(
+        "invalid substitution '|': char substituted more than once",
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_build_policy() {
+    // no rule, no policy
+    assert_eq!(Ok(None), DEFAULT_CLI.build_policy());
+
+    // attempts alone isn't a rule
+    let cli = Cli {
+        policy_attempts: Some(10),
+        ..DEFAULT_CLI
+    };
+    assert_eq!(Ok(None), cli.build_policy());
+
+    let cli = Cli {
+        policy_min_length: Some(12),
+        policy_max_length: Some(24),
+        policy_require: Some(vec![CharClass::Digit, CharClass::Uppercase]),
+        policy_forbid: Some("~'".to_string()),
+        policy_max_repeat: Some(2),
+        policy_attempts: Some(10),
+        ..DEFAULT_CLI
+    };
+    let expected = PasswordPolicy::default()
+        .with_lengths(Some(12), Some(24))
+        .unwrap()
+        .with_required_classes(&[CharClass::Digit, CharClass::Uppercase])
+        .with_forbidden_chars("~'")
+        .with_max_repeated_chars(2)
+        .unwrap()
+        .with_max_attempts(10)
+        .unwrap();
+    assert_eq!(Ok(Some(expected)), cli.build_policy());
+
+    let cli = Cli {
+        policy_min_length: Some(12),
+        policy_max_length: Some(8),
+        ..DEFAULT_CLI
+    };
+    let err = cli.build_policy().err().unwrap();
+    assert_eq!("policy_max_length", err.field());
+
+    let cli = Cli {
+        policy_forbid: Some("-".to_string()),
+        policy_attempts: Some(0),
+        ..DEFAULT_CLI
+    };
+    let err = cli.build_policy().err().unwrap();
+    assert_eq!("policy_attempts", err.field());
+}
+
+#[test]
+fn test_build_constraints() {
+    // no subcommand, no constraints
+    assert_eq!(Ok(None), DEFAULT_CLI.build_constraints());
+
+    let cli = Cli::try_parse_from(["xkpasswd", "optimize"]).unwrap();
+    assert_eq!(Ok(Some(Constraints::default())), cli.build_constraints());
+
+    let cli = Cli::try_parse_from([
+        "xkpasswd",
+        "--lang",
+        "fr",
+        "optimize",
+        "--min-length",
+        "12",
+        "--max-length",
+        "20",
+        "--require",
+        "digit",
+        "--require",
+        "uppercase",
+        "--symbols",
+        "!#",
+    ])
+    .unwrap();
+    let expected = Constraints::default()
+        .with_lengths(Some(12), Some(20))
+        .unwrap()
+        .with_required_classes(&[CharClass::Digit, CharClass::Uppercase])
+        .with_symbols("!#")
+        .unwrap();
+    assert_eq!(Language::French, cli.language());
+    assert_eq!(Ok(Some(expected)), cli.build_constraints());
+
+    let cli = Cli::try_parse_from(["xkpasswd", "optimize", "--min-length", "40"]).unwrap();
+    let err = cli.build_constraints().err().unwrap();
+    assert_eq!("max_length", err.field());
+
+    let cli = Cli::try_parse_from(["xkpasswd", "optimize", "--symbols", "!a"]).unwrap();
+    let err = cli.build_constraints().err().unwrap();
+    assert_eq!("symbols", err.field());
+}
+
+#[test]
+fn test_render_optimized() {
+    let settings = Settings::default();
+    let entropy = Xkpasswd::default().calc_entropy(&settings);
+
+    let rendered = DEFAULT_CLI.render_optimized(&settings, &entropy);
+    let (comment, config) = rendered.split_once('\n').unwrap();
+
+    assert_eq!(format!("# {}", entropy), comment);
+    assert_eq!(DEFAULT_CLI.render_config(&settings, None, None), config);
+}
+
+#[test]
+fn test_char_class_value_enum() {
+    let table = [
+        (CharClass::Lowercase, "lowercase"),
+        (CharClass::Uppercase, "uppercase"),
+        (CharClass::Digit, "digit"),
+        (CharClass::Symbol, "symbol"),
+    ];
+
+    assert_eq!(table.len(), CharClass::value_variants().len());
+
+    for (class, name) in table {
+        assert_eq!(name, value_name(&class));
+        assert_eq!(Ok(class), CharClass::from_str(name, true));
+    }
+}
+
+#[test]
+fn test_build_attacker() {
+    // default attacker unless set
+    assert_eq!(Ok(None), DEFAULT_CLI.build_attacker());
+
+    let cli = Cli {
+        attacker: Some(AttackerProfile::OfflineFastHash),
+        ..DEFAULT_CLI
+    };
+    assert_eq!(
+        Ok(Some(Attacker::new(AttackerProfile::OfflineFastHash))),
+        cli.build_attacker()
+    );
+
+    // speed alone overrides the default profile one
+    let cli = Cli {
+        guesses_per_sec: Some(50),
+        ..DEFAULT_CLI
+    };
+    let expected = Attacker::default().with_guesses_per_sec(50.0).unwrap();
+    assert_eq!(Ok(Some(expected)), cli.build_attacker());
+}
+
+#[test]
+fn test_attacker_profile_value_enum() {
+    let table = [
+        (AttackerProfile::OnlineThrottled, "online-throttled"),
+        (AttackerProfile::OfflineSlowHash, "offline-slow-hash"),
+        (AttackerProfile::OfflineFastHash, "offline-fast-hash"),
+        (AttackerProfile::GpuCluster, "gpu-cluster"),
+    ];
+
+    assert_eq!(table.len(), AttackerProfile::value_variants().len());
+
+    for (profile, name) in table {
+        assert_eq!(name, value_name(&profile));
+        assert_eq!(Ok(profile), AttackerProfile::from_str(name, true));
+    }
+}
+
+#[test]
+fn test_try_pass_generator_exclude_words() {
+    use std::io::Write;
+
+    let mut dict_file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(dict_file, "correct\nhorse\nbattery\nstaple").unwrap();
+    let mut blocklist = tempfile::NamedTempFile::new().unwrap();
+    writeln!(blocklist, "horse\nbattery\nstaple").unwrap();
+
+    let cli = Cli {
+        dict_file: Some(dict_file.path().to_str().unwrap().to_string()),
+        exclude_words: Some(blocklist.path().to_str().unwrap().to_string()),
+        ..DEFAULT_CLI
+    };
+
+    let settings = Settings::default()
+        .with_words_count(2)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Lowercase))
+        .unwrap()
+        .with_separators(" ")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbol_lengths(Some(0), Some(0));
+    let (passwd, _) = cli
+        .try_pass_generator()
+        .unwrap()
+        .gen_pass(&settings)
+        .unwrap();
+    assert_eq!("correct correct", passwd);
+
+    let cli = Cli {
+        exclude_words: Some("/nonexistent/path/to/blocklist.txt".to_string()),
+        ..cli
+    };
+    assert!(cli.try_pass_generator().is_err());
+}
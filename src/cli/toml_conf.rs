@@ -1,5 +1,7 @@
 use super::*;
 use crate::bit_flags::*;
+use crate::policy::PasswordPolicy;
+use crate::settings::Settings;
 use clap::ValueEnum;
 use std::fs;
 
@@ -8,96 +10,633 @@ const CONFIG_FILE_NAME: &str = "xkpasswd.toml";
 #[derive(Debug)]
 pub enum ConfigParseError {
     Ignore,
-    InvalidFile(String),
-    InvalidConfig(String, String),
+    Invalid(XkpasswdError),
+}
+
+impl ConfigParseError {
+    fn invalid_file(message: String) -> Self {
+        Self::Invalid(XkpasswdError::ConfigFile(message))
+    }
+
+    fn invalid_config(field: &str, message: String) -> Self {
+        Self::Invalid(XkpasswdError::Config {
+            field: field.to_string(),
+            message,
+        })
+    }
 }
 
 pub trait ConfigParser {
     fn parse_config_file(&mut self) -> Result<(), ConfigParseError>;
+    fn load_config(&mut self, config: &toml::Value) -> Result<(), ConfigParseError>;
 }
 
 impl ConfigParser for Cli {
     fn parse_config_file(&mut self) -> Result<(), ConfigParseError> {
         let config = read_config_file(&self.config_file)?;
+        self.load_config(&config)
+    }
 
-        parse_number_config(
-            self.words_count.is_some(),
-            &config,
-            "words_count",
-            |value| self.words_count = Some(value as u8),
-        );
+    // fields already set, e.g. from command arguments, take precedence
+    fn load_config(&mut self, config: &toml::Value) -> Result<(), ConfigParseError> {
+        parse_number_config(self.words_count.is_some(), config, "words_count", |value| {
+            self.words_count = Some(value)
+        })?;
 
         parse_number_config(
             self.word_length_min.is_some(),
-            &config,
+            config,
             "word_min",
-            |value| self.word_length_min = Some(value as u8),
-        );
+            |value| self.word_length_min = Some(value),
+        )?;
 
         parse_number_config(
             self.word_length_max.is_some(),
-            &config,
+            config,
             "word_max",
-            |value| self.word_length_max = Some(value as u8),
-        );
+            |value| self.word_length_max = Some(value),
+        )?;
+
+        parse_enum_arr_config(
+            self.word_transforms.is_some(),
+            config,
+            "transforms",
+            |transforms| self.word_transforms = Some(transforms),
+        )?;
+
+        parse_bool_config(self.ascii_fold.is_some(), config, "ascii_fold", |value| {
+            self.ascii_fold = Some(value)
+        });
 
-        parse_transforms(self.word_transforms.is_some(), &config, |transforms| {
-            self.word_transforms = Some(transforms)
-        })?;
+        parse_number_config(
+            self.unique_prefix.is_some(),
+            config,
+            "unique_prefix",
+            |value| self.unique_prefix = Some(value),
+        )?;
 
-        parse_str_config(self.separators.is_some(), &config, "separators", |value| {
+        parse_str_config(self.separators.is_some(), config, "separators", |value| {
             self.separators = Some(value)
         });
 
+        parse_enum_config(
+            self.separator_mode.is_some(),
+            config,
+            "separator_mode",
+            |value| self.separator_mode = Some(value),
+        )?;
+
         parse_number_config(
             self.padding_digits_before.is_some(),
-            &config,
+            config,
             "digits_before",
-            |value| self.padding_digits_before = Some(value as u8),
-        );
+            |value| self.padding_digits_before = Some(value),
+        )?;
 
         parse_number_config(
             self.padding_digits_after.is_some(),
-            &config,
+            config,
             "digits_after",
-            |value| self.padding_digits_after = Some(value as u8),
-        );
+            |value| self.padding_digits_after = Some(value),
+        )?;
 
-        parse_str_config(
-            self.padding_symbols.is_some(),
-            &config,
-            "symbols",
-            |value| self.padding_symbols = Some(value),
-        );
+        parse_str_config(self.padding_symbols.is_some(), config, "symbols", |value| {
+            self.padding_symbols = Some(value)
+        });
 
         parse_number_config(
             self.padding_symbols_before.is_some(),
-            &config,
+            config,
             "symbols_before",
-            |value| self.padding_symbols_before = Some(value as u8),
-        );
+            |value| self.padding_symbols_before = Some(value),
+        )?;
 
         parse_number_config(
             self.padding_symbols_after.is_some(),
-            &config,
+            config,
             "symbols_after",
-            |value| self.padding_symbols_after = Some(value as u8),
-        );
+            |value| self.padding_symbols_after = Some(value),
+        )?;
 
-        parse_enum_config(self.padding.is_some(), &config, "padding", |value| {
+        parse_enum_config(self.symbol_mode.is_some(), config, "symbol_mode", |value| {
+            self.symbol_mode = Some(value)
+        })?;
+
+        parse_enum_config(self.padding.is_some(), config, "padding", |value| {
             self.padding = Some(value)
         })?;
 
-        parse_enum_config(self.preset.is_some(), &config, "preset", |value| {
-            self.preset = Some(value)
+        parse_number_config(
+            self.adaptive_length.is_some(),
+            config,
+            "adaptive_length",
+            |value| self.adaptive_length = Some(value),
+        )?;
+
+        parse_str_arr_config(
+            self.substitutions.is_some(),
+            config,
+            "substitutions",
+            |rules| self.substitutions = Some(rules),
+        )?;
+
+        parse_number_config(self.min_length.is_some(), config, "min_length", |value| {
+            self.min_length = Some(value)
+        })?;
+
+        parse_number_config(self.max_length.is_some(), config, "max_length", |value| {
+            self.max_length = Some(value)
+        })?;
+
+        parse_number_config(
+            self.policy_min_length.is_some(),
+            config,
+            "policy_min_length",
+            |value| self.policy_min_length = Some(value),
+        )?;
+
+        parse_number_config(
+            self.policy_max_length.is_some(),
+            config,
+            "policy_max_length",
+            |value| self.policy_max_length = Some(value),
+        )?;
+
+        parse_enum_arr_config(
+            self.policy_require.is_some(),
+            config,
+            "policy_require",
+            |classes| self.policy_require = Some(classes),
+        )?;
+
+        parse_str_config(
+            self.policy_forbid.is_some(),
+            config,
+            "policy_forbid",
+            |value| self.policy_forbid = Some(value),
+        );
+
+        parse_number_config(
+            self.policy_max_repeat.is_some(),
+            config,
+            "policy_max_repeat",
+            |value| self.policy_max_repeat = Some(value),
+        )?;
+
+        parse_number_config(
+            self.policy_attempts.is_some(),
+            config,
+            "policy_attempts",
+            |value| self.policy_attempts = Some(value),
+        )?;
+
+        parse_enum_config(self.attacker.is_some(), config, "attacker", |value| {
+            self.attacker = Some(value)
         })?;
 
-        parse_enum_config(self.language.is_some(), &config, "lang", |value| {
+        parse_number_config(
+            self.guesses_per_sec.is_some(),
+            config,
+            "guesses_per_sec",
+            |value| self.guesses_per_sec = Some(value),
+        )?;
+
+        parse_str_config(self.preset.is_some(), config, "preset", |value| {
+            self.preset = Some(CliPreset::parse(&value))
+        });
+
+        parse_enum_config(self.language.is_some(), config, "lang", |value| {
             self.language = Some(value)
         })?;
 
+        parse_str_config(self.dict_file.is_some(), config, "dict", |value| {
+            self.dict_file = Some(value)
+        });
+
+        parse_str_config(
+            self.exclude_words.is_some(),
+            config,
+            "exclude_words",
+            |value| self.exclude_words = Some(value),
+        );
+
+        parse_enum_config(self.format.is_some(), config, "format", |value| {
+            self.format = Some(value)
+        })?;
+
+        if self.presets.is_none() {
+            self.presets = parse_presets_config(config)?;
+        }
+
+        self.resolve_preset()
+    }
+}
+
+impl Cli {
+    // a user preset fills the settings left unset, like a built-in one would,
+    // then gives way to the built-in preset it extends
+    fn resolve_preset(&mut self) -> Result<(), ConfigParseError> {
+        let Some(CliPreset::Custom(name)) = &self.preset else {
+            return Ok(());
+        };
+
+        let name = name.clone();
+        let preset = match self.presets.as_ref().and_then(|presets| presets.get(&name)) {
+            Some(preset) => preset.clone(),
+            None => {
+                return Err(ConfigParseError::Invalid(XkpasswdError::UnknownPreset {
+                    name,
+                }))
+            }
+        };
+
+        let in_table = |err: ConfigParseError| match err {
+            ConfigParseError::Invalid(err) => {
+                let field = format!("presets.{}.{}", name, err.field());
+                let message = match err {
+                    XkpasswdError::Config { message, .. } => message,
+                    err => err.to_string(),
+                };
+                ConfigParseError::invalid_config(&field, message)
+            }
+            err => err,
+        };
+
+        if let Some(key) = preset.as_table().and_then(|table| {
+            table
+                .keys()
+                .find(|key| !PRESET_KEYS.contains(&key.as_str()))
+        }) {
+            return Err(in_table(ConfigParseError::invalid_config(
+                key,
+                "unknown key, presets only hold settings & the built-in preset they extend"
+                    .to_string(),
+            )));
+        }
+
+        let mut extends: Option<Preset> = None;
+        parse_enum_config(false, &preset, "extends", |value| extends = Some(value))
+            .map_err(in_table)?;
+
+        // validated on its own first, so that errors point to the table
+        let mut preset_cli = Cli {
+            preset: extends.map(CliPreset::BuiltIn),
+            ..Cli::default()
+        };
+        preset_cli.load_config(&preset).map_err(in_table)?;
+        preset_cli
+            .build_settings::<Settings>()
+            .map_err(|err| in_table(ConfigParseError::Invalid(err)))?;
+
+        self.preset = extends.map(CliPreset::BuiltIn);
+        self.load_config(&preset).map_err(in_table)
+    }
+}
+
+// keys a [presets.<name>] table may hold
+const PRESET_KEYS: [&str; 20] = [
+    "extends",
+    "words_count",
+    "word_min",
+    "word_max",
+    "transforms",
+    "ascii_fold",
+    "unique_prefix",
+    "separators",
+    "separator_mode",
+    "digits_before",
+    "digits_after",
+    "symbols",
+    "symbols_before",
+    "symbols_after",
+    "symbol_mode",
+    "padding",
+    "adaptive_length",
+    "substitutions",
+    "min_length",
+    "max_length",
+];
+
+#[derive(Clone, Copy)]
+enum ConfigType {
+    // up to the maximum of the field it's loaded into
+    Number(u64),
+    Bool,
+    Str,
+    StrArr,
+    Table,
+}
+
+impl ConfigType {
+    const U8: Self = Self::Number(u8::MAX as u64);
+    const USIZE: Self = Self::Number(usize::MAX as u64);
+    const U64: Self = Self::Number(u64::MAX);
+
+    fn describe(self) -> String {
+        match self {
+            // TOML integers stop at i64::MAX anyway
+            Self::Number(max) if max >= i64::MAX as u64 => "a non-negative integer".to_string(),
+            Self::Number(max) => format!("an integer from 0 to {}", max),
+            Self::Bool => "a boolean".to_string(),
+            Self::Str => "a string".to_string(),
+            Self::StrArr => "an array of strings".to_string(),
+            Self::Table => "a table".to_string(),
+        }
+    }
+}
+
+// top-level keys read by `load_config`, with the type its parser expects
+const CONFIG_KEYS: [(&str, ConfigType); 33] = [
+    ("words_count", ConfigType::U8),
+    ("word_min", ConfigType::U8),
+    ("word_max", ConfigType::U8),
+    ("transforms", ConfigType::StrArr),
+    ("ascii_fold", ConfigType::Bool),
+    ("unique_prefix", ConfigType::U8),
+    ("separators", ConfigType::Str),
+    ("separator_mode", ConfigType::Str),
+    ("digits_before", ConfigType::U8),
+    ("digits_after", ConfigType::U8),
+    ("symbols", ConfigType::Str),
+    ("symbols_before", ConfigType::U8),
+    ("symbols_after", ConfigType::U8),
+    ("symbol_mode", ConfigType::Str),
+    ("padding", ConfigType::Str),
+    ("adaptive_length", ConfigType::USIZE),
+    ("substitutions", ConfigType::StrArr),
+    ("min_length", ConfigType::USIZE),
+    ("max_length", ConfigType::USIZE),
+    ("policy_min_length", ConfigType::USIZE),
+    ("policy_max_length", ConfigType::USIZE),
+    ("policy_require", ConfigType::StrArr),
+    ("policy_forbid", ConfigType::Str),
+    ("policy_max_repeat", ConfigType::USIZE),
+    ("policy_attempts", ConfigType::USIZE),
+    ("attacker", ConfigType::Str),
+    ("guesses_per_sec", ConfigType::U64),
+    ("preset", ConfigType::Str),
+    ("lang", ConfigType::Str),
+    ("dict", ConfigType::Str),
+    ("exclude_words", ConfigType::Str),
+    ("format", ConfigType::Str),
+    ("presets", ConfigType::Table),
+];
+
+/// Fails on keys unknown to `xkpasswd.toml` & values of another type than expected,
+/// which loading the config file would skip. Without a value, e.g. a reset key, only the key is checked.
+pub fn check_config_value(key: &str, value: Option<&toml::Value>) -> Result<(), XkpasswdError> {
+    let Some(config_type) = config_type(key) else {
+        return Err(XkpasswdError::Config {
+            field: key.to_string(),
+            message: "unknown key".to_string(),
+        });
+    };
+
+    let Some(value) = value else {
+        return Ok(());
+    };
+
+    let valid = match config_type {
+        ConfigType::Number(max) => value
+            .as_integer()
+            .is_some_and(|number| number >= 0 && number as u64 <= max),
+        ConfigType::Bool => value.is_bool(),
+        ConfigType::Str => value.is_str(),
+        ConfigType::StrArr => value
+            .as_array()
+            .is_some_and(|values| values.iter().all(toml::Value::is_str)),
+        ConfigType::Table => value.is_table(),
+    };
+
+    if valid {
         Ok(())
+    } else {
+        Err(XkpasswdError::Config {
+            field: key.to_string(),
+            message: format!("expected {}, got {}", config_type.describe(), value),
+        })
+    }
+}
+
+fn config_type(key: &str) -> Option<ConfigType> {
+    CONFIG_KEYS
+        .iter()
+        .find(|(config_key, _)| *config_key == key)
+        .map(|(_, config_type)| *config_type)
+}
+
+fn parse_presets_config(config: &toml::Value) -> Result<Option<toml::Table>, ConfigParseError> {
+    let Some(presets) = config.get("presets") else {
+        return Ok(None);
+    };
+
+    let Some(presets) = presets.as_table() else {
+        return Err(ConfigParseError::invalid_config(
+            "presets",
+            "must be a table of [presets.<name>] tables".to_string(),
+        ));
+    };
+
+    for (name, preset) in presets {
+        let field = format!("presets.{}", name);
+
+        if !preset.is_table() {
+            return Err(ConfigParseError::invalid_config(
+                &field,
+                "must be a table of settings".to_string(),
+            ));
+        }
+
+        if Preset::from_str(name, true).is_ok() {
+            return Err(ConfigParseError::invalid_config(
+                &field,
+                "can't redefine a built-in preset".to_string(),
+            ));
+        }
+    }
+
+    Ok(Some(presets.clone()))
+}
+
+/// Effective settings keyed the same way as `xkpasswd.toml`
+pub fn settings_to_config(settings: &Settings) -> toml::Table {
+    let mut config = toml::Table::new();
+
+    let (word_min, word_max) = settings.word_length_bounds();
+    let (digits_before, digits_after) = settings.padding_digits();
+    let (symbols_before, symbols_after) = settings.padding_symbol_lengths();
+
+    let transforms: Vec<toml::Value> = settings
+        .word_transforms()
+        .to_flags()
+        .iter()
+        .map(|transform| toml::Value::from(value_name(transform)))
+        .collect();
+
+    config.insert("words_count".into(), settings.words_count().into());
+    config.insert("word_min".into(), word_min.into());
+    config.insert("word_max".into(), word_max.into());
+    config.insert("transforms".into(), transforms.into());
+    config.insert("ascii_fold".into(), settings.ascii_fold().into());
+    config.insert("unique_prefix".into(), settings.unique_prefix().into());
+    config.insert("separators".into(), settings.separators().into());
+    config.insert(
+        "separator_mode".into(),
+        value_name(&settings.separator_mode()).into(),
+    );
+    config.insert("digits_before".into(), digits_before.into());
+    config.insert("digits_after".into(), digits_after.into());
+    config.insert("symbols".into(), settings.padding_symbols().into());
+    config.insert("symbols_before".into(), symbols_before.into());
+    config.insert("symbols_after".into(), symbols_after.into());
+    config.insert(
+        "symbol_mode".into(),
+        value_name(&settings.symbol_mode()).into(),
+    );
+
+    let substitutions: Vec<toml::Value> = settings
+        .substitutions()
+        .iter()
+        .map(|(from, to)| toml::Value::from(format!("{}={}", from, to)))
+        .collect();
+    config.insert("substitutions".into(), substitutions.into());
+
+    match settings.padding_strategy() {
+        PaddingStrategy::Fixed => {
+            config.insert("padding".into(), value_name(&CliPadding::Fixed).into());
+        }
+        PaddingStrategy::Adaptive(len) => {
+            config.insert("padding".into(), value_name(&CliPadding::Adaptive).into());
+            config.insert("adaptive_length".into(), (*len as i64).into());
+        }
+        PaddingStrategy::Exact(len) => {
+            config.insert("padding".into(), value_name(&CliPadding::Exact).into());
+            config.insert("adaptive_length".into(), (*len as i64).into());
+        }
+    }
+
+    let (min_length, max_length) = settings.length_range();
+
+    if let Some(min_length) = min_length {
+        config.insert("min_length".into(), (min_length as i64).into());
+    }
+
+    if let Some(max_length) = max_length {
+        config.insert("max_length".into(), (max_length as i64).into());
+    }
+
+    config
+}
+
+/// Password policy keyed the same way as `xkpasswd.toml`, omitting unset rules
+pub fn policy_to_config(policy: &PasswordPolicy) -> toml::Table {
+    let mut config = toml::Table::new();
+
+    let (min_length, max_length) = policy.length_bounds();
+
+    if let Some(min_length) = min_length {
+        config.insert("policy_min_length".into(), (min_length as i64).into());
+    }
+
+    if let Some(max_length) = max_length {
+        config.insert("policy_max_length".into(), (max_length as i64).into());
+    }
+
+    if !policy.required_classes().is_empty() {
+        let classes: Vec<toml::Value> = policy
+            .required_classes()
+            .iter()
+            .map(|class| toml::Value::from(value_name(class)))
+            .collect();
+        config.insert("policy_require".into(), classes.into());
+    }
+
+    if !policy.forbidden_chars().is_empty() {
+        config.insert("policy_forbid".into(), policy.forbidden_chars().into());
     }
+
+    if let Some(max_repeat) = policy.max_repeated_chars() {
+        config.insert("policy_max_repeat".into(), (max_repeat as i64).into());
+    }
+
+    config.insert(
+        "policy_attempts".into(),
+        (policy.max_attempts() as i64).into(),
+    );
+
+    config
+}
+
+/// Effective settings & CLI options as the content of a valid `xkpasswd.toml`
+pub fn render_config(
+    settings: &Settings,
+    policy: Option<&PasswordPolicy>,
+    attacker: Option<&Attacker>,
+    language: Option<Language>,
+    dict_file: Option<&str>,
+    exclude_words: Option<&str>,
+    format: Option<OutputFormat>,
+) -> String {
+    let config = options_to_config(
+        settings,
+        policy,
+        attacker,
+        language,
+        dict_file,
+        exclude_words,
+        format,
+    );
+    toml::to_string(&config).unwrap_or_default()
+}
+
+/// Effective settings & CLI options keyed the same way as `xkpasswd.toml`
+pub fn options_to_config(
+    settings: &Settings,
+    policy: Option<&PasswordPolicy>,
+    attacker: Option<&Attacker>,
+    language: Option<Language>,
+    dict_file: Option<&str>,
+    exclude_words: Option<&str>,
+    format: Option<OutputFormat>,
+) -> toml::Table {
+    let mut config = settings_to_config(settings);
+
+    if let Some(policy) = policy {
+        config.extend(policy_to_config(policy));
+    }
+
+    if let Some(attacker) = attacker {
+        config.insert("attacker".into(), value_name(&attacker.profile()).into());
+        config.insert(
+            "guesses_per_sec".into(),
+            (attacker.guesses_per_sec() as i64).into(),
+        );
+    }
+
+    if let Some(language) = language {
+        config.insert("lang".into(), value_name(&language).into());
+    }
+
+    if let Some(dict_file) = dict_file {
+        config.insert("dict".into(), dict_file.into());
+    }
+
+    if let Some(exclude_words) = exclude_words {
+        config.insert("exclude_words".into(), exclude_words.into());
+    }
+
+    if let Some(format) = format {
+        config.insert("format".into(), value_name(&format).into());
+    }
+
+    config
+}
+
+pub fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|possible_value| possible_value.get_name().to_string())
+        .unwrap_or_default()
 }
 
 fn lookup_default_config_path() -> Option<String> {
@@ -122,7 +661,7 @@ fn read_config_file(config_file: &Option<String>) -> Result<toml::Value, ConfigP
                 log::debug!("found config file at custom path {}", config_file);
                 Ok(data)
             }
-            Err(err) => Err(ConfigParseError::InvalidFile(err.to_string())),
+            Err(err) => Err(ConfigParseError::invalid_file(err.to_string())),
         },
         None => match lookup_default_config_path() {
             None => {
@@ -134,7 +673,7 @@ fn read_config_file(config_file: &Option<String>) -> Result<toml::Value, ConfigP
                     log::debug!("found config file at default path {}", config_path);
                     Ok(data)
                 }
-                Err(err) => Err(ConfigParseError::InvalidFile(err.to_string())),
+                Err(err) => Err(ConfigParseError::invalid_file(err.to_string())),
             },
         },
     };
@@ -142,7 +681,7 @@ fn read_config_file(config_file: &Option<String>) -> Result<toml::Value, ConfigP
     match file_data {
         Err(err) => Err(err),
         Ok(data) => match toml::from_str::<toml::Value>(&data) {
-            Err(parse_err) => Err(ConfigParseError::InvalidFile(parse_err.to_string())),
+            Err(parse_err) => Err(ConfigParseError::invalid_file(parse_err.to_string())),
             Ok(parsed_data) => Ok(parsed_data),
         },
     }
@@ -166,7 +705,7 @@ fn parse_enum_config<T: ValueEnum, F: FnMut(T)>(
                 log::debug!("loading '{}' from config file", field);
                 Ok(())
             }
-            Err(err) => Err(ConfigParseError::InvalidConfig(field.to_string(), err)),
+            Err(err) => Err(ConfigParseError::invalid_config(field, err)),
         },
         None => {
             log::debug!("loading default value for '{}'", field);
@@ -175,43 +714,64 @@ fn parse_enum_config<T: ValueEnum, F: FnMut(T)>(
     }
 }
 
-fn parse_transforms<F: FnMut(Vec<WordTransform>)>(
+fn parse_enum_arr_config<T: ValueEnum, F: FnMut(Vec<T>)>(
     ignore: bool,
     config: &toml::Value,
+    field: &str,
     mut callback: F,
 ) -> Result<(), ConfigParseError> {
-    let field = "transforms";
-
     if ignore {
         log::debug!("loading '{}' from command arguments", field);
         return Ok(());
     }
 
-    let raw_transforms = match config.get_str_arr(field) {
+    let raw_values = match config.get_str_arr(field) {
         Err(err) => {
             return match err {
                 ConfigParseError::Ignore => Ok(()),
                 _ => Err(err),
             }
         }
-        Ok(transforms) => transforms,
+        Ok(values) => values,
     };
 
-    let parsed_transforms: Result<Vec<WordTransform>, String> = raw_transforms
+    let parsed_values: Result<Vec<T>, String> = raw_values
         .iter()
-        .map(|transform| WordTransform::from_str(transform, true))
+        .map(|value| T::from_str(value, true))
         .collect();
 
-    match parsed_transforms {
-        Ok(word_transforms) => {
+    match parsed_values {
+        Ok(values) => {
+            log::debug!("loading '{}' from config file", field);
+            callback(values);
+            Ok(())
+        }
+        Err(err) => Err(ConfigParseError::invalid_config(field, err)),
+    }
+}
+
+fn parse_str_arr_config<F: FnMut(Vec<String>)>(
+    ignore: bool,
+    config: &toml::Value,
+    field: &str,
+    mut callback: F,
+) -> Result<(), ConfigParseError> {
+    if ignore {
+        log::debug!("loading '{}' from command arguments", field);
+        return Ok(());
+    }
+
+    match config.get_str_arr(field) {
+        Ok(values) => {
+            callback(values.iter().map(|value| value.to_string()).collect());
             log::debug!("loading '{}' from config file", field);
-            callback(word_transforms);
             Ok(())
         }
-        Err(err) => Err(ConfigParseError::InvalidConfig(
-            "transforms".to_string(),
-            err,
-        )),
+        Err(ConfigParseError::Ignore) => {
+            log::debug!("loading default value for '{}'", field);
+            Ok(())
+        }
+        Err(err) => Err(err),
     }
 }
 
@@ -235,7 +795,7 @@ fn parse_str_config<F: FnMut(String)>(
     }
 }
 
-fn parse_number_config<F: FnMut(u64)>(
+fn parse_bool_config<F: FnMut(bool)>(
     ignore: bool,
     config: &toml::Value,
     field: &str,
@@ -246,13 +806,45 @@ fn parse_number_config<F: FnMut(u64)>(
         return;
     }
 
+    match config.get_bool(field) {
+        Some(value) => {
+            callback(value);
+            log::debug!("loading '{}' from config file", field);
+        }
+        None => log::debug!("loading default value for '{}'", field),
+    }
+}
+
+// fails rather than truncating numbers out of the field's range
+fn parse_number_config<T: TryFrom<u64>, F: FnMut(T)>(
+    ignore: bool,
+    config: &toml::Value,
+    field: &str,
+    mut callback: F,
+) -> Result<(), ConfigParseError> {
+    if ignore {
+        log::debug!("loading '{}' from command arguments", field);
+        return Ok(());
+    }
+
     match config.get_number(field) {
         Some(value) => {
+            let value = T::try_from(value).map_err(|_| {
+                let expected = config_type(field)
+                    .map(ConfigType::describe)
+                    .unwrap_or_else(|| "a smaller integer".to_string());
+                ConfigParseError::invalid_config(
+                    field,
+                    format!("expected {}, got {}", expected, value),
+                )
+            })?;
             callback(value);
             log::debug!("loading '{}' from config file", field);
         }
         None => log::debug!("loading default value for '{}'", field),
     }
+
+    Ok(())
 }
 
 trait Getter {
@@ -260,6 +852,7 @@ trait Getter {
     fn get_array<'a>(&'a self, field: &'a str) -> Option<&'a Vec<toml::Value>>;
     fn get_str<'a>(&'a self, field: &str) -> Option<&'a str>;
     fn get_number(&self, field: &str) -> Option<u64>;
+    fn get_bool(&self, field: &str) -> Option<bool>;
 }
 
 impl Getter for toml::Value {
@@ -278,9 +871,7 @@ impl Getter for toml::Value {
 
                 match result {
                     Ok(values) => Ok(values),
-                    Err(message) => {
-                        Err(ConfigParseError::InvalidConfig(field.to_string(), message))
-                    }
+                    Err(message) => Err(ConfigParseError::invalid_config(field, message)),
                 }
             }
             None => Err(ConfigParseError::Ignore),
@@ -300,6 +891,10 @@ impl Getter for toml::Value {
             .as_integer()
             .map(|data| data.unsigned_abs())
     }
+
+    fn get_bool(&self, field: &str) -> Option<bool> {
+        self.get(field)?.as_bool()
+    }
 }
 
 #[cfg(test)]
@@ -346,7 +941,9 @@ mod tests {
             panic!("shouldn't be invoked")
         });
 
-        if let ConfigParseError::InvalidConfig(field, message) = result.err().unwrap() {
+        if let ConfigParseError::Invalid(XkpasswdError::Config { field, message }) =
+            result.err().unwrap()
+        {
             assert_eq!("preset", field);
             assert_eq!("invalid variant: apple_id", message);
         } else {
@@ -359,6 +956,7 @@ mod tests {
         let table = [
             (CliPadding::Fixed, "fixed"),
             (CliPadding::Adaptive, "adaptive"),
+            (CliPadding::Exact, "exact"),
         ];
 
         for (_preset, config_value) in table {
@@ -386,7 +984,9 @@ mod tests {
             panic!("shouldn't be invoked")
         });
 
-        if let ConfigParseError::InvalidConfig(field, message) = result.err().unwrap() {
+        if let ConfigParseError::Invalid(XkpasswdError::Config { field, message }) =
+            result.err().unwrap()
+        {
             assert_eq!("padding", field);
             assert_eq!("invalid variant: fixed_padding", message);
         } else {
@@ -395,23 +995,33 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_transforms() {
+    fn test_parse_enum_arr_config() {
         let config: toml::Value = toml::from_str(r#"transforms = ["lowercase"]"#).unwrap();
-        let result = parse_transforms(true, &config, |_| panic!("shouldn't be invoked"));
+        let result = parse_enum_arr_config(true, &config, "transforms", |_: Vec<WordTransform>| {
+            panic!("shouldn't be invoked")
+        });
         assert!(matches!(result, Ok(())));
 
-        let result = parse_transforms(false, &config, |value| {
+        let result = parse_enum_arr_config(false, &config, "transforms", |value| {
             assert_eq!(vec![WordTransform::Lowercase], value)
         });
         assert!(matches!(result, Ok(())));
 
         let config: toml::Value = toml::from_str(r#"transforms = "lowercase""#).unwrap();
-        let result = parse_transforms(false, &config, |_| panic!("shouldn't be invoked"));
+        let result =
+            parse_enum_arr_config(false, &config, "transforms", |_: Vec<WordTransform>| {
+                panic!("shouldn't be invoked")
+            });
         assert!(matches!(result, Ok(())));
 
         let config: toml::Value = toml::from_str(r#"transforms = ["lowercase", false]"#).unwrap();
-        let result = parse_transforms(false, &config, |_| panic!("shouldn't be invoked"));
-        if let ConfigParseError::InvalidConfig(field, message) = result.err().unwrap() {
+        let result =
+            parse_enum_arr_config(false, &config, "transforms", |_: Vec<WordTransform>| {
+                panic!("shouldn't be invoked")
+            });
+        if let ConfigParseError::Invalid(XkpasswdError::Config { field, message }) =
+            result.err().unwrap()
+        {
             assert_eq!("transforms", field);
             assert_eq!("Invalid data type, expect string but got 'false'", message);
         } else {
@@ -420,8 +1030,13 @@ mod tests {
 
         let config: toml::Value =
             toml::from_str(r#"transforms = ["lowercase", "inversed_titlecase"]"#).unwrap();
-        let result = parse_transforms(false, &config, |_| panic!("shouldn't be invoked"));
-        if let ConfigParseError::InvalidConfig(field, message) = result.err().unwrap() {
+        let result =
+            parse_enum_arr_config(false, &config, "transforms", |_: Vec<WordTransform>| {
+                panic!("shouldn't be invoked")
+            });
+        if let ConfigParseError::Invalid(XkpasswdError::Config { field, message }) =
+            result.err().unwrap()
+        {
             assert_eq!("transforms", field);
             assert_eq!("invalid variant: inversed_titlecase", message);
         } else {
@@ -447,14 +1062,29 @@ mod tests {
     #[test]
     fn test_parse_number_config() {
         let config: toml::Value = toml::from_str(r#"words_count = 3"#).unwrap();
-        parse_number_config(true, &config, "foo", |_| panic!("shouldn't be invoked"));
-        parse_number_config(false, &config, "foo", |_| panic!("shouldn't be invoked"));
-        parse_number_config(false, &config, "words_count", |value| assert_eq!(3, value));
+        let never = |_: u8| panic!("shouldn't be invoked");
+        assert!(parse_number_config(true, &config, "foo", never).is_ok());
+        assert!(parse_number_config(false, &config, "foo", never).is_ok());
+        assert!(
+            parse_number_config(false, &config, "words_count", |value: u8| assert_eq!(
+                3,
+                value
+            ))
+            .is_ok()
+        );
 
         let config: toml::Value = toml::from_str(r#"words_count = true"#).unwrap();
-        parse_number_config(false, &config, "words_count", |_| {
-            panic!("shouldn't be invoked")
-        });
+        assert!(parse_number_config(false, &config, "words_count", never).is_ok());
+
+        // out of range rather than truncated
+        let config: toml::Value = toml::from_str(r#"words_count = 257"#).unwrap();
+        match parse_number_config(false, &config, "words_count", never) {
+            Err(ConfigParseError::Invalid(err)) => assert_eq!(
+                "words_count: expected an integer from 0 to 255, got 257",
+                err.to_string()
+            ),
+            _ => panic!("expected invalid config"),
+        }
     }
 
     #[test]
@@ -468,14 +1098,30 @@ words_count = 5
 word_min = 4
 word_max = 8
 separators = ".-_"
+separator_mode = "independent"
 digits_before = 2
 digits_after = 3
 symbols = "!@#$"
 symbols_before = 1
 symbols_after = 2
+symbol_mode = "independent"
 preset = "web32"
 lang = "de"
+dict = "/path/to/words.txt"
+exclude_words = "/path/to/blocklist.txt"
+format = "json"
 transforms = ["lowercase", "uppercase"]
+ascii_fold = true
+unique_prefix = 3
+substitutions = [" =_", "|="]
+min_length = 20
+max_length = 40
+policy_min_length = 16
+policy_max_length = 32
+policy_require = ["digit", "symbol"]
+policy_forbid = "'"
+policy_max_repeat = 2
+policy_attempts = 50
 "#
         )
         .unwrap();
@@ -486,17 +1132,42 @@ transforms = ["lowercase", "uppercase"]
             word_length_min: None,
             word_length_max: None,
             word_transforms: None,
+            ascii_fold: None,
+            unique_prefix: None,
             separators: None,
+            separator_mode: None,
             padding_digits_before: None,
             padding_digits_after: None,
             padding_symbols: None,
             padding_symbols_before: None,
             padding_symbols_after: None,
+            symbol_mode: None,
             padding: None,
             adaptive_length: None,
+            substitutions: None,
+            min_length: None,
+            max_length: None,
             preset: None,
+            presets: None,
             verbosity: 0,
             language: None,
+            dict_file: None,
+            exclude_words: None,
+            count: None,
+            format: None,
+            readout: false,
+            policy_min_length: None,
+            policy_max_length: None,
+            policy_require: None,
+            policy_forbid: None,
+            policy_max_repeat: None,
+            policy_attempts: None,
+            attacker: None,
+            guesses_per_sec: None,
+            serve_stdin: false,
+            seed: None,
+            dump_config: false,
+            command: None,
         };
 
         let result = cli.parse_config_file();
@@ -507,17 +1178,45 @@ transforms = ["lowercase", "uppercase"]
         assert_eq!(Some(4), cli.word_length_min);
         assert_eq!(Some(8), cli.word_length_max);
         assert_eq!(Some(".-_".to_string()), cli.separators);
+        assert_eq!(Some(SeparatorMode::Independent), cli.separator_mode);
         assert_eq!(Some(2), cli.padding_digits_before);
         assert_eq!(Some(3), cli.padding_digits_after);
         assert_eq!(Some("!@#$".to_string()), cli.padding_symbols);
         assert_eq!(Some(1), cli.padding_symbols_before);
         assert_eq!(Some(2), cli.padding_symbols_after);
-        assert!(matches!(cli.preset, Some(Preset::Web32)));
+        assert_eq!(Some(SymbolMode::Independent), cli.symbol_mode);
+        assert!(matches!(
+            cli.preset,
+            Some(CliPreset::BuiltIn(Preset::Web32))
+        ));
         assert!(matches!(cli.language, Some(Language::German)));
+        assert_eq!(Some("/path/to/words.txt".to_string()), cli.dict_file);
+        assert_eq!(
+            Some("/path/to/blocklist.txt".to_string()),
+            cli.exclude_words
+        );
+        assert_eq!(Some(OutputFormat::Json), cli.format);
         assert_eq!(
             Some(vec![WordTransform::Lowercase, WordTransform::Uppercase]),
             cli.word_transforms
         );
+        assert_eq!(Some(true), cli.ascii_fold);
+        assert_eq!(Some(3), cli.unique_prefix);
+        assert_eq!(
+            Some(vec![" =_".to_string(), "|=".to_string()]),
+            cli.substitutions
+        );
+        assert_eq!(Some(20), cli.min_length);
+        assert_eq!(Some(40), cli.max_length);
+        assert_eq!(Some(16), cli.policy_min_length);
+        assert_eq!(Some(32), cli.policy_max_length);
+        assert_eq!(
+            Some(vec![CharClass::Digit, CharClass::Symbol]),
+            cli.policy_require
+        );
+        assert_eq!(Some("'".to_string()), cli.policy_forbid);
+        assert_eq!(Some(2), cli.policy_max_repeat);
+        assert_eq!(Some(50), cli.policy_attempts);
     }
 
     #[test]
@@ -533,17 +1232,42 @@ transforms = ["lowercase", "uppercase"]
             word_length_min: None,
             word_length_max: None,
             word_transforms: None,
+            ascii_fold: None,
+            unique_prefix: None,
             separators: None,
+            separator_mode: None,
             padding_digits_before: None,
             padding_digits_after: None,
             padding_symbols: None,
             padding_symbols_before: None,
             padding_symbols_after: None,
+            symbol_mode: None,
             padding: None,
             adaptive_length: None,
+            substitutions: None,
+            min_length: None,
+            max_length: None,
             preset: None,
+            presets: None,
             verbosity: 0,
             language: None,
+            dict_file: None,
+            exclude_words: None,
+            count: None,
+            format: None,
+            readout: false,
+            policy_min_length: None,
+            policy_max_length: None,
+            policy_require: None,
+            policy_forbid: None,
+            policy_max_repeat: None,
+            policy_attempts: None,
+            attacker: None,
+            guesses_per_sec: None,
+            serve_stdin: false,
+            seed: None,
+            dump_config: false,
+            command: None,
         };
 
         let result = cli.parse_config_file();
@@ -561,21 +1285,49 @@ transforms = ["lowercase", "uppercase"]
             word_length_min: None,
             word_length_max: None,
             word_transforms: None,
+            ascii_fold: None,
+            unique_prefix: None,
             separators: None,
+            separator_mode: None,
             padding_digits_before: None,
             padding_digits_after: None,
             padding_symbols: None,
             padding_symbols_before: None,
             padding_symbols_after: None,
+            symbol_mode: None,
             padding: None,
             adaptive_length: None,
+            substitutions: None,
+            min_length: None,
+            max_length: None,
             preset: None,
+            presets: None,
             verbosity: 0,
             language: None,
+            dict_file: None,
+            exclude_words: None,
+            count: None,
+            format: None,
+            readout: false,
+            policy_min_length: None,
+            policy_max_length: None,
+            policy_require: None,
+            policy_forbid: None,
+            policy_max_repeat: None,
+            policy_attempts: None,
+            attacker: None,
+            guesses_per_sec: None,
+            serve_stdin: false,
+            seed: None,
+            dump_config: false,
+            command: None,
         };
 
         let result = cli.parse_config_file();
-        assert!(matches!(result, Err(ConfigParseError::InvalidFile(_))));
+        assert!(matches!(
+            result,
+            Err(ConfigParseError::Invalid(XkpasswdError::ConfigFile(_)))
+        ));
     }
 
     #[test]
@@ -589,21 +1341,49 @@ transforms = ["lowercase", "uppercase"]
             word_length_min: None,
             word_length_max: None,
             word_transforms: None,
+            ascii_fold: None,
+            unique_prefix: None,
             separators: None,
+            separator_mode: None,
             padding_digits_before: None,
             padding_digits_after: None,
             padding_symbols: None,
             padding_symbols_before: None,
             padding_symbols_after: None,
+            symbol_mode: None,
             padding: None,
             adaptive_length: None,
+            substitutions: None,
+            min_length: None,
+            max_length: None,
             preset: None,
+            presets: None,
             verbosity: 0,
             language: None,
+            dict_file: None,
+            exclude_words: None,
+            count: None,
+            format: None,
+            readout: false,
+            policy_min_length: None,
+            policy_max_length: None,
+            policy_require: None,
+            policy_forbid: None,
+            policy_max_repeat: None,
+            policy_attempts: None,
+            attacker: None,
+            guesses_per_sec: None,
+            serve_stdin: false,
+            seed: None,
+            dump_config: false,
+            command: None,
         };
 
         let result = cli.parse_config_file();
-        assert!(matches!(result, Err(ConfigParseError::InvalidFile(_))));
+        assert!(matches!(
+            result,
+            Err(ConfigParseError::Invalid(XkpasswdError::ConfigFile(_)))
+        ));
     }
 
     #[test]
@@ -618,22 +1398,47 @@ transforms = ["lowercase", "uppercase"]
             word_length_min: None,
             word_length_max: None,
             word_transforms: None,
+            ascii_fold: None,
+            unique_prefix: None,
             separators: None,
+            separator_mode: None,
             padding_digits_before: None,
             padding_digits_after: None,
             padding_symbols: None,
             padding_symbols_before: None,
             padding_symbols_after: None,
+            symbol_mode: None,
             padding: None,
             adaptive_length: None,
+            substitutions: None,
+            min_length: None,
+            max_length: None,
             preset: None,
+            presets: None,
             verbosity: 0,
             language: None,
+            dict_file: None,
+            exclude_words: None,
+            count: None,
+            format: None,
+            readout: false,
+            policy_min_length: None,
+            policy_max_length: None,
+            policy_require: None,
+            policy_forbid: None,
+            policy_max_repeat: None,
+            policy_attempts: None,
+            attacker: None,
+            guesses_per_sec: None,
+            serve_stdin: false,
+            seed: None,
+            dump_config: false,
+            command: None,
         };
 
         let result = cli.parse_config_file();
         // Either no config found (Ignore) or config found and parsed (Ok)
-        // We don't fail on InvalidFile/InvalidConfig here
+        // We don't fail on InvalidFile/Config here
         assert!(matches!(result, Err(ConfigParseError::Ignore) | Ok(())));
     }
 
@@ -656,17 +1461,42 @@ separators = 123
             word_length_min: None,
             word_length_max: None,
             word_transforms: None,
+            ascii_fold: None,
+            unique_prefix: None,
             separators: None,
+            separator_mode: None,
             padding_digits_before: None,
             padding_digits_after: None,
             padding_symbols: None,
             padding_symbols_before: None,
             padding_symbols_after: None,
+            symbol_mode: None,
             padding: None,
             adaptive_length: None,
+            substitutions: None,
+            min_length: None,
+            max_length: None,
             preset: None,
+            presets: None,
             verbosity: 0,
             language: None,
+            dict_file: None,
+            exclude_words: None,
+            count: None,
+            format: None,
+            readout: false,
+            policy_min_length: None,
+            policy_max_length: None,
+            policy_require: None,
+            policy_forbid: None,
+            policy_max_repeat: None,
+            policy_attempts: None,
+            attacker: None,
+            guesses_per_sec: None,
+            serve_stdin: false,
+            seed: None,
+            dump_config: false,
+            command: None,
         };
 
         // Should succeed - type mismatches are silently ignored (use defaults)
@@ -681,7 +1511,7 @@ separators = 123
     #[test]
     fn test_parse_config_file_with_padding_strategy() {
         let mut temp_file = NamedTempFile::new().unwrap();
-        writeln!(temp_file, r#"padding = "adaptive""#).unwrap();
+        writeln!(temp_file, "padding = \"adaptive\"\nadaptive_length = 42").unwrap();
 
         let mut cli = Cli {
             config_file: Some(temp_file.path().to_str().unwrap().to_string()),
@@ -689,21 +1519,499 @@ separators = 123
             word_length_min: None,
             word_length_max: None,
             word_transforms: None,
+            ascii_fold: None,
+            unique_prefix: None,
             separators: None,
+            separator_mode: None,
             padding_digits_before: None,
             padding_digits_after: None,
             padding_symbols: None,
             padding_symbols_before: None,
             padding_symbols_after: None,
+            symbol_mode: None,
             padding: None,
             adaptive_length: None,
+            substitutions: None,
+            min_length: None,
+            max_length: None,
             preset: None,
+            presets: None,
             verbosity: 0,
             language: None,
+            dict_file: None,
+            exclude_words: None,
+            count: None,
+            format: None,
+            readout: false,
+            policy_min_length: None,
+            policy_max_length: None,
+            policy_require: None,
+            policy_forbid: None,
+            policy_max_repeat: None,
+            policy_attempts: None,
+            attacker: None,
+            guesses_per_sec: None,
+            serve_stdin: false,
+            seed: None,
+            dump_config: false,
+            command: None,
         };
 
         let result = cli.parse_config_file();
         assert!(result.is_ok());
         assert!(matches!(cli.padding, Some(CliPadding::Adaptive)));
+        assert_eq!(Some(42), cli.adaptive_length);
+    }
+
+    #[test]
+    fn test_render_config() {
+        let settings = Settings::from_preset(Preset::Wifi)
+            .with_separators("\"'\\")
+            .with_words_count(5)
+            .unwrap()
+            .with_substitutions(&[('\'', "".to_string()), ('=', "==".to_string())])
+            .unwrap()
+            .with_length_range(None, Some(60))
+            .unwrap();
+
+        let config = render_config(
+            &settings,
+            None,
+            None,
+            Some(Language::Spanish),
+            None,
+            None,
+            None,
+        );
+        assert!(config.contains("adaptive_length = 63\n"));
+        assert!(config.contains("lang = \"es\"\n"));
+        assert!(config.contains("padding = \"adaptive\"\n"));
+        assert!(config.contains("words_count = 5\n"));
+        assert!(config.contains("substitutions = [\"'=\", \"====\"]\n"));
+        assert!(config.contains("max_length = 60\n"));
+        assert!(!config.contains("min_length"));
+        assert!(!config.contains("dict"));
+        assert!(!config.contains("format"));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", config).unwrap();
+
+        // loading the dumped config without any flag gives back the same settings
+        let mut cli = Cli {
+            config_file: Some(temp_file.path().to_str().unwrap().to_string()),
+            words_count: None,
+            word_length_min: None,
+            word_length_max: None,
+            word_transforms: None,
+            ascii_fold: None,
+            unique_prefix: None,
+            separators: None,
+            separator_mode: None,
+            padding_digits_before: None,
+            padding_digits_after: None,
+            padding_symbols: None,
+            padding_symbols_before: None,
+            padding_symbols_after: None,
+            symbol_mode: None,
+            padding: None,
+            adaptive_length: None,
+            substitutions: None,
+            min_length: None,
+            max_length: None,
+            preset: None,
+            presets: None,
+            verbosity: 0,
+            language: None,
+            dict_file: None,
+            exclude_words: None,
+            count: None,
+            format: None,
+            readout: false,
+            policy_min_length: None,
+            policy_max_length: None,
+            policy_require: None,
+            policy_forbid: None,
+            policy_max_repeat: None,
+            policy_attempts: None,
+            attacker: None,
+            guesses_per_sec: None,
+            serve_stdin: false,
+            seed: None,
+            dump_config: false,
+            command: None,
+        };
+
+        assert!(cli.parse_config_file().is_ok());
+        assert!(matches!(cli.language, Some(Language::Spanish)));
+        assert_eq!(settings, cli.build_settings::<Settings>().unwrap());
+
+        let config = render_config(
+            &Settings::default(),
+            None,
+            None,
+            None,
+            Some("/path/to/words.txt"),
+            Some("/path/to/blocklist.txt"),
+            Some(OutputFormat::Tsv),
+        );
+        assert!(config.contains("dict = \"/path/to/words.txt\"\n"));
+        assert!(config.contains("exclude_words = \"/path/to/blocklist.txt\"\n"));
+        assert!(config.contains("format = \"tsv\"\n"));
+        assert!(config.contains("padding = \"fixed\"\n"));
+        assert!(!config.contains("lang"));
+        assert!(!config.contains("adaptive_length"));
+        assert!(!config.contains("policy"));
+        assert!(!config.contains("attacker"));
+    }
+
+    #[test]
+    fn test_policy_to_config() {
+        let config = policy_to_config(&PasswordPolicy::default());
+        assert_eq!(1, config.len());
+        assert_eq!(
+            Some(&toml::Value::from(
+                PasswordPolicy::DEFAULT_MAX_ATTEMPTS as i64
+            )),
+            config.get("policy_attempts")
+        );
+
+        let policy = PasswordPolicy::default()
+            .with_lengths(Some(12), Some(20))
+            .unwrap()
+            .with_required_classes(&[CharClass::Uppercase, CharClass::Digit])
+            .with_forbidden_chars("\"'")
+            .with_max_repeated_chars(1)
+            .unwrap();
+        let rendered = render_config(
+            &Settings::default(),
+            Some(&policy),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", rendered).unwrap();
+
+        // loading the dumped config gives back the same policy
+        let mut cli = Cli {
+            config_file: Some(temp_file.path().to_str().unwrap().to_string()),
+            words_count: None,
+            word_length_min: None,
+            word_length_max: None,
+            word_transforms: None,
+            ascii_fold: None,
+            unique_prefix: None,
+            separators: None,
+            separator_mode: None,
+            padding_digits_before: None,
+            padding_digits_after: None,
+            padding_symbols: None,
+            padding_symbols_before: None,
+            padding_symbols_after: None,
+            symbol_mode: None,
+            padding: None,
+            adaptive_length: None,
+            substitutions: None,
+            min_length: None,
+            max_length: None,
+            preset: None,
+            presets: None,
+            verbosity: 0,
+            language: None,
+            dict_file: None,
+            exclude_words: None,
+            count: None,
+            format: None,
+            readout: false,
+            policy_min_length: None,
+            policy_max_length: None,
+            policy_require: None,
+            policy_forbid: None,
+            policy_max_repeat: None,
+            policy_attempts: None,
+            attacker: None,
+            guesses_per_sec: None,
+            serve_stdin: false,
+            seed: None,
+            dump_config: false,
+            command: None,
+        };
+
+        assert!(cli.parse_config_file().is_ok());
+        assert_eq!(Ok(Some(policy)), cli.build_policy());
+    }
+
+    #[test]
+    fn test_render_attacker_config() {
+        let attacker = Attacker::new(AttackerProfile::OfflineSlowHash)
+            .with_guesses_per_sec(20_000.0)
+            .unwrap();
+        let rendered = render_config(
+            &Settings::default(),
+            None,
+            Some(&attacker),
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(rendered.contains("attacker = \"offline-slow-hash\"\n"));
+        assert!(rendered.contains("guesses_per_sec = 20000\n"));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", rendered).unwrap();
+
+        // loading the dumped config gives back the same attacker
+        let mut cli = Cli {
+            config_file: Some(temp_file.path().to_str().unwrap().to_string()),
+            words_count: None,
+            word_length_min: None,
+            word_length_max: None,
+            word_transforms: None,
+            ascii_fold: None,
+            unique_prefix: None,
+            separators: None,
+            separator_mode: None,
+            padding_digits_before: None,
+            padding_digits_after: None,
+            padding_symbols: None,
+            padding_symbols_before: None,
+            padding_symbols_after: None,
+            symbol_mode: None,
+            padding: None,
+            adaptive_length: None,
+            substitutions: None,
+            min_length: None,
+            max_length: None,
+            preset: None,
+            presets: None,
+            verbosity: 0,
+            language: None,
+            dict_file: None,
+            exclude_words: None,
+            count: None,
+            format: None,
+            readout: false,
+            policy_min_length: None,
+            policy_max_length: None,
+            policy_require: None,
+            policy_forbid: None,
+            policy_max_repeat: None,
+            policy_attempts: None,
+            attacker: None,
+            guesses_per_sec: None,
+            serve_stdin: false,
+            seed: None,
+            dump_config: false,
+            command: None,
+        };
+
+        assert!(cli.parse_config_file().is_ok());
+        assert_eq!(Ok(Some(attacker)), cli.build_attacker());
+    }
+
+    #[test]
+    fn test_parse_config_file_user_preset() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(
+            temp_file,
+            r#"
+preset = "mine"
+words_count = 5
+
+[presets.mine]
+extends = "xkcd"
+words_count = 3
+separators = "+"
+
+[presets.other]
+words_count = 2
+"#
+        )
+        .unwrap();
+        let config_file = Some(temp_file.path().to_str().unwrap().to_string());
+
+        // top-level keys win over the preset, which wins over the built-in one it extends
+        let mut cli = Cli {
+            config_file: config_file.clone(),
+            ..Cli::default()
+        };
+        assert!(cli.parse_config_file().is_ok());
+        assert!(matches!(cli.preset, Some(CliPreset::BuiltIn(Preset::Xkcd))));
+        assert_eq!(Some(5), cli.words_count);
+        assert_eq!(Some("+".to_string()), cli.separators);
+        assert_eq!(
+            Settings::from_preset(Preset::Xkcd)
+                .with_words_count(5)
+                .unwrap()
+                .with_separators("+"),
+            cli.build_settings::<Settings>().unwrap()
+        );
+
+        // picked from command arguments, without extending any built-in preset
+        let mut cli = Cli {
+            config_file,
+            preset: Some(CliPreset::parse("other")),
+            ..Cli::default()
+        };
+        assert!(cli.parse_config_file().is_ok());
+        assert!(cli.preset.is_none());
+        assert_eq!(Some(5), cli.words_count);
+        assert_eq!(Some(2), cli.presets.as_ref().map(|presets| presets.len()));
+        assert_eq!(
+            Settings::default().with_words_count(5).unwrap(),
+            cli.build_settings::<Settings>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_config_user_preset_errors() {
+        let table = [
+            (
+                r#"preset = "mine""#,
+                "unknown preset mine, neither built-in nor defined as [presets.mine] in xkpasswd.toml",
+            ),
+            ("presets = 1", "presets: must be a table of [presets.<name>] tables"),
+            (
+                "presets.mine = 1",
+                "presets.mine: must be a table of settings",
+            ),
+            (
+                "[presets.xkcd]\nwords_count = 3",
+                "presets.xkcd: can't redefine a built-in preset",
+            ),
+            (
+                "preset = \"mine\"\n[presets.mine]\nlang = \"de\"",
+                "presets.mine.lang: unknown key, presets only hold settings & the built-in preset they extend",
+            ),
+            (
+                "preset = \"mine\"\n[presets.mine]\nextends = \"mine\"",
+                "presets.mine.extends: invalid variant: mine",
+            ),
+            (
+                "preset = \"mine\"\n[presets.mine]\nseparator_mode = \"random\"",
+                "presets.mine.separator_mode: invalid variant: random",
+            ),
+            (
+                "preset = \"mine\"\n[presets.mine]\nwords_count = 0",
+                "presets.mine.words_count: words count must be 1 or higher, got 0",
+            ),
+        ];
+
+        for (config, expected) in table {
+            let config: toml::Value = toml::from_str(config).unwrap();
+
+            match Cli::default().load_config(&config) {
+                Err(ConfigParseError::Invalid(err)) => assert_eq!(expected, err.to_string()),
+                _ => panic!("expected an error for {}", config),
+            }
+        }
+
+        // valid on its own, the preset may still be overridden into invalid settings
+        let config: toml::Value =
+            toml::from_str("preset = \"mine\"\nmin_length = 24\n[presets.mine]\nmax_length = 16")
+                .unwrap();
+        let mut cli = Cli::default();
+        assert!(cli.load_config(&config).is_ok());
+        let err = cli.build_settings::<Settings>().err().unwrap();
+        assert_eq!("max length must be 24 or higher, got 16", err.to_string());
+    }
+
+    #[test]
+    fn test_preset_keys() {
+        let settings = Settings::from_preset(Preset::Xkcd)
+            .with_padding_strategy(PaddingStrategy::Adaptive(24))
+            .unwrap()
+            .with_length_range(Some(20), Some(28))
+            .unwrap();
+
+        for key in settings_to_config(&settings).keys() {
+            assert!(PRESET_KEYS.contains(&key.as_str()), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_check_config_value() {
+        let settings = Settings::from_preset(Preset::Xkcd)
+            .with_padding_strategy(PaddingStrategy::Adaptive(24))
+            .unwrap()
+            .with_length_range(Some(20), Some(28))
+            .unwrap();
+        let policy = PasswordPolicy::default()
+            .with_required_classes(&[CharClass::Digit])
+            .with_forbidden_chars("'");
+        let config = options_to_config(
+            &settings,
+            Some(&policy),
+            Some(&Attacker::new(AttackerProfile::OfflineSlowHash)),
+            Some(Language::English),
+            Some("words.txt"),
+            Some("blocklist.txt"),
+            Some(OutputFormat::Json),
+        );
+
+        // every rendered key reads back
+        for (key, value) in &config {
+            assert_eq!(Ok(()), check_config_value(key, Some(value)), "{}", key);
+        }
+
+        assert_eq!(Ok(()), check_config_value("words_count", None));
+        assert_eq!(
+            "word_count: unknown key",
+            check_config_value("word_count", None)
+                .unwrap_err()
+                .to_string()
+        );
+
+        let table = [
+            (
+                "words_count",
+                toml::Value::from(-1),
+                "an integer from 0 to 255, got -1",
+            ),
+            (
+                "words_count",
+                toml::Value::from(1.5),
+                "an integer from 0 to 255, got 1.5",
+            ),
+            (
+                "word_min",
+                toml::Value::from(260),
+                "an integer from 0 to 255, got 260",
+            ),
+            (
+                "max_length",
+                toml::Value::from(-3),
+                "a non-negative integer, got -3",
+            ),
+            (
+                "ascii_fold",
+                toml::Value::from("yes"),
+                r#"a boolean, got "yes""#,
+            ),
+            ("symbols", toml::Value::from(1), "a string, got 1"),
+            (
+                "transforms",
+                toml::Value::from("lowercase"),
+                r#"an array of strings, got "lowercase""#,
+            ),
+            (
+                "presets",
+                toml::Value::from("mine"),
+                r#"a table, got "mine""#,
+            ),
+        ];
+
+        for (key, value, message) in table {
+            assert_eq!(
+                format!("{}: expected {}", key, message),
+                check_config_value(key, Some(&value))
+                    .unwrap_err()
+                    .to_string()
+            );
+        }
     }
 }
@@ -0,0 +1,608 @@
+use crate::bit_flags::FieldSize;
+use crate::policy::PolicyViolation;
+use crate::prelude::Language;
+use std::error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum XkpasswdError {
+    WordsCount {
+        value: u8,
+        min: u8,
+    },
+    WordLength {
+        field: &'static str,
+        value: u8,
+        min: u8,
+        max: u8,
+    },
+    AdaptiveLength {
+        value: usize,
+        min: usize,
+    },
+    WordTransforms {
+        value: FieldSize,
+    },
+    AdaptiveLengthRequired,
+    ExactLength {
+        value: usize,
+        min: usize,
+    },
+    // no words fit an exact padding length, `padded` if symbols may make up the rest
+    ExactPaddingUnfit {
+        value: usize,
+        words_count: u8,
+        padded: bool,
+    },
+    // min above max, `field` being the max one
+    LengthRange {
+        field: &'static str,
+        min: usize,
+        max: usize,
+    },
+    // no password length within the range, `shortest` & `longest` being the reachable ones
+    LengthRangeUnfit {
+        min: Option<usize>,
+        max: Option<usize>,
+        shortest: usize,
+        longest: usize,
+    },
+    // too few passwords fit the range to be regenerated until one does, `acceptance` being
+    // the chance of a password to fit & `min_acceptance` the lowest one allowed
+    LengthRangeUnlikely {
+        min: Option<usize>,
+        max: Option<usize>,
+        acceptance: f64,
+        min_acceptance: f64,
+    },
+    // no password regenerated fit the range
+    LengthRangeAttempts {
+        attempts: usize,
+    },
+    Minimum {
+        field: &'static str,
+        value: usize,
+        min: usize,
+    },
+    Incompatible {
+        field: &'static str,
+        with: &'static str,
+    },
+    Substitution {
+        rule: String,
+        reason: &'static str,
+    },
+    GuessesPerSec {
+        value: f64,
+    },
+    NoLanguage,
+    LanguageWeight {
+        language: Language,
+        value: u32,
+        min: u32,
+    },
+    Symbol {
+        value: char,
+    },
+    NoSettingsFit {
+        max_length: usize,
+    },
+    UnknownPreset {
+        name: String,
+    },
+    Dictionary(String),
+    ConfigFile(String),
+    // invalid key of the `xkpasswd.toml` config file
+    Config {
+        field: String,
+        message: String,
+    },
+    Policy {
+        attempts: usize,
+        violation: PolicyViolation,
+    },
+}
+
+impl fmt::Display for XkpasswdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WordsCount { value, min } => {
+                write!(f, "words count must be {} or higher, got {}", min, value)
+            }
+            Self::WordLength {
+                field,
+                value,
+                min,
+                max,
+            } => {
+                let name = if *field == "word_min" { "min" } else { "max" };
+                write!(
+                    f,
+                    "{} word length must be between {} and {}, got {}",
+                    name, min, max, value
+                )
+            }
+            Self::AdaptiveLength { value, min } => write!(
+                f,
+                "adaptive padding length must be {} or higher, got {}",
+                min, value
+            ),
+            Self::WordTransforms { value } => {
+                write!(f, "invalid transform {:#010b}", value)
+            }
+            Self::AdaptiveLengthRequired => write!(
+                f,
+                "adaptive padding length is required for adaptive & exact padding strategies"
+            ),
+            Self::ExactLength { value, min } => write!(
+                f,
+                "exact padding length must be {} or higher, got {}",
+                min, value
+            ),
+            Self::ExactPaddingUnfit {
+                value,
+                words_count,
+                padded: false,
+            } => write!(
+                f,
+                "no {} word(s) fit in exactly {} chars along with digits & separators, without padding symbols",
+                words_count, value
+            ),
+            Self::ExactPaddingUnfit {
+                value,
+                words_count,
+                padded: true,
+            } => write!(
+                f,
+                "no {} word(s) fit in {} chars along with digits & separators",
+                words_count, value
+            ),
+            Self::LengthRange { field, min, max } => write!(
+                f,
+                "{} must be {} or higher, got {}",
+                field_name(field),
+                min,
+                max
+            ),
+            Self::LengthRangeUnfit {
+                min,
+                max,
+                shortest,
+                longest,
+            } => match (min, max) {
+                (_, Some(max)) if max < shortest => write!(
+                    f,
+                    "max length must be {} or higher to fit any password, got {}",
+                    shortest, max
+                ),
+                (Some(min), _) if min > longest => write!(
+                    f,
+                    "min length must be {} or lower to fit any password, got {}",
+                    longest, min
+                ),
+                _ => write!(
+                    f,
+                    "no password length from {} to {}",
+                    min.unwrap_or(0),
+                    max.unwrap_or(*longest)
+                ),
+            },
+            Self::LengthRangeUnlikely {
+                min,
+                max,
+                acceptance,
+                min_acceptance,
+            } => {
+                let range = match (min, max) {
+                    (Some(min), Some(max)) => format!("from {} to {} chars", min, max),
+                    (Some(min), None) => format!("of {} chars or more", min),
+                    (None, max) => format!("of {} chars or fewer", max.unwrap_or(0)),
+                };
+                write!(
+                    f,
+                    "only {}% of passwords have a length {}, at least {}% must",
+                    percent(*acceptance),
+                    range,
+                    percent(*min_acceptance)
+                )
+            }
+            Self::LengthRangeAttempts { attempts } => write!(
+                f,
+                "no password fit the length range after {} attempts",
+                attempts
+            ),
+            Self::Minimum { field, value, min } => write!(
+                f,
+                "{} must be {} or higher, got {}",
+                field_name(field),
+                min,
+                value
+            ),
+            Self::Incompatible { field, with } => {
+                write!(f, "{} can't be combined with {}", field_name(field), with)
+            }
+            Self::Substitution { rule, reason } => {
+                write!(f, "invalid substitution '{}': {}", rule, reason)
+            }
+            Self::GuessesPerSec { value } => write!(
+                f,
+                "guesses per sec must be a positive number, got {}",
+                number(*value)
+            ),
+            Self::NoLanguage => write!(f, "at least one language is required"),
+            Self::LanguageWeight {
+                language,
+                value,
+                min,
+            } => write!(
+                f,
+                "weight of {:?} must be {} or higher, got {}",
+                language, min, value
+            ),
+            Self::Symbol { value } => write!(f, "'{}' is not a symbol", value),
+            Self::NoSettingsFit { max_length } => write!(
+                f,
+                "no settings fit in {} chars with the required classes",
+                max_length
+            ),
+            Self::UnknownPreset { name } => write!(
+                f,
+                "unknown preset {}, neither built-in nor defined as [presets.{}] in xkpasswd.toml",
+                name, name
+            ),
+            Self::Dictionary(message) => write!(f, "{}", message),
+            Self::ConfigFile(message) => write!(f, "{}", message),
+            Self::Config { field, message } => write!(f, "{}: {}", field, message),
+            Self::Policy {
+                attempts,
+                violation,
+            } => write!(
+                f,
+                "no password matched the policy after {} attempts: {}",
+                attempts, violation
+            ),
+        }
+    }
+}
+
+impl error::Error for XkpasswdError {}
+
+impl XkpasswdError {
+    /// Name of the offending setting, same as its key in `xkpasswd.toml`
+    pub fn field(&self) -> &str {
+        match self {
+            Self::WordsCount { .. } => "words_count",
+            Self::WordLength { field, .. } => field,
+            Self::AdaptiveLength { .. } => "adaptive_length",
+            Self::WordTransforms { .. } => "transforms",
+            Self::AdaptiveLengthRequired => "adaptive_length",
+            Self::ExactLength { .. } => "adaptive_length",
+            Self::ExactPaddingUnfit { .. } => "adaptive_length",
+            Self::LengthRange { field, .. } => field,
+            Self::LengthRangeUnfit {
+                min,
+                max,
+                shortest,
+                longest,
+            } => match (min, max) {
+                (_, Some(max)) if max < shortest => "max_length",
+                (Some(min), _) if min > longest => "min_length",
+                _ => "max_length",
+            },
+            Self::LengthRangeUnlikely { max: None, .. } => "min_length",
+            Self::LengthRangeUnlikely { .. } | Self::LengthRangeAttempts { .. } => "max_length",
+            Self::Minimum { field, .. } => field,
+            Self::Incompatible { field, .. } => field,
+            Self::Substitution { .. } => "substitutions",
+            Self::GuessesPerSec { .. } => "guesses_per_sec",
+            Self::NoLanguage | Self::LanguageWeight { .. } => "lang",
+            Self::Symbol { .. } => "symbols",
+            Self::NoSettingsFit { .. } => "max_length",
+            Self::UnknownPreset { .. } => "preset",
+            Self::Dictionary(_) => "dict",
+            Self::ConfigFile(_) => "config",
+            Self::Config { field, .. } => field,
+            Self::Policy { .. } => "policy",
+        }
+    }
+}
+
+// e.g. "policy max length" for `policy_max_length`
+fn field_name(field: &str) -> String {
+    field.replace('_', " ")
+}
+
+// e.g. "0.05" for 0.0005, down to its first significant digit
+fn percent(ratio: f64) -> String {
+    let percent = ratio * 100.0;
+    let decimals = (-percent.log10().floor()).clamp(0.0, 6.0) as usize;
+    fixed_point(percent, decimals)
+}
+
+// e.g. "-0.5" for -0.5, without trailing zeros
+fn number(value: f64) -> String {
+    let digits = fixed_point(value, 6);
+
+    match digits.contains('.') {
+        true => digits
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string(),
+        false => digits,
+    }
+}
+
+// same as `format!("{:.*}", decimals, value)` for values within u64 once scaled, except for
+// exact halves rounding away from zero, formatted as integers since float formatting alone
+// would weigh ~10KB in Wasm bundles
+fn fixed_point(value: f64, decimals: usize) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+
+    if value.is_infinite() {
+        return if value > 0.0 { "inf" } else { "-inf" }.to_string();
+    }
+
+    let scale = 10u64.pow(decimals as u32);
+    let scaled = (value.abs() * scale as f64).round() as u64;
+    let sign = if value.is_sign_negative() { "-" } else { "" };
+
+    match decimals {
+        0 => format!("{}{}", sign, scaled),
+        _ => format!(
+            "{}{}.{:0width$}",
+            sign,
+            scaled / scale,
+            scaled % scale,
+            width = decimals
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let table = [
+            (
+                XkpasswdError::WordsCount { value: 0, min: 1 },
+                "words count must be 1 or higher, got 0",
+                "words_count",
+            ),
+            (
+                XkpasswdError::WordLength {
+                    field: "word_min",
+                    value: 3,
+                    min: 4,
+                    max: 10,
+                },
+                "min word length must be between 4 and 10, got 3",
+                "word_min",
+            ),
+            (
+                XkpasswdError::WordLength {
+                    field: "word_max",
+                    value: 11,
+                    min: 4,
+                    max: 10,
+                },
+                "max word length must be between 4 and 10, got 11",
+                "word_max",
+            ),
+            (
+                XkpasswdError::AdaptiveLength { value: 0, min: 1 },
+                "adaptive padding length must be 1 or higher, got 0",
+                "adaptive_length",
+            ),
+            (
+                XkpasswdError::WordTransforms { value: 0b00000000 },
+                "invalid transform 0b00000000",
+                "transforms",
+            ),
+            (
+                XkpasswdError::AdaptiveLengthRequired,
+                "adaptive padding length is required for adaptive & exact padding strategies",
+                "adaptive_length",
+            ),
+            (
+                XkpasswdError::ExactLength { value: 0, min: 1 },
+                "exact padding length must be 1 or higher, got 0",
+                "adaptive_length",
+            ),
+            (
+                XkpasswdError::ExactPaddingUnfit {
+                    value: 8,
+                    words_count: 3,
+                    padded: true,
+                },
+                "no 3 word(s) fit in 8 chars along with digits & separators",
+                "adaptive_length",
+            ),
+            (
+                XkpasswdError::LengthRange {
+                    field: "policy_max_length",
+                    min: 24,
+                    max: 16,
+                },
+                "policy max length must be 24 or higher, got 16",
+                "policy_max_length",
+            ),
+            (
+                XkpasswdError::LengthRangeUnfit {
+                    min: None,
+                    max: Some(8),
+                    shortest: 10,
+                    longest: 20,
+                },
+                "max length must be 10 or higher to fit any password, got 8",
+                "max_length",
+            ),
+            (
+                XkpasswdError::LengthRangeUnfit {
+                    min: Some(24),
+                    max: None,
+                    shortest: 10,
+                    longest: 20,
+                },
+                "min length must be 20 or lower to fit any password, got 24",
+                "min_length",
+            ),
+            (
+                XkpasswdError::LengthRangeUnfit {
+                    min: Some(12),
+                    max: None,
+                    shortest: 10,
+                    longest: 20,
+                },
+                "no password length from 12 to 20",
+                "max_length",
+            ),
+            (
+                XkpasswdError::LengthRangeUnlikely {
+                    min: Some(15),
+                    max: None,
+                    acceptance: 0.00049,
+                    min_acceptance: 0.002,
+                },
+                "only 0.05% of passwords have a length of 15 chars or more, at least 0.2% must",
+                "min_length",
+            ),
+            (
+                XkpasswdError::LengthRangeUnlikely {
+                    min: Some(12),
+                    max: Some(13),
+                    acceptance: 0.0012,
+                    min_acceptance: 0.002,
+                },
+                "only 0.1% of passwords have a length from 12 to 13 chars, at least 0.2% must",
+                "max_length",
+            ),
+            (
+                XkpasswdError::LengthRangeAttempts { attempts: 10000 },
+                "no password fit the length range after 10000 attempts",
+                "max_length",
+            ),
+            (
+                XkpasswdError::Minimum {
+                    field: "policy_attempts",
+                    value: 0,
+                    min: 1,
+                },
+                "policy attempts must be 1 or higher, got 0",
+                "policy_attempts",
+            ),
+            (
+                XkpasswdError::Incompatible {
+                    field: "unique_prefix",
+                    with: "exact padding",
+                },
+                "unique prefix can't be combined with exact padding",
+                "unique_prefix",
+            ),
+            (
+                XkpasswdError::Substitution {
+                    rule: "|!".to_string(),
+                    reason: "expected a single char, '=' & its replacement, e.g. '|=!'",
+                },
+                "invalid substitution '|!': expected a single char, '=' & its replacement, e.g. '|=!'",
+                "substitutions",
+            ),
+            (
+                XkpasswdError::GuessesPerSec { value: -1.0 },
+                "guesses per sec must be a positive number, got -1",
+                "guesses_per_sec",
+            ),
+            (
+                XkpasswdError::NoLanguage,
+                "at least one language is required",
+                "lang",
+            ),
+            (
+                XkpasswdError::LanguageWeight {
+                    language: Language::French,
+                    value: 0,
+                    min: 1,
+                },
+                "weight of French must be 1 or higher, got 0",
+                "lang",
+            ),
+            (
+                XkpasswdError::Symbol { value: 'a' },
+                "'a' is not a symbol",
+                "symbols",
+            ),
+            (
+                XkpasswdError::NoSettingsFit { max_length: 8 },
+                "no settings fit in 8 chars with the required classes",
+                "max_length",
+            ),
+            (
+                XkpasswdError::UnknownPreset {
+                    name: "mine".to_string(),
+                },
+                "unknown preset mine, neither built-in nor defined as [presets.mine] in xkpasswd.toml",
+                "preset",
+            ),
+            (
+                XkpasswdError::Dictionary("no valid words".to_string()),
+                "no valid words",
+                "dict",
+            ),
+            (
+                XkpasswdError::ConfigFile("not found".to_string()),
+                "not found",
+                "config",
+            ),
+            (
+                XkpasswdError::Config {
+                    field: "preset".to_string(),
+                    message: "invalid variant: foo".to_string(),
+                },
+                "preset: invalid variant: foo",
+                "preset",
+            ),
+            (
+                XkpasswdError::Policy {
+                    attempts: 1000,
+                    violation: PolicyViolation::TooLong {
+                        length: 33,
+                        max: 32,
+                    },
+                },
+                "no password matched the policy after 1000 attempts: length 33 is longer than 32",
+                "policy",
+            ),
+        ];
+
+        for (err, message, field) in table {
+            assert_eq!(message, err.to_string());
+            assert_eq!(field, err.field());
+        }
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        for value in [0.0, 0.05, 0.126, 1.5, 99.99, 12345.678, -0.0001, -3.75] {
+            for decimals in 0..=6 {
+                assert_eq!(
+                    format!("{:.*}", decimals, value),
+                    fixed_point(value, decimals),
+                    "{} to {} decimals",
+                    value,
+                    decimals
+                );
+            }
+        }
+
+        assert_eq!("NaN", fixed_point(f64::NAN, 2));
+        assert_eq!("-inf", fixed_point(f64::NEG_INFINITY, 2));
+
+        assert_eq!("-1", number(-1.0));
+        assert_eq!("-0.5", number(-0.5));
+        assert_eq!("0", number(0.0));
+        assert_eq!("inf", number(f64::INFINITY));
+    }
+}
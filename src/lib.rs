@@ -8,7 +8,9 @@
 //!
 //! - **CLI application** for generating secure, memorable passwords
 //! - **WASM module** for web integration
-//! - **Multiple language support**: English, German, Spanish, French, Portuguese
+//! - **Multiple language support**: English, German, Spanish, French, Portuguese,
+//!   with native spelling dictionaries for the accented ones, optionally mixed
+//!   with `Xkpasswd::for_languages` or `Xkpasswd::for_weighted_languages`
 //! - **Configurable presets**: AppleID, Web16, Web32, WiFi, XKCD, and more
 //! - **Entropy calculation** to help assess password strength
 //!
@@ -20,18 +22,26 @@
 //!
 //! let generator = Xkpasswd::for_language(Language::English);
 //! let settings = Settings::default();
-//! let (password, entropy) = generator.gen_pass(&settings);
+//! let (password, entropy) = generator.gen_pass(&settings)?;
 //! println!("Password: {}, Entropy: {:.2}-{:.2} bits", password, entropy.blind_min, entropy.blind_max);
+//! # Ok::<(), xkpasswd::error::XkpasswdError>(())
 //! ```
 
 pub mod bit_flags;
+mod case;
+pub mod error;
+pub mod optimizer;
+pub mod policy;
 pub mod prelude;
+pub mod readout;
 pub mod settings;
 mod wasm;
 
 #[cfg(test)]
 mod tests {
     use super::bit_flags::*;
+    #[cfg(feature = "wasm_readout")]
+    use super::prelude::Language;
     use super::wasm::*;
     use wasm_bindgen_test::*;
     wasm_bindgen_test_configure!(run_in_browser);
@@ -42,13 +52,88 @@ mod tests {
 
         let settings = WasmSettings::default()
             .with_words_count(3)
+            .unwrap()
             .with_word_lengths(None, Some(8))
+            .unwrap()
             .with_separators(".")
             .with_padding_digits(None, Some(2))
             .with_padding_symbols("!@#$%^&*-_=+:|~?/;")
             .with_padding_symbol_lengths(None, Some(2))
             .with_word_transforms(WordTransform::Lowercase | WordTransform::Uppercase)
-            .with_fixed_padding();
-        assert_eq!(4, pass.gen_pass(&settings).passwd().split('.').count());
+            .unwrap()
+            .with_fixed_padding()
+            .unwrap();
+        assert_eq!(
+            4,
+            pass.gen_pass(&settings)
+                .unwrap()
+                .passwd()
+                .split('.')
+                .count()
+        );
+    }
+
+    #[cfg(feature = "wasm_readout")]
+    #[wasm_bindgen_test]
+    fn test_readout() {
+        let settings = WasmSettings::default()
+            .with_words_count(3)
+            .unwrap()
+            .with_separators(".")
+            .with_padding_digits(Some(0), Some(0))
+            .with_padding_symbol_lengths(Some(0), Some(0))
+            .with_fixed_padding()
+            .unwrap();
+
+        // separators read out by name
+        let readout = WasmXkpasswd::new()
+            .gen_pass(&settings)
+            .unwrap()
+            .readout(Language::French);
+        assert_eq!(2, readout.iter().filter(|piece| *piece == "point").count());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_settings_error() {
+        let err = WasmSettings::default().with_words_count(0).err().unwrap();
+        assert_eq!("words_count", err.field());
+        assert_eq!("words count must be 1 or higher, got 0", err.message());
+
+        let err = WasmSettings::default()
+            .with_word_lengths(Some(2), None)
+            .err()
+            .unwrap();
+        assert_eq!("word_min", err.field());
+
+        let err = WasmSettings::default()
+            .with_adaptive_padding(0)
+            .err()
+            .unwrap();
+        assert_eq!("adaptive_length", err.field());
+
+        let err = WasmSettings::default().with_exact_padding(0).err().unwrap();
+        assert_eq!("adaptive_length", err.field());
+
+        let settings = WasmSettings::default().with_exact_padding(8).unwrap();
+        let err = WasmXkpasswd::new().check_settings(&settings).err().unwrap();
+        assert_eq!("adaptive_length", err.field());
+
+        let err = WasmSettings::default()
+            .with_length_range(Some(20), Some(10))
+            .err()
+            .unwrap();
+        assert_eq!("max_length", err.field());
+
+        let settings = WasmSettings::default()
+            .with_length_range(None, Some(4))
+            .unwrap();
+        let err = WasmXkpasswd::new().check_settings(&settings).err().unwrap();
+        assert_eq!("max_length", err.field());
+
+        let err = WasmSettings::default()
+            .with_word_transforms(0)
+            .err()
+            .unwrap();
+        assert_eq!("transforms", err.field());
     }
 }
@@ -1,21 +1,100 @@
+// library API shared with the Wasm module, only partially used by the CLI
+#[allow(dead_code)]
 mod bit_flags;
+#[allow(dead_code)]
+mod case;
 mod cli;
+#[allow(dead_code)]
+mod error;
+#[allow(dead_code)]
+mod optimizer;
+#[allow(dead_code)]
+mod policy;
+#[allow(dead_code)]
 mod prelude;
+#[allow(dead_code)]
+mod readout;
+#[allow(dead_code)]
 mod settings;
 
 use cli::*;
-use prelude::*;
+use prelude::Entropy;
 use settings::*;
+use std::io::{self, Write};
 
 fn main() {
     let mut cli = Cli::init();
     let settings: Settings = cli.parse_settings();
-    let language = cli.language();
-    log::info!("generating password in {:?} with {}", language, settings);
+    let policy = cli.parse_policy();
+    let attacker = cli.parse_attacker();
 
-    let pass_generator = Xkpasswd::for_language(language);
-    let (passwd, entropy) = pass_generator.gen_pass(&settings);
-    log::info!("calculated entropy: {}", entropy);
+    if let Some(constraints) = cli.parse_constraints() {
+        let pass_generator = cli.pass_generator();
+        let (settings, entropy) = cli.optimize_settings(&pass_generator, &constraints);
+        let entropy = entropy.with_attacker(attacker.unwrap_or_default());
+        log::info!("optimized settings: {}", settings);
 
-    println!("{}", passwd);
+        write_stdout(&cli.render_optimized(&settings, &entropy));
+        return;
+    }
+
+    if cli.dump_config() {
+        write_stdout(&cli.render_config(&settings, policy.as_ref(), attacker.as_ref()));
+        return;
+    }
+
+    if cli.serve_stdin() {
+        let stdin = std::io::stdin();
+        let stdout = std::io::stdout();
+
+        if let Err(err) = cli.serve(
+            &settings,
+            policy.as_ref(),
+            attacker.as_ref(),
+            stdin.lock(),
+            stdout.lock(),
+        ) {
+            // the reader went away, nothing left to answer
+            if err.kind() == io::ErrorKind::BrokenPipe {
+                return;
+            }
+
+            log::error!("stopped serving: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let pass_generator = cli.pass_generator();
+    log::info!("generating password with {}", settings);
+
+    let attacker = attacker.unwrap_or_default();
+    let passwds: Vec<(String, Entropy)> = cli
+        .gen_passes(&pass_generator, &settings, policy.as_ref())
+        .into_iter()
+        .map(|(passwd, entropy)| (passwd, entropy.with_attacker(attacker)))
+        .collect();
+
+    if let Some((_, entropy)) = passwds.first() {
+        log::info!("calculated entropy: {}", entropy);
+    }
+
+    write_stdout(&(cli.render_passwds(&settings, &passwds) + "\n"));
+}
+
+// stops quietly once the reader closes the pipe, e.g. `xkpasswd -n 5000 | head -1`
+fn write_stdout(output: &str) {
+    let mut stdout = io::stdout().lock();
+
+    match stdout
+        .write_all(output.as_bytes())
+        .and_then(|_| stdout.flush())
+    {
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(err) => {
+            log::error!("unable to write output: {}", err);
+            std::process::exit(1);
+        }
+    }
 }
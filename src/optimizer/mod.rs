@@ -0,0 +1,397 @@
+#[cfg(test)]
+mod tests;
+
+use crate::bit_flags::*;
+use crate::error::XkpasswdError;
+use crate::policy::CharClass;
+use crate::prelude::*;
+use crate::settings::Settings;
+use std::collections::hash_map::{Entry, HashMap};
+use std::ops::RangeInclusive;
+
+// same bounds as accepted by settings
+const WORD_LENGTHS: RangeInclusive<u8> = 4..=10;
+const MAX_WORDS_COUNT: u8 = 12;
+const MAX_PADDING_DIGITS: u8 = 4;
+const MAX_PADDING_SYMBOLS: u8 = 3;
+
+// single transforms with whether they always give a lowercase & an uppercase letter,
+// words being at least 4 letters long
+const SINGLE_WORD_TRANSFORMS: [(WordTransform, bool, bool); 8] = [
+    (WordTransform::Lowercase, true, false),
+    (WordTransform::Titlecase, true, true),
+    (WordTransform::Uppercase, false, true),
+    (WordTransform::InversedTitlecase, true, true),
+    (WordTransform::Leetspeak, false, false),
+    (WordTransform::RandomCapital, true, true),
+    (WordTransform::LastCapital, true, true),
+    (WordTransform::Reversed, true, false),
+];
+
+/// Limits of the settings searched by `optimize`.
+/// All lengths are counted in chars.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constraints {
+    min_length: Option<usize>,
+    max_length: usize,
+    required_classes: Vec<CharClass>,
+    symbols: String,
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Constraints {
+            min_length: None,
+            max_length: Self::DEFAULT_MAX_LENGTH,
+            required_classes: vec![],
+            symbols: Settings::default().padding_symbols().to_string(),
+        }
+    }
+}
+
+impl Constraints {
+    pub const DEFAULT_MAX_LENGTH: usize = 32;
+
+    pub fn with_lengths(
+        &self,
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+    ) -> Result<Self, XkpasswdError> {
+        let min_length = min_length.or(self.min_length);
+        let max_length = max_length.unwrap_or(self.max_length);
+
+        if let Some(min) = min_length {
+            if min > max_length {
+                return Err(XkpasswdError::LengthRange {
+                    field: "max_length",
+                    min,
+                    max: max_length,
+                });
+            }
+        }
+
+        Ok(Constraints {
+            min_length,
+            max_length,
+            ..self.clone()
+        })
+    }
+
+    pub fn with_required_classes(&self, classes: &[CharClass]) -> Self {
+        let mut required_classes: Vec<CharClass> = vec![];
+
+        classes.iter().for_each(|class| {
+            if !required_classes.contains(class) {
+                required_classes.push(*class);
+            }
+        });
+
+        Constraints {
+            required_classes,
+            ..self.clone()
+        }
+    }
+
+    /// Symbols allowed as separators & padding, none to only use words & digits
+    pub fn with_symbols(&self, symbols: &str) -> Result<Self, XkpasswdError> {
+        if let Some(c) = symbols.chars().find(|c| !CharClass::Symbol.matches(*c)) {
+            return Err(XkpasswdError::Symbol { value: c });
+        }
+
+        // duplicates would make some symbols more likely than others
+        let mut unique_symbols = String::new();
+        symbols.chars().for_each(|c| {
+            if !unique_symbols.contains(c) {
+                unique_symbols.push(c);
+            }
+        });
+
+        Ok(Constraints {
+            symbols: unique_symbols,
+            ..self.clone()
+        })
+    }
+
+    pub fn length_bounds(&self) -> (Option<usize>, usize) {
+        (self.min_length, self.max_length)
+    }
+
+    pub fn required_classes(&self) -> &[CharClass] {
+        &self.required_classes
+    }
+
+    pub fn symbols(&self) -> &str {
+        &self.symbols
+    }
+
+    fn requires(&self, class: CharClass) -> bool {
+        self.required_classes.contains(&class)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Candidate {
+    words_count: u8,
+    word_lengths: (u8, u8),
+    word_transforms: FieldSize,
+    separated: bool,
+    padding_digits: (u8, u8),
+    padding_symbols: (u8, u8),
+    seen: f64,
+}
+
+/// Searches words count, word lengths, transforms & padding for the settings
+/// with the highest seen entropy among those whose passwords always fit the constraints,
+/// returned with their entropy.
+///
+/// Only fixed padding is considered, with separators & padding symbols independently
+/// drawn from the allowed symbols, so that lengths & required classes hold by construction.
+pub fn optimize(
+    pass_generator: &Xkpasswd,
+    constraints: &Constraints,
+) -> Result<(Settings, Entropy), XkpasswdError> {
+    let symbols_count = constraints.symbols.chars().count();
+    let symbol_bits = if symbols_count > 0 {
+        (symbols_count as f64).log2()
+    } else {
+        0.0
+    };
+
+    // words entropies by lengths & transforms, for each count up to the max fitting one
+    let mut words_entropies: HashMap<((u8, u8), FieldSize), Vec<f64>> = HashMap::new();
+    let mut best: Option<Candidate> = None;
+
+    for candidate in candidates(constraints) {
+        let (word_lengths, word_transforms) = (candidate.word_lengths, candidate.word_transforms);
+
+        let entropies = match words_entropies.entry((word_lengths, word_transforms)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let max_count = max_words_count(constraints.max_length, word_lengths.1);
+                let settings = words_settings(max_count, word_lengths, word_transforms)?;
+                let pool = pass_generator.words_pool(&settings);
+                entry.insert(settings.words_entropies(&pool))
+            }
+        };
+
+        // not enough words of these lengths to pick from
+        let words_seen = entropies
+            .get(candidate.words_count as usize - 1)
+            .copied()
+            .unwrap_or_default();
+        if words_seen <= 0.0 {
+            continue;
+        }
+
+        let seen = words_seen + candidate.padding_entropy(symbol_bits);
+        if best.is_none_or(|best| seen > best.seen) {
+            best = Some(Candidate { seen, ..candidate });
+        }
+    }
+
+    let best = match best {
+        Some(best) => best,
+        None => {
+            return Err(XkpasswdError::NoSettingsFit {
+                max_length: constraints.max_length,
+            })
+        }
+    };
+
+    log::debug!("optimizer: best candidate {:?}", best);
+
+    let separators = if best.separated {
+        constraints.symbols.as_str()
+    } else {
+        ""
+    };
+
+    let settings = words_settings(best.words_count, best.word_lengths, best.word_transforms)?
+        .with_separators(separators)
+        .with_separator_mode(SeparatorMode::Independent)
+        .with_padding_digits(Some(best.padding_digits.0), Some(best.padding_digits.1))
+        .with_padding_symbols(&constraints.symbols)
+        .with_padding_symbol_lengths(Some(best.padding_symbols.0), Some(best.padding_symbols.1))
+        .with_symbol_mode(SymbolMode::Independent);
+    let entropy = pass_generator.calc_entropy(&settings);
+
+    Ok((settings, entropy))
+}
+
+// every words & padding always fitting the lengths & giving the required classes,
+// words being as short as the min length allows, shorter ones giving more to pick from
+fn candidates(constraints: &Constraints) -> Vec<Candidate> {
+    let (min_length, max_length) = constraints.length_bounds();
+    let min_length = min_length.unwrap_or(0);
+
+    let (separations, max_symbols): (&[bool], u8) = if constraints.symbols.is_empty() {
+        (&[false], 0)
+    } else {
+        (&[false, true], MAX_PADDING_SYMBOLS)
+    };
+
+    // separators & padding only, words being filled in later
+    let paddings: Vec<Candidate> = separations
+        .iter()
+        .flat_map(|&separated| {
+            pairs(MAX_PADDING_DIGITS).flat_map(move |padding_digits| {
+                pairs(max_symbols).map(move |padding_symbols| Candidate {
+                    words_count: 0,
+                    word_lengths: (0, 0),
+                    word_transforms: 0,
+                    separated,
+                    padding_digits,
+                    padding_symbols,
+                    seen: 0.0,
+                })
+            })
+        })
+        .collect();
+
+    let mut candidates: Vec<Candidate> = vec![];
+
+    for word_max in WORD_LENGTHS {
+        for word_transforms in transform_candidates(constraints) {
+            for words_count in 1..=max_words_count(max_length, word_max) {
+                for padding in &paddings {
+                    let mut candidate = Candidate {
+                        words_count,
+                        word_lengths: (*WORD_LENGTHS.start(), word_max),
+                        word_transforms,
+                        ..*padding
+                    };
+
+                    let missing = min_length.saturating_sub(candidate.padding_len());
+                    let word_min = missing.div_ceil(words_count as usize);
+                    if word_min > word_max as usize {
+                        continue;
+                    }
+                    candidate.word_lengths.0 = candidate.word_lengths.0.max(word_min as u8);
+
+                    if candidate.lengths().1 <= max_length && candidate.has_classes(constraints) {
+                        candidates.push(candidate);
+                    }
+                }
+            }
+        }
+    }
+
+    candidates
+}
+
+fn max_words_count(max_length: usize, word_max: u8) -> u8 {
+    (max_length / word_max as usize).min(MAX_WORDS_COUNT as usize) as u8
+}
+
+impl Candidate {
+    fn blocks(&self) -> usize {
+        let (prefix_digits, suffix_digits) = self.padding_digits;
+        self.words_count as usize + (prefix_digits > 0) as usize + (suffix_digits > 0) as usize
+    }
+
+    fn gaps(&self) -> usize {
+        if self.separated {
+            self.blocks() - 1
+        } else {
+            0
+        }
+    }
+
+    // separators, digits & symbols
+    fn padding_len(&self) -> usize {
+        let (prefix_digits, suffix_digits) = self.padding_digits;
+        let (prefix_symbols, suffix_symbols) = self.padding_symbols;
+
+        self.gaps()
+            + (prefix_digits + suffix_digits) as usize
+            + (prefix_symbols + suffix_symbols) as usize
+    }
+
+    fn lengths(&self) -> (usize, usize) {
+        let count = self.words_count as usize;
+        let (min, max) = self.word_lengths;
+        (
+            count * (min as usize) + self.padding_len(),
+            count * (max as usize) + self.padding_len(),
+        )
+    }
+
+    // letters are guaranteed by the transforms candidates
+    fn has_classes(&self, constraints: &Constraints) -> bool {
+        let (prefix_digits, suffix_digits) = self.padding_digits;
+        let (prefix_symbols, suffix_symbols) = self.padding_symbols;
+
+        let has_digit = prefix_digits + suffix_digits > 0;
+        let has_symbol = self.gaps() + (prefix_symbols + suffix_symbols) as usize > 0;
+
+        (has_digit || !constraints.requires(CharClass::Digit))
+            && (has_symbol || !constraints.requires(CharClass::Symbol))
+    }
+
+    // same as credited by the settings for independent separators & symbols
+    fn padding_entropy(&self, symbol_bits: f64) -> f64 {
+        let (prefix_digits, suffix_digits) = self.padding_digits;
+        let (prefix_symbols, suffix_symbols) = self.padding_symbols;
+
+        let digits_bits = |count: u8| {
+            if count > 0 {
+                9f64.log2() + (count - 1) as f64 * 10f64.log2()
+            } else {
+                0.0
+            }
+        };
+
+        digits_bits(prefix_digits)
+            + digits_bits(suffix_digits)
+            + (self.gaps() + (prefix_symbols + suffix_symbols) as usize) as f64 * symbol_bits
+    }
+}
+
+// words alone, without any separator nor padding
+fn words_settings(
+    words_count: u8,
+    word_lengths: (u8, u8),
+    word_transforms: FieldSize,
+) -> Result<Settings, XkpasswdError> {
+    Ok(Settings::default()
+        .with_words_count(words_count)?
+        .with_word_lengths(Some(word_lengths.0), Some(word_lengths.1))?
+        .with_word_transforms(word_transforms)?
+        .with_separators("")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbol_lengths(Some(0), Some(0)))
+}
+
+// all single transforms guaranteeing the required letters, more of them giving
+// more outputs per word, also without leetspeak & reversed words whose outputs
+// may collide with other words
+fn transform_candidates(constraints: &Constraints) -> Vec<FieldSize> {
+    let lowercase = constraints.requires(CharClass::Lowercase);
+    let uppercase = constraints.requires(CharClass::Uppercase);
+
+    let all = SINGLE_WORD_TRANSFORMS
+        .iter()
+        .filter(|(_, has_lowercase, has_uppercase)| {
+            (*has_lowercase || !lowercase) && (*has_uppercase || !uppercase)
+        })
+        .fold(0 as FieldSize, |acc, (transform, _, _)| acc | *transform);
+
+    let mut candidates: Vec<FieldSize> = vec![];
+    for excluded in [
+        0 as FieldSize,
+        FieldSize::from_flag(WordTransform::Leetspeak),
+        FieldSize::from_flag(WordTransform::Reversed),
+        WordTransform::Leetspeak | WordTransform::Reversed,
+    ] {
+        let transforms = all & !excluded;
+        if transforms != 0 && !candidates.contains(&transforms) {
+            candidates.push(transforms);
+        }
+    }
+
+    candidates
+}
+
+fn pairs(max: u8) -> impl Iterator<Item = (u8, u8)> {
+    (0..=max).flat_map(move |prefix| (0..=max).map(move |suffix| (prefix, suffix)))
+}
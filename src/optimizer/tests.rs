@@ -0,0 +1,259 @@
+use super::*;
+use crate::policy::PasswordPolicy;
+
+// a few words of each length, searching the full dictionaries being slow in debug builds
+fn pass_generator() -> Xkpasswd {
+    Xkpasswd::from_words([
+        "bark",
+        "cove",
+        "dusk",
+        "fern",
+        "glow",
+        "hush",
+        "jolt",
+        "mint",
+        "amber",
+        "brisk",
+        "cedar",
+        "dwell",
+        "flint",
+        "grove",
+        "haven",
+        "lunar",
+        "anchor",
+        "bridge",
+        "canyon",
+        "dragon",
+        "falcon",
+        "garden",
+        "harbor",
+        "meadow",
+        "balcony",
+        "cabinet",
+        "diamond",
+        "eclipse",
+        "fortune",
+        "gallery",
+        "harvest",
+        "lantern",
+        "absolute",
+        "backpack",
+        "calendar",
+        "daylight",
+        "elephant",
+        "festival",
+        "graphite",
+        "highland",
+        "adventure",
+        "blueberry",
+        "carpenter",
+        "dandelion",
+        "evergreen",
+        "fireplace",
+        "grassland",
+        "hurricane",
+        "background",
+        "basketball",
+        "chocolates",
+        "dictionary",
+        "earthquake",
+        "friendship",
+        "generation",
+        "helicopter",
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_default_constraints() {
+    let constraints = Constraints::default();
+
+    assert_eq!(
+        (None, Constraints::DEFAULT_MAX_LENGTH),
+        constraints.length_bounds()
+    );
+    assert!(constraints.required_classes().is_empty());
+    assert_eq!(Settings::default().padding_symbols(), constraints.symbols());
+}
+
+#[test]
+fn test_with_lengths() {
+    let constraints = Constraints::default()
+        .with_lengths(Some(12), Some(20))
+        .unwrap();
+    assert_eq!((Some(12), 20), constraints.length_bounds());
+
+    // unset lengths are kept
+    let constraints = constraints.with_lengths(None, Some(16)).unwrap();
+    assert_eq!((Some(12), 16), constraints.length_bounds());
+
+    let err = constraints.with_lengths(Some(24), None).unwrap_err();
+    assert_eq!("max_length", err.field());
+    assert_eq!("max length must be 24 or higher, got 16", err.to_string());
+}
+
+#[test]
+fn test_with_required_classes() {
+    let constraints = Constraints::default().with_required_classes(&[
+        CharClass::Digit,
+        CharClass::Uppercase,
+        CharClass::Digit,
+    ]);
+
+    assert_eq!(
+        &[CharClass::Digit, CharClass::Uppercase],
+        constraints.required_classes()
+    );
+}
+
+#[test]
+fn test_with_symbols() {
+    let constraints = Constraints::default().with_symbols("!-!.-").unwrap();
+    assert_eq!("!-.", constraints.symbols());
+
+    let constraints = constraints.with_symbols("").unwrap();
+    assert_eq!("", constraints.symbols());
+
+    let err = Constraints::default().with_symbols("!a").unwrap_err();
+    assert_eq!("symbols", err.field());
+    assert_eq!("'a' is not a symbol", err.to_string());
+}
+
+#[test]
+fn test_transform_candidates() {
+    let table = [
+        (
+            vec![],
+            vec![0b11_00111111, 0b11_00101111, 0b01_00111111, 0b01_00101111],
+        ),
+        (
+            vec![CharClass::Lowercase],
+            vec![0b11_00101011, 0b01_00101011],
+        ),
+        (vec![CharClass::Uppercase], vec![0b01_00101110]),
+        (
+            vec![CharClass::Lowercase, CharClass::Uppercase, CharClass::Digit],
+            vec![0b01_00101010],
+        ),
+    ];
+
+    for (classes, expected) in table {
+        let constraints = Constraints::default().with_required_classes(&classes);
+        assert_eq!(
+            expected,
+            transform_candidates(&constraints),
+            "{:?}",
+            classes
+        );
+    }
+}
+
+#[test]
+fn test_optimize() {
+    let pass = pass_generator();
+
+    let table = [
+        (Constraints::default(), vec![]),
+        (
+            Constraints::default().with_lengths(None, Some(20)).unwrap(),
+            vec![CharClass::Digit],
+        ),
+        (
+            Constraints::default()
+                .with_lengths(Some(14), Some(16))
+                .unwrap()
+                .with_symbols("!#")
+                .unwrap(),
+            vec![
+                CharClass::Lowercase,
+                CharClass::Uppercase,
+                CharClass::Digit,
+                CharClass::Symbol,
+            ],
+        ),
+        (
+            Constraints::default()
+                .with_lengths(Some(24), Some(28))
+                .unwrap()
+                .with_symbols("")
+                .unwrap(),
+            vec![CharClass::Uppercase],
+        ),
+    ];
+
+    for (constraints, classes) in table {
+        let constraints = constraints.with_required_classes(&classes);
+        let (settings, entropy) = optimize(&pass, &constraints).unwrap();
+
+        assert_eq!(pass.calc_entropy(&settings), entropy);
+        assert!(entropy.seen > 0.0, "{}", settings);
+
+        for c in settings
+            .separators()
+            .chars()
+            .chain(settings.padding_symbols().chars())
+        {
+            assert!(constraints.symbols().contains(c), "{} not allowed", c);
+        }
+
+        let (min_length, max_length) = constraints.length_bounds();
+        let policy = PasswordPolicy::default()
+            .with_lengths(min_length, Some(max_length))
+            .unwrap()
+            .with_required_classes(&classes);
+
+        for seed in 0..50 {
+            let (passwd, _) = pass.gen_pass_with_seed(&settings, seed).unwrap();
+            assert_eq!(
+                Ok(()),
+                policy.check(&passwd),
+                "{} with {}",
+                passwd,
+                settings
+            );
+        }
+    }
+}
+
+#[test]
+fn test_optimize_beats_fitting_settings() {
+    let pass = pass_generator();
+
+    // default settings draw all their separators & symbols from the default ones
+    let settings = Settings::default();
+    let max_length = 3 * 10 + 2 + 2 * 3 + 2 * 2;
+    let constraints = Constraints::default()
+        .with_lengths(None, Some(max_length))
+        .unwrap();
+
+    let (_, entropy) = optimize(&pass, &constraints).unwrap();
+    assert!(entropy.seen > pass.calc_entropy(&settings).seen);
+
+    // more room never hurts
+    let shorter = constraints.with_lengths(None, Some(20)).unwrap();
+    let (_, shorter_entropy) = optimize(&pass, &shorter).unwrap();
+    assert!(entropy.seen > shorter_entropy.seen);
+}
+
+#[test]
+fn test_optimize_errors() {
+    let pass = pass_generator();
+
+    let table = [
+        Constraints::default().with_lengths(None, Some(3)).unwrap(),
+        // digits & symbols can't fit next to a 4-letter word
+        Constraints::default()
+            .with_lengths(None, Some(5))
+            .unwrap()
+            .with_required_classes(&[CharClass::Digit, CharClass::Symbol]),
+        Constraints::default()
+            .with_symbols("")
+            .unwrap()
+            .with_required_classes(&[CharClass::Symbol]),
+    ];
+
+    for constraints in table {
+        let err = optimize(&pass, &constraints).unwrap_err();
+        assert_eq!("max_length", err.field());
+    }
+}
@@ -0,0 +1,267 @@
+#[cfg(test)]
+mod tests;
+
+use crate::error::XkpasswdError;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    /// Letters are classified by their case, including accented ones,
+    /// anything neither a letter nor a digit counts as a symbol
+    pub fn matches(self, c: char) -> bool {
+        match self {
+            Self::Lowercase => c.is_lowercase(),
+            Self::Uppercase => c.is_uppercase(),
+            Self::Digit => c.is_ascii_digit(),
+            Self::Symbol => !c.is_alphanumeric(),
+        }
+    }
+}
+
+impl fmt::Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Lowercase => "lowercase letter",
+            Self::Uppercase => "uppercase letter",
+            Self::Digit => "digit",
+            Self::Symbol => "symbol",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// First rule of a policy broken by a password
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PolicyViolation {
+    TooShort {
+        length: usize,
+        min: usize,
+    },
+    TooLong {
+        length: usize,
+        max: usize,
+    },
+    ForbiddenChar(char),
+    MissingClass(CharClass),
+    RepeatedChar {
+        value: char,
+        count: usize,
+        max: usize,
+    },
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooShort { length, min } => {
+                write!(f, "length {} is shorter than {}", length, min)
+            }
+            Self::TooLong { length, max } => {
+                write!(f, "length {} is longer than {}", length, max)
+            }
+            Self::ForbiddenChar(c) => write!(f, "contains forbidden char '{}'", c),
+            Self::MissingClass(class) => write!(f, "missing {}", class),
+            Self::RepeatedChar { value, count, max } => write!(
+                f,
+                "'{}' repeated {} times in a row, at most {} allowed",
+                value, count, max
+            ),
+        }
+    }
+}
+
+/// Site rules a generated password must comply with,
+/// e.g. length limits, required or forbidden chars.
+/// All lengths are counted in chars.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    required_classes: Vec<CharClass>,
+    forbidden_chars: String,
+    max_repeated_chars: Option<usize>,
+    max_attempts: usize,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            min_length: None,
+            max_length: None,
+            required_classes: vec![],
+            forbidden_chars: "".to_string(),
+            max_repeated_chars: None,
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub const DEFAULT_MAX_ATTEMPTS: usize = 1000;
+
+    pub fn with_lengths(
+        &self,
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+    ) -> Result<Self, XkpasswdError> {
+        let min_length = min_length.or(self.min_length);
+        let max_length = max_length.or(self.max_length);
+
+        if let (Some(min), Some(max)) = (min_length, max_length) {
+            if min > max {
+                return Err(XkpasswdError::LengthRange {
+                    field: "policy_max_length",
+                    min,
+                    max,
+                });
+            }
+        }
+
+        Ok(PasswordPolicy {
+            min_length,
+            max_length,
+            ..self.clone()
+        })
+    }
+
+    pub fn with_required_classes(&self, classes: &[CharClass]) -> Self {
+        let mut required_classes: Vec<CharClass> = vec![];
+
+        classes.iter().for_each(|class| {
+            if !required_classes.contains(class) {
+                required_classes.push(*class);
+            }
+        });
+
+        PasswordPolicy {
+            required_classes,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_forbidden_chars(&self, chars: &str) -> Self {
+        PasswordPolicy {
+            forbidden_chars: chars.to_string(),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_max_repeated_chars(&self, max: usize) -> Result<Self, XkpasswdError> {
+        if max == 0 {
+            return Err(XkpasswdError::Minimum {
+                field: "policy_max_repeat",
+                value: max,
+                min: 1,
+            });
+        }
+
+        Ok(PasswordPolicy {
+            max_repeated_chars: Some(max),
+            ..self.clone()
+        })
+    }
+
+    pub fn with_max_attempts(&self, max: usize) -> Result<Self, XkpasswdError> {
+        if max == 0 {
+            return Err(XkpasswdError::Minimum {
+                field: "policy_attempts",
+                value: max,
+                min: 1,
+            });
+        }
+
+        Ok(PasswordPolicy {
+            max_attempts: max,
+            ..self.clone()
+        })
+    }
+
+    pub fn length_bounds(&self) -> (Option<usize>, Option<usize>) {
+        (self.min_length, self.max_length)
+    }
+
+    pub fn required_classes(&self) -> &[CharClass] {
+        &self.required_classes
+    }
+
+    pub fn forbidden_chars(&self) -> &str {
+        &self.forbidden_chars
+    }
+
+    pub fn max_repeated_chars(&self) -> Option<usize> {
+        self.max_repeated_chars
+    }
+
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// Returns the first broken rule, checked in the order:
+    /// length, forbidden chars, required classes then repeated chars
+    pub fn check(&self, passwd: &str) -> Result<(), PolicyViolation> {
+        let length = passwd.chars().count();
+
+        if let Some(min) = self.min_length {
+            if length < min {
+                return Err(PolicyViolation::TooShort { length, min });
+            }
+        }
+
+        if let Some(max) = self.max_length {
+            if length > max {
+                return Err(PolicyViolation::TooLong { length, max });
+            }
+        }
+
+        if let Some(c) = passwd.chars().find(|c| self.forbidden_chars.contains(*c)) {
+            return Err(PolicyViolation::ForbiddenChar(c));
+        }
+
+        if let Some(class) = self
+            .required_classes
+            .iter()
+            .find(|class| !passwd.chars().any(|c| class.matches(c)))
+        {
+            return Err(PolicyViolation::MissingClass(*class));
+        }
+
+        if let Some(max) = self.max_repeated_chars {
+            if let Some((value, count)) = longest_run(passwd) {
+                if count > max {
+                    return Err(PolicyViolation::RepeatedChar { value, count, max });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// longest streak of the same char, the first one wins on ties
+fn longest_run(passwd: &str) -> Option<(char, usize)> {
+    let mut longest: Option<(char, usize)> = None;
+    let mut current: Option<(char, usize)> = None;
+
+    for c in passwd.chars() {
+        current = match current {
+            Some((prev, count)) if prev == c => Some((c, count + 1)),
+            _ => Some((c, 1)),
+        };
+
+        if current.map(|(_, count)| count) > longest.map(|(_, count)| count) {
+            longest = current;
+        }
+    }
+
+    longest
+}
@@ -0,0 +1,203 @@
+use super::*;
+
+#[test]
+fn test_char_class_matches() {
+    let table = [
+        (CharClass::Lowercase, "aéß", "A1-É"),
+        (CharClass::Uppercase, "AÉ", "a1-é"),
+        (CharClass::Digit, "09", "a-٣"),
+        (CharClass::Symbol, "-. ¿€", "aA1é"),
+    ];
+
+    for (class, matching, not_matching) in table {
+        for c in matching.chars() {
+            assert!(class.matches(c), "{} should match {}", c, class);
+        }
+
+        for c in not_matching.chars() {
+            assert!(!class.matches(c), "{} shouldn't match {}", c, class);
+        }
+    }
+}
+
+#[test]
+fn test_default_policy() {
+    let policy = PasswordPolicy::default();
+
+    assert_eq!((None, None), policy.length_bounds());
+    assert!(policy.required_classes().is_empty());
+    assert_eq!("", policy.forbidden_chars());
+    assert_eq!(None, policy.max_repeated_chars());
+    assert_eq!(PasswordPolicy::DEFAULT_MAX_ATTEMPTS, policy.max_attempts());
+
+    // anything goes
+    for passwd in ["", "a", "aaaaaaaa", "correct horse battery staple"] {
+        assert_eq!(Ok(()), policy.check(passwd));
+    }
+}
+
+#[test]
+fn test_with_lengths() {
+    let policy = PasswordPolicy::default()
+        .with_lengths(Some(8), None)
+        .unwrap();
+    assert_eq!((Some(8), None), policy.length_bounds());
+
+    // unset bounds keep the current values
+    let policy = policy.with_lengths(None, Some(16)).unwrap();
+    assert_eq!((Some(8), Some(16)), policy.length_bounds());
+
+    assert_eq!(
+        Err(XkpasswdError::LengthRange {
+            field: "policy_max_length",
+            min: 8,
+            max: 7,
+        }),
+        policy.with_lengths(None, Some(7))
+    );
+
+    let err = policy.with_lengths(Some(17), None).err().unwrap();
+    assert_eq!("policy_max_length", err.field());
+}
+
+#[test]
+fn test_with_required_classes() {
+    let policy = PasswordPolicy::default().with_required_classes(&[
+        CharClass::Digit,
+        CharClass::Symbol,
+        CharClass::Digit,
+    ]);
+
+    // deduplicated, first occurrence order kept
+    assert_eq!(
+        &[CharClass::Digit, CharClass::Symbol],
+        policy.required_classes()
+    );
+}
+
+#[test]
+fn test_with_invalid_limits() {
+    let err = PasswordPolicy::default()
+        .with_max_repeated_chars(0)
+        .err()
+        .unwrap();
+    assert_eq!("policy_max_repeat", err.field());
+    assert_eq!(
+        "policy max repeat must be 1 or higher, got 0",
+        err.to_string()
+    );
+
+    let err = PasswordPolicy::default()
+        .with_max_attempts(0)
+        .err()
+        .unwrap();
+    assert_eq!("policy_attempts", err.field());
+    assert_eq!(
+        "policy attempts must be 1 or higher, got 0",
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_check() {
+    let policy = PasswordPolicy::default()
+        .with_lengths(Some(8), Some(12))
+        .unwrap()
+        .with_required_classes(&[CharClass::Uppercase, CharClass::Digit])
+        .with_forbidden_chars("'\"")
+        .with_max_repeated_chars(2)
+        .unwrap();
+
+    let table = [
+        ("Horse.42", Ok(())),
+        ("Cheval.Été99", Ok(())),
+        (
+            "Horse42",
+            Err(PolicyViolation::TooShort { length: 7, min: 8 }),
+        ),
+        (
+            "Horse.Staple.42",
+            Err(PolicyViolation::TooLong {
+                length: 15,
+                max: 12,
+            }),
+        ),
+        // lengths are counted in chars rather than bytes
+        (
+            "Éléphant.été",
+            Err(PolicyViolation::MissingClass(CharClass::Digit)),
+        ),
+        ("horse'42", Err(PolicyViolation::ForbiddenChar('\''))),
+        (
+            "horse.42",
+            Err(PolicyViolation::MissingClass(CharClass::Uppercase)),
+        ),
+        (
+            "Horse...42",
+            Err(PolicyViolation::RepeatedChar {
+                value: '.',
+                count: 3,
+                max: 2,
+            }),
+        ),
+        // forbidden chars are checked before classes
+        ("horse\"42", Err(PolicyViolation::ForbiddenChar('"'))),
+    ];
+
+    for (passwd, expected) in table {
+        assert_eq!(expected, policy.check(passwd), "checking {}", passwd);
+    }
+}
+
+#[test]
+fn test_longest_run() {
+    let table = [
+        ("", None),
+        ("a", Some(('a', 1))),
+        ("abc", Some(('a', 1))),
+        ("abbccc", Some(('c', 3))),
+        ("aabb", Some(('a', 2))),
+        ("ééé.", Some(('é', 3))),
+    ];
+
+    for (passwd, expected) in table {
+        assert_eq!(expected, longest_run(passwd), "checking {}", passwd);
+    }
+}
+
+#[test]
+fn test_violation_display() {
+    let table = [
+        (
+            PolicyViolation::TooShort { length: 7, min: 8 },
+            "length 7 is shorter than 8",
+        ),
+        (
+            PolicyViolation::TooLong {
+                length: 40,
+                max: 32,
+            },
+            "length 40 is longer than 32",
+        ),
+        (
+            PolicyViolation::ForbiddenChar('~'),
+            "contains forbidden char '~'",
+        ),
+        (
+            PolicyViolation::MissingClass(CharClass::Uppercase),
+            "missing uppercase letter",
+        ),
+        (
+            PolicyViolation::RepeatedChar {
+                value: '-',
+                count: 3,
+                max: 1,
+            },
+            "'-' repeated 3 times in a row, at most 1 allowed",
+        ),
+    ];
+
+    for (violation, message) in table {
+        assert_eq!(message, violation.to_string());
+    }
+}
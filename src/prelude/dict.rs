@@ -0,0 +1,261 @@
+use super::dict_compiler::{compile_words, expand, Bucket};
+use super::{Language, WordsPool};
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+/// Words sorted by length & stored back to back without separators.
+/// Bundled dictionaries are precompiled & front-coded at build time, each expanded once
+/// on first use, after which loading them copies nothing.
+#[derive(Clone, Debug)]
+pub struct Dict {
+    words: Cow<'static, str>,
+    buckets: Cow<'static, [Bucket]>,
+}
+
+#[cfg(feature = "lang_en")]
+static DICT_EN: LazyLock<Dict> =
+    LazyLock::new(|| include!(concat!(env!("OUT_DIR"), "/dict_en.rs")));
+#[cfg(feature = "lang_de")]
+static DICT_DE: LazyLock<Dict> =
+    LazyLock::new(|| include!(concat!(env!("OUT_DIR"), "/dict_de.rs")));
+#[cfg(feature = "lang_es")]
+static DICT_ES: LazyLock<Dict> =
+    LazyLock::new(|| include!(concat!(env!("OUT_DIR"), "/dict_es.rs")));
+#[cfg(feature = "lang_fr")]
+static DICT_FR: LazyLock<Dict> =
+    LazyLock::new(|| include!(concat!(env!("OUT_DIR"), "/dict_fr.rs")));
+#[cfg(feature = "lang_pt")]
+static DICT_PT: LazyLock<Dict> =
+    LazyLock::new(|| include!(concat!(env!("OUT_DIR"), "/dict_pt.rs")));
+#[cfg(feature = "lang_de_native")]
+static DICT_DE_NATIVE: LazyLock<Dict> =
+    LazyLock::new(|| include!(concat!(env!("OUT_DIR"), "/dict_de_native.rs")));
+#[cfg(feature = "lang_es_native")]
+static DICT_ES_NATIVE: LazyLock<Dict> =
+    LazyLock::new(|| include!(concat!(env!("OUT_DIR"), "/dict_es_native.rs")));
+#[cfg(feature = "lang_fr_native")]
+static DICT_FR_NATIVE: LazyLock<Dict> =
+    LazyLock::new(|| include!(concat!(env!("OUT_DIR"), "/dict_fr_native.rs")));
+#[cfg(feature = "lang_pt_native")]
+static DICT_PT_NATIVE: LazyLock<Dict> =
+    LazyLock::new(|| include!(concat!(env!("OUT_DIR"), "/dict_pt_native.rs")));
+
+impl Dict {
+    // used by the code generated in build.rs
+    #[allow(dead_code)]
+    fn from_front_coded(coded: &str, buckets: &'static [Bucket]) -> Self {
+        // expanded once into its static for the whole run, its clones staying borrowed
+        Dict {
+            words: Cow::Borrowed(expand(coded, buckets).leak()),
+            buckets: Cow::Borrowed(buckets),
+        }
+    }
+
+    /// Bundled dictionaries are only borrowed, cloning them copies nothing
+    pub fn bundled(language: Language) -> &'static Self {
+        Self::find_bundled(language).expect("no language bundled")
+    }
+
+    /// Bundled dictionary of the language, none if its feature is off
+    pub fn find_bundled(language: Language) -> Option<&'static Self> {
+        let dict: &LazyLock<Dict> = match language {
+            #[cfg(feature = "lang_en")]
+            Language::English => &DICT_EN,
+            #[cfg(feature = "lang_de")]
+            Language::German => &DICT_DE,
+            #[cfg(feature = "lang_es")]
+            Language::Spanish => &DICT_ES,
+            #[cfg(feature = "lang_fr")]
+            Language::French => &DICT_FR,
+            #[cfg(feature = "lang_pt")]
+            Language::Portuguese => &DICT_PT,
+            #[cfg(feature = "lang_de_native")]
+            Language::GermanNative => &DICT_DE_NATIVE,
+            #[cfg(feature = "lang_es_native")]
+            Language::SpanishNative => &DICT_ES_NATIVE,
+            #[cfg(feature = "lang_fr_native")]
+            Language::FrenchNative => &DICT_FR_NATIVE,
+            #[cfg(feature = "lang_pt_native")]
+            Language::PortugueseNative => &DICT_PT_NATIVE,
+            #[allow(unreachable_patterns)]
+            _ => return None,
+        };
+
+        Some(LazyLock::force(dict))
+    }
+
+    /// Scans only the words as long as this one, both in chars & bytes
+    pub fn contains(&self, word: &str) -> bool {
+        let char_len = word.chars().count();
+
+        self.buckets
+            .iter()
+            .filter(|bucket| bucket.char_len as usize == char_len)
+            .filter(|bucket| bucket.byte_len as usize == word.len())
+            .any(|bucket| {
+                let start = bucket.offset as usize;
+                let end = start + bucket.count as usize * word.len();
+                self.words.as_bytes()[start..end]
+                    .chunks(word.len())
+                    .any(|other| other == word.as_bytes())
+            })
+    }
+
+    /// Words must be at most 255 chars long
+    pub fn from_words<S: AsRef<str>>(words: &[S]) -> Self {
+        let (words, buckets) = compile_words(words);
+
+        Dict {
+            words: Cow::Owned(words),
+            buckets: Cow::Owned(buckets),
+        }
+    }
+
+    pub fn words(&self) -> DictPool<'_> {
+        DictPool {
+            words: &self.words,
+            buckets: &self.buckets,
+        }
+    }
+
+    /// Words with a length in chars within the range,
+    /// a contiguous slice of the dictionary found without scanning words
+    pub fn pool(&self, lengths: Range<u8>) -> DictPool<'_> {
+        let start = self
+            .buckets
+            .partition_point(|bucket| bucket.char_len < lengths.start);
+        let end = self
+            .buckets
+            .partition_point(|bucket| bucket.char_len < lengths.end)
+            .max(start);
+
+        DictPool {
+            words: &self.words,
+            buckets: &self.buckets[start..end],
+        }
+    }
+}
+
+/// View over the words of a dictionary within a range of lengths
+#[derive(Clone, Copy, Debug)]
+pub struct DictPool<'a> {
+    words: &'a str,
+    buckets: &'a [Bucket],
+}
+
+impl<'a> DictPool<'a> {
+    /// Finds the bucket holding the word then jumps to it by stride
+    pub fn get(&self, idx: usize) -> &'a str {
+        let first = self.buckets.first().map_or(0, |bucket| bucket.first) as usize;
+        let idx = first + idx;
+
+        let bucket = &self.buckets[self
+            .buckets
+            .partition_point(|bucket| ((bucket.first + bucket.count) as usize) <= idx)];
+
+        let byte_len = bucket.byte_len as usize;
+        let start = bucket.offset as usize + (idx - bucket.first as usize) * byte_len;
+        &self.words[start..start + byte_len]
+    }
+}
+
+impl WordsPool for DictPool<'_> {
+    fn len(&self) -> usize {
+        match (self.buckets.first(), self.buckets.last()) {
+            (Some(first), Some(last)) => (last.first + last.count - first.first) as usize,
+            _ => 0,
+        }
+    }
+
+    fn word(&self, idx: usize) -> &str {
+        self.get(idx)
+    }
+}
+
+/// Words of several dictionaries, each kept once. A dictionary is drawn by weight first,
+/// then one of its words, so that a dictionary weighted 2 gives twice as many words
+/// as one weighted 1 whatever their sizes.
+#[derive(Clone, Debug)]
+pub struct WeightedPool<'a> {
+    // dictionaries without words of the lengths are left out, never being drawn
+    pools: Vec<(DictPool<'a>, u32)>,
+    total_weight: u64,
+}
+
+impl<'a> WeightedPool<'a> {
+    pub fn new(pools: Vec<(DictPool<'a>, u32)>) -> Self {
+        let pools: Vec<(DictPool<'a>, u32)> = pools
+            .into_iter()
+            .filter(|(pool, _)| !pool.is_empty())
+            .collect();
+        let total_weight = pools.iter().map(|&(_, weight)| weight as u64).sum();
+
+        WeightedPool {
+            pools,
+            total_weight,
+        }
+    }
+
+    // dictionary holding the word, with the index of the word within it
+    fn locate(&self, idx: usize) -> (&(DictPool<'a>, u32), usize) {
+        let mut offset = idx;
+
+        for entry in &self.pools {
+            if offset < entry.0.len() {
+                return (entry, offset);
+            }
+
+            offset -= entry.0.len();
+        }
+
+        panic!(
+            "index out of bounds: the len is {} but the index is {}",
+            self.len(),
+            idx
+        )
+    }
+}
+
+impl WordsPool for WeightedPool<'_> {
+    fn len(&self) -> usize {
+        self.pools.iter().map(|(pool, _)| pool.len()).sum()
+    }
+
+    fn word(&self, idx: usize) -> &str {
+        let ((pool, _), word_idx) = self.locate(idx);
+        pool.get(word_idx)
+    }
+
+    fn prob(&self, idx: usize) -> f64 {
+        let ((pool, weight), _) = self.locate(idx);
+        *weight as f64 / self.total_weight as f64 / pool.len() as f64
+    }
+
+    fn draw<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let word_idx = |rng: &mut R, pool: &DictPool| -> usize {
+            Uniform::from(0..pool.len() as u64).sample(rng) as usize
+        };
+
+        // a single dictionary draws the same as a plain pool
+        if let [(pool, _)] = self.pools.as_slice() {
+            return word_idx(rng, pool);
+        }
+
+        let mut target = Uniform::from(0..self.total_weight).sample(rng);
+        let mut offset = 0;
+
+        for (pool, weight) in &self.pools {
+            if target < *weight as u64 {
+                return offset + word_idx(rng, pool);
+            }
+
+            target -= *weight as u64;
+            offset += pool.len();
+        }
+
+        panic!("no words to draw from")
+    }
+}
@@ -0,0 +1,119 @@
+// Shared with build.rs, which precompiles the bundled dictionaries,
+// hence depending on std only.
+
+/// Run of words sharing the same length, both in chars & bytes,
+/// stored back to back in the words blob so that any of them is found by stride
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bucket {
+    pub char_len: u8,
+    pub byte_len: u32,
+    // byte offset of the first word in the blob
+    pub offset: u32,
+    // index of the first word across all buckets
+    pub first: u32,
+    pub count: u32,
+}
+
+/// Sorts words by length in chars then bytes, otherwise keeping their order,
+/// so that any range of lengths maps to contiguous buckets.
+/// Words must be at most 255 chars long.
+pub fn compile_words<S: AsRef<str>>(words: &[S]) -> (String, Vec<Bucket>) {
+    let mut sorted: Vec<&str> = words.iter().map(AsRef::as_ref).collect();
+    sorted.sort_by_key(|word| (word.chars().count(), word.len()));
+
+    let mut blob = String::with_capacity(sorted.iter().map(|word| word.len()).sum());
+    let mut buckets: Vec<Bucket> = vec![];
+
+    for (idx, word) in sorted.iter().enumerate() {
+        let char_len = word.chars().count() as u8;
+        let byte_len = word.len() as u32;
+
+        match buckets.last_mut() {
+            Some(bucket) if bucket.char_len == char_len && bucket.byte_len == byte_len => {
+                bucket.count += 1;
+            }
+            _ => buckets.push(Bucket {
+                char_len,
+                byte_len,
+                offset: blob.len() as u32,
+                first: idx as u32,
+                count: 1,
+            }),
+        }
+
+        blob.push_str(word);
+    }
+
+    (blob, buckets)
+}
+
+/// Front-codes the words blob: each word is stored as the byte length of the prefix
+/// it shares with the previous word of its bucket, as a single ASCII char, and the rest
+/// of it, all lengths coming first then all rests. Sorted words share long prefixes,
+/// which shrinks the bundled dictionaries by about a fourth once compressed.
+/// Shared prefixes are cut at char boundaries.
+// used by build.rs only
+#[allow(dead_code)]
+pub fn front_code(blob: &str, buckets: &[Bucket]) -> String {
+    let mut shared_lens = String::new();
+    let mut rests = String::with_capacity(blob.len());
+
+    for bucket in buckets {
+        let byte_len = bucket.byte_len as usize;
+        let mut prev = "";
+
+        for idx in 0..bucket.count as usize {
+            let start = bucket.offset as usize + idx * byte_len;
+            let word = &blob[start..start + byte_len];
+
+            let shared = word
+                .char_indices()
+                .zip(prev.chars())
+                .take_while(|((_, c), other)| c == other)
+                .map(|((idx, c), _)| idx + c.len_utf8())
+                .take_while(|&len| len <= 0x7f)
+                .last()
+                .unwrap_or(0);
+
+            shared_lens.push(shared as u8 as char);
+            rests.push_str(&word[shared..]);
+            prev = word;
+        }
+    }
+
+    shared_lens + &rests
+}
+
+/// Reverts `front_code`, giving back the words blob of the buckets
+pub fn expand(coded: &str, buckets: &[Bucket]) -> String {
+    let count: usize = buckets.iter().map(|bucket| bucket.count as usize).sum();
+    let (shared_lens, rests) = coded.as_bytes().split_at(count);
+    let mut shared_lens = shared_lens.iter();
+    let mut pos = 0;
+
+    let mut blob: Vec<u8> = Vec::with_capacity(
+        buckets
+            .iter()
+            .map(|bucket| bucket.count as usize * bucket.byte_len as usize)
+            .sum(),
+    );
+
+    for bucket in buckets {
+        let byte_len = bucket.byte_len as usize;
+
+        for idx in 0..bucket.count as usize {
+            let shared = *shared_lens.next().expect("a shared length per word") as usize;
+            let rest = byte_len - shared;
+
+            if idx > 0 {
+                let prev = blob.len() - byte_len;
+                blob.extend_from_within(prev..prev + shared);
+            }
+
+            blob.extend_from_slice(&rests[pos..pos + rest]);
+            pos += rest;
+        }
+    }
+
+    String::from_utf8(blob).expect("front-coded words are valid UTF-8")
+}
@@ -1,16 +1,65 @@
+mod dict;
+mod dict_compiler;
 #[cfg(test)]
 mod tests;
 
-use std::collections::HashMap;
+use crate::bit_flags::FieldSize;
+pub use crate::error::XkpasswdError;
+use crate::policy::PasswordPolicy;
+pub(crate) use dict::Dict;
+use dict::WeightedPool;
+use rand::distributions::{Distribution, Uniform};
+use rand::{CryptoRng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::fs;
 use std::ops::Range;
-use std::str::*;
+use std::path::Path;
+use std::sync::Mutex;
 use wasm_bindgen::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum PaddingStrategy {
     Fixed,
     Adaptive(usize),
+    // words picked by length & padded with symbols to fit, never trimmed
+    Exact(usize),
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum SymbolMode {
+    // a single symbol repeated over each padding run
+    #[default]
+    Repeat,
+    // each padding symbol drawn separately
+    Independent,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum SeparatorMode {
+    // a single separator repeated between all words & digits
+    #[default]
+    Repeat,
+    // each gap between words & digits with its own separator
+    Independent,
 }
 
 #[derive(Debug)]
@@ -22,10 +71,18 @@ pub enum PaddingResult {
 
 #[wasm_bindgen]
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum Preset {
     Default,
+    #[cfg_attr(feature = "serde", serde(rename = "apple-id"))]
     AppleID,
+    #[cfg_attr(feature = "serde", serde(rename = "ntlm"))]
     WindowsNtlmV1,
+    #[cfg_attr(feature = "serde", serde(rename = "secq"))]
     SecurityQuestions,
     Web16,
     Web32,
@@ -35,29 +92,117 @@ pub enum Preset {
 
 #[wasm_bindgen]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-pub struct GuessTime {
-    pub years: usize,
-    pub months: u8,
-    pub days: u8,
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum AttackerProfile {
+    // guessing through a login form, rate limited by the service
+    #[default]
+    OnlineThrottled,
+    // cracking leaked hashes of a slow function, e.g. bcrypt
+    OfflineSlowHash,
+    // cracking leaked hashes of a fast function, e.g. SHA-1 or NTLM
+    OfflineFastHash,
+    // cracking fast hashes with many GPUs at once
+    GpuCluster,
 }
 
-impl fmt::Display for GuessTime {
+impl fmt::Display for AttackerProfile {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.years > 1_000_000_000 {
-            return write!(f, "more than a billion years");
+        let name = match self {
+            Self::OnlineThrottled => "an online throttled attacker",
+            Self::OfflineSlowHash => "an offline attacker against slow hashes",
+            Self::OfflineFastHash => "an offline attacker against fast hashes",
+            Self::GpuCluster => "a GPU cluster",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl AttackerProfile {
+    pub const fn default_guesses_per_sec(self) -> f64 {
+        match self {
+            Self::OnlineThrottled => 1e3,
+            Self::OfflineSlowHash => 1e5,
+            Self::OfflineFastHash => 1e10,
+            Self::GpuCluster => 1e12,
         }
+    }
+}
+
+/// Attacker profile with its guessing speed, the profile default one unless overridden
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attacker {
+    profile: AttackerProfile,
+    guesses_per_sec: f64,
+}
+
+impl Default for Attacker {
+    fn default() -> Self {
+        Self::new(AttackerProfile::default())
+    }
+}
 
-        if self.years > 1_000_000 {
-            return write!(f, "more than a million years");
+impl Attacker {
+    pub const fn new(profile: AttackerProfile) -> Self {
+        Attacker {
+            profile,
+            guesses_per_sec: profile.default_guesses_per_sec(),
         }
+    }
+
+    pub fn with_guesses_per_sec(&self, guesses_per_sec: f64) -> Result<Self, XkpasswdError> {
+        if !guesses_per_sec.is_finite() || guesses_per_sec <= 0.0 {
+            return Err(XkpasswdError::GuessesPerSec {
+                value: guesses_per_sec,
+            });
+        }
+
+        Ok(Attacker {
+            guesses_per_sec,
+            ..*self
+        })
+    }
+
+    pub fn profile(&self) -> AttackerProfile {
+        self.profile
+    }
 
-        if self.years > 1_000 {
-            return write!(f, "more than a thousand years");
+    pub fn guesses_per_sec(&self) -> f64 {
+        self.guesses_per_sec
+    }
+
+    /// Time to try every password of the given entropy
+    pub fn guess_time(&self, entropy: f64) -> GuessTime {
+        GuessTime::for_entropy(entropy, self.guesses_per_sec)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GuessTime {
+    pub seconds: f64,
+    pub years: f64,
+    pub months: u8,
+    pub days: u8,
+}
+
+impl fmt::Display for GuessTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // months & days are meaningless at this scale
+        if self.years >= 1e6 {
+            return write!(f, "{:.2e} years", self.years);
         }
 
         let mut comps: Vec<String> = vec![];
 
-        if self.years > 0 {
+        if self.years > 0.0 {
             comps.push(format!("{} years", self.years))
         }
 
@@ -70,7 +215,24 @@ impl fmt::Display for GuessTime {
         }
 
         if comps.is_empty() {
-            write!(f, "less than a day")
+            let seconds = self.seconds.floor() as u64;
+            let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+
+            if hours > 0 {
+                comps.push(format!("{} hours", hours))
+            }
+
+            if minutes > 0 {
+                comps.push(format!("{} minutes", minutes))
+            }
+
+            if seconds > 0 {
+                comps.push(format!("{} seconds", seconds))
+            }
+        }
+
+        if comps.is_empty() {
+            write!(f, "less than a second")
         } else {
             write!(f, "{}", comps.join(" "))
         }
@@ -78,41 +240,21 @@ impl fmt::Display for GuessTime {
 }
 
 impl GuessTime {
-    pub const GUESSES_PER_SEC: usize = 1_000;
     const SECONDS_PER_DAY: f64 = 86_400.0;
     const DAYS_PER_MONTH: f64 = 30.0;
     const DAYS_PER_YEAR: f64 = 365.0;
 
-    pub fn for_entropy(amount: usize) -> Self {
-        if amount > 64 {
-            return Self {
-                years: 1_000_000_001,
-                months: 0,
-                days: 0,
-            };
-        }
-
-        if amount > 54 {
-            return Self {
-                years: 1_000_001,
-                months: 0,
-                days: 0,
-            };
-        }
-
-        if amount > 44 {
-            return Self {
-                years: 1001,
-                months: 0,
-                days: 0,
-            };
-        }
-
-        let mut time_to_guess =
-            2.0f64.powi(amount as i32) / (Self::SECONDS_PER_DAY * Self::GUESSES_PER_SEC as f64);
+    pub fn for_entropy(amount: f64, guesses_per_sec: f64) -> Self {
+        // 2^amount overflows past ~1024 bits, while the time itself may not,
+        // otherwise saturating rather than going infinite
+        let seconds = match 2.0f64.powf(amount) / guesses_per_sec {
+            seconds if seconds.is_finite() => seconds,
+            _ => (amount - guesses_per_sec.log2()).exp2().min(f64::MAX),
+        };
+        let mut time_to_guess = seconds / Self::SECONDS_PER_DAY;
 
-        let years = (time_to_guess / Self::DAYS_PER_YEAR).floor() as usize;
-        time_to_guess -= Self::DAYS_PER_YEAR * years as f64;
+        let years = (time_to_guess / Self::DAYS_PER_YEAR).floor();
+        time_to_guess -= Self::DAYS_PER_YEAR * years;
 
         let months = (time_to_guess / Self::DAYS_PER_MONTH).floor() as u8;
         time_to_guess -= Self::DAYS_PER_MONTH * months as f64;
@@ -120,82 +262,236 @@ impl GuessTime {
         let days = time_to_guess.floor() as u8;
 
         Self {
-            days,
-            months,
+            seconds,
             years,
+            months,
+            days,
         }
     }
 }
 
 #[wasm_bindgen]
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entropy {
-    pub blind_min: usize,
-    pub blind_max: usize,
-    pub seen: usize,
+    pub blind_min: f64,
+    pub blind_max: f64,
+    pub seen: f64,
+    pub attacker: Attacker,
     pub guess_time: GuessTime,
 }
 
 impl fmt::Display for Entropy {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let blind_entropies = if self.blind_min == self.blind_max {
-            format!("{} bits", self.blind_min)
+            format!("{:.2} bits", self.blind_min)
         } else {
-            format!("between {} & {} bits", self.blind_min, self.blind_max)
+            format!("between {:.2} & {:.2} bits", self.blind_min, self.blind_max)
         };
 
         write!(
             f,
-            "{} blind and {} bits with full knowledge, which takes computers {} to break at {} guesses/sec",
-            blind_entropies, self.seen, self.guess_time, GuessTime::GUESSES_PER_SEC
+            "{} blind and {:.2} bits with full knowledge, which takes {} {} to break at {} guesses/sec",
+            blind_entropies,
+            self.seen,
+            self.attacker.profile,
+            self.guess_time,
+            self.attacker.guesses_per_sec
         )
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+impl Entropy {
+    /// Same entropy, with the guess time of another attacker
+    pub fn with_attacker(&self, attacker: Attacker) -> Self {
+        Entropy {
+            attacker,
+            guess_time: self.guess_time_for(attacker),
+            ..*self
+        }
+    }
+
+    pub fn guess_time_for(&self, attacker: Attacker) -> GuessTime {
+        attacker.guess_time(self.seen)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Language {
     English,
     French,
     German,
     Portuguese,
     Spanish,
+    // same languages, keeping accents & special letters
+    FrenchNative,
+    GermanNative,
+    PortugueseNative,
+    SpanishNative,
 }
 
-type Dict<'a> = HashMap<u8, Vec<&'a str>>;
-
 pub trait L10n {
     fn for_language(language: Language) -> Self;
 }
 
 pub trait Builder: Default + fmt::Display + Sized {
-    fn with_words_count(&self, words_count: u8) -> Result<Self, String>;
+    fn with_words_count(&self, words_count: u8) -> Result<Self, XkpasswdError>;
     fn with_word_lengths(
         &self,
         min_length: Option<u8>,
         max_length: Option<u8>,
-    ) -> Result<Self, String>;
+    ) -> Result<Self, XkpasswdError>;
     fn with_separators(&self, separators: &str) -> Self;
     fn with_padding_digits(&self, prefix: Option<u8>, suffix: Option<u8>) -> Self;
     fn with_padding_symbols(&self, symbols: &str) -> Self;
     fn with_padding_symbol_lengths(&self, prefix: Option<u8>, suffix: Option<u8>) -> Self;
-    fn with_padding_strategy(&self, strategy: PaddingStrategy) -> Result<Self, String>;
-    fn with_word_transforms(&self, transform: u8) -> Result<Self, String>;
+    fn with_padding_strategy(&self, strategy: PaddingStrategy) -> Result<Self, XkpasswdError>;
+    fn with_symbol_mode(&self, mode: SymbolMode) -> Self;
+    fn with_separator_mode(&self, mode: SeparatorMode) -> Self;
+    fn with_word_transforms(&self, transform: FieldSize) -> Result<Self, XkpasswdError>;
+    fn with_ascii_fold(&self, ascii_fold: bool) -> Self;
+    fn with_unique_prefix(&self, length: u8) -> Self;
+    fn with_substitutions(&self, rules: &[(char, String)]) -> Result<Self, XkpasswdError>;
+    fn with_length_range(
+        &self,
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+    ) -> Result<Self, XkpasswdError>;
     fn from_preset(preset: Preset) -> Self;
 }
 
+/// Parses a substitution rule written as `FROM=TO`, e.g. `|=!` or ` =_`,
+/// FROM being a single char & TO any string, empty to drop the char
+pub fn parse_substitution(rule: &str) -> Result<(char, String), XkpasswdError> {
+    let mut chars = rule.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(from), Some('=')) => Ok((from, chars.as_str().to_string())),
+        _ => Err(XkpasswdError::Substitution {
+            rule: rule.to_string(),
+            reason: "expected a single char, '=' & its replacement, e.g. '|=!'",
+        }),
+    }
+}
+
+/// Words a password is picked from, indexed in place rather than collected.
+/// Words are expected to be distinct.
+pub trait WordsPool {
+    fn len(&self) -> usize;
+    fn word(&self, idx: usize) -> &str;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Chance of the word to be drawn, all words being equally likely unless weighted
+    fn prob(&self, _idx: usize) -> f64 {
+        1.0 / self.len() as f64
+    }
+
+    /// Index of a word drawn following `prob`
+    fn draw<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        Uniform::from(0..self.len() as u64).sample(rng) as usize
+    }
+}
+
+impl<S: AsRef<str>> WordsPool for [S] {
+    fn len(&self) -> usize {
+        <[S]>::len(self)
+    }
+
+    fn word(&self, idx: usize) -> &str {
+        self[idx].as_ref()
+    }
+}
+
+impl<S: AsRef<str>> WordsPool for Vec<S> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn word(&self, idx: usize) -> &str {
+        self[idx].as_ref()
+    }
+}
+
+impl<S: AsRef<str>, const N: usize> WordsPool for [S; N] {
+    fn len(&self) -> usize {
+        N
+    }
+
+    fn word(&self, idx: usize) -> &str {
+        self[idx].as_ref()
+    }
+}
+
 pub trait Randomizer {
+    /// Derived from the settings & a words pool once, then shared by every password
+    /// drawn from that pool, e.g. tables too costly to rebuild for each password
+    type PoolState: fmt::Debug;
+
     fn word_lengths(&self) -> Range<u8>;
-    fn rand_words(&self, pool: &[&str]) -> Vec<String>;
-    fn rand_separator(&self) -> String;
-    fn rand_prefix(&self) -> (String, String);
-    fn rand_suffix(&self) -> (String, String);
-    fn adjust_padding(&self, pass_length: usize) -> PaddingResult;
-    fn calc_entropy(&self, pool_size: usize) -> Entropy;
+    fn pool_state<P: WordsPool + ?Sized>(&self, pool: &P) -> Self::PoolState;
+    fn rand_words_with<R: RngCore + CryptoRng, P: WordsPool + ?Sized>(
+        &self,
+        rng: &mut R,
+        pool: &P,
+        state: &Self::PoolState,
+    ) -> Vec<String>;
+    fn rand_separators<R: RngCore + CryptoRng>(&self, rng: &mut R, gaps: usize) -> Vec<String>;
+    fn rand_prefix<R: RngCore + CryptoRng>(&self, rng: &mut R) -> (String, String);
+    fn rand_suffix<R: RngCore + CryptoRng>(&self, rng: &mut R) -> (String, String);
+    fn adjust_padding<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+        pass_length: usize,
+    ) -> PaddingResult;
+    fn substitute_chars(&self, passwd: &str) -> String;
+    fn fits_length(&self, passwd: &str) -> bool;
+    fn check_pool_with<P: WordsPool + ?Sized>(
+        &self,
+        pool: &P,
+        state: &Self::PoolState,
+    ) -> Result<(), XkpasswdError>;
+    fn calc_entropy_with<P: WordsPool + ?Sized>(
+        &self,
+        pool: &P,
+        state: &Self::PoolState,
+    ) -> Entropy;
+
+    fn rand_words<R: RngCore + CryptoRng, P: WordsPool + ?Sized>(
+        &self,
+        rng: &mut R,
+        pool: &P,
+    ) -> Vec<String> {
+        self.rand_words_with(rng, pool, &self.pool_state(pool))
+    }
+
+    fn check_pool<P: WordsPool + ?Sized>(&self, pool: &P) -> Result<(), XkpasswdError> {
+        self.check_pool_with(pool, &self.pool_state(pool))
+    }
+
+    fn calc_entropy<P: WordsPool + ?Sized>(&self, pool: &P) -> Entropy {
+        self.calc_entropy_with(pool, &self.pool_state(pool))
+    }
+
+    /// Identifies settings whose entropy `Xkpasswd` may compute once & reuse,
+    /// none to compute it for every password
+    fn cache_key(&self) -> Option<u64> {
+        None
+    }
 }
 
+// entropies cached per settings, cleared once full as callers may try any number of settings
+const MAX_CACHED_ENTROPIES: usize = 64;
+
 #[derive(Debug)]
 pub struct Xkpasswd {
-    dict: Dict<'static>,
+    // a single dictionary unless several languages are mixed
+    dicts: Vec<(Dict, u32)>,
+    // keyed by `Randomizer::cache_key`, the dictionaries being fixed once built
+    entropies: Mutex<HashMap<u64, Entropy>>,
 }
 
 impl Default for Xkpasswd {
@@ -210,6 +506,14 @@ impl Default for Xkpasswd {
             Xkpasswd::for_language(Language::French)
         } else if cfg!(feature = "lang_pt") {
             Xkpasswd::for_language(Language::Portuguese)
+        } else if cfg!(feature = "lang_de_native") {
+            Xkpasswd::for_language(Language::GermanNative)
+        } else if cfg!(feature = "lang_es_native") {
+            Xkpasswd::for_language(Language::SpanishNative)
+        } else if cfg!(feature = "lang_fr_native") {
+            Xkpasswd::for_language(Language::FrenchNative)
+        } else if cfg!(feature = "lang_pt_native") {
+            Xkpasswd::for_language(Language::PortugueseNative)
         } else {
             panic!("no language bundled")
         }
@@ -218,97 +522,546 @@ impl Default for Xkpasswd {
 
 impl L10n for Xkpasswd {
     fn for_language(language: Language) -> Self {
-        let dict_bytes: &[u8] = match language {
-            #[cfg(feature = "lang_en")]
-            Language::English => include_bytes!("../assets/dict_en.txt"),
-            #[cfg(feature = "lang_de")]
-            Language::German => include_bytes!("../assets/dict_de.txt"),
-            #[cfg(feature = "lang_es")]
-            Language::Spanish => include_bytes!("../assets/dict_es.txt"),
-            #[cfg(feature = "lang_fr")]
-            Language::French => include_bytes!("../assets/dict_fr.txt"),
-            #[cfg(feature = "lang_pt")]
-            Language::Portuguese => include_bytes!("../assets/dict_pt.txt"),
-            #[allow(unreachable_patterns)]
-            _ => panic!("no language bundled"),
-        };
-
-        let dict = load_dict(dict_bytes);
-        Xkpasswd { dict }
+        Xkpasswd::from_dicts(vec![(Dict::bundled(language).clone(), 1)])
     }
 }
 
 impl Xkpasswd {
-    pub fn gen_pass<S: Randomizer>(&self, settings: &S) -> (String, Entropy) {
-        let mut all_words: Vec<&str> = vec![];
+    /// Builds a generator from any list of words, grouping them by length.
+    /// Words are lowercased & deduplicated, non-alphabetic entries are skipped.
+    pub fn from_words<I, S>(words: I) -> Result<Self, XkpasswdError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut valid_words: Vec<String> = vec![];
+        let mut seen_words: HashSet<String> = HashSet::new();
+
+        for word in words {
+            let word = word.as_ref().trim().to_lowercase();
+
+            if word.is_empty() || !word.chars().all(char::is_alphabetic) {
+                log::warn!("skipping invalid word in dictionary: {}", word);
+                continue;
+            }
+
+            if u8::try_from(word.chars().count()).is_err() {
+                log::warn!("skipping too long word in dictionary: {}", word);
+                continue;
+            }
+
+            if seen_words.insert(word.clone()) {
+                valid_words.push(word);
+            }
+        }
+
+        if valid_words.is_empty() {
+            return Err(XkpasswdError::Dictionary(
+                "no valid words found in dictionary".to_string(),
+            ));
+        }
+
+        log::debug!("loaded custom dict with {} entries", valid_words.len());
 
-        settings.word_lengths().for_each(|len| {
-            if let Some(words) = self.dict.get(&len) {
-                all_words.extend(words);
-            };
+        Ok(Xkpasswd::from_dicts(vec![(
+            Dict::from_words(&valid_words),
+            1,
+        )]))
+    }
+
+    /// Draws words from the union of several bundled languages, all words being equally likely.
+    /// Words shared by languages are kept once.
+    pub fn for_languages(languages: &[Language]) -> Result<Self, XkpasswdError> {
+        let dicts = Self::unique_dicts(languages.iter().map(|&language| (language, 1)))?;
+
+        // a single language stays borrowed
+        if let [(dict, _)] = dicts.as_slice() {
+            return Ok(Xkpasswd::from_dicts(vec![(dict.clone(), 1)]));
+        }
+
+        let words: Vec<&str> = dicts
+            .iter()
+            .flat_map(|(dict, _)| {
+                let words = dict.words();
+                (0..words.len()).map(move |idx| words.get(idx))
+            })
+            .collect();
+
+        Ok(Xkpasswd::from_dicts(vec![(Dict::from_words(&words), 1)]))
+    }
+
+    /// Draws a language by weight first, then one of its words, so that a language weighted 2
+    /// gives twice as many words as one weighted 1 whatever the sizes of their dictionaries.
+    /// Words shared by languages are kept only in the first one, a password never
+    /// containing the same word twice, and seen entropy follows the weighted draws.
+    pub fn for_weighted_languages(weights: &[(Language, u32)]) -> Result<Self, XkpasswdError> {
+        for &(language, weight) in weights {
+            if weight == 0 {
+                return Err(XkpasswdError::LanguageWeight {
+                    language,
+                    value: weight,
+                    min: 1,
+                });
+            }
+        }
+
+        Ok(Xkpasswd::from_dicts(Self::unique_dicts(
+            weights.iter().copied(),
+        )?))
+    }
+
+    // repeated languages add up, words shared by languages are kept in the first one only
+    fn unique_dicts<I>(weights: I) -> Result<Vec<(Dict, u32)>, XkpasswdError>
+    where
+        I: IntoIterator<Item = (Language, u32)>,
+    {
+        let mut merged: Vec<(Language, u32)> = vec![];
+
+        for (language, weight) in weights {
+            match merged.iter_mut().find(|(lang, _)| *lang == language) {
+                Some((_, total)) => *total += weight,
+                None => merged.push((language, weight)),
+            }
+        }
+
+        if merged.is_empty() {
+            return Err(XkpasswdError::NoLanguage);
+        }
+
+        let mut seen_words: HashSet<&'static str> = HashSet::new();
+
+        Ok(merged
+            .into_iter()
+            .map(|(language, weight)| {
+                let dict = Dict::bundled(language);
+                let words = dict.words();
+                let unique_words: Vec<&str> = (0..words.len())
+                    .map(|idx| words.get(idx))
+                    .filter(|word| seen_words.insert(word))
+                    .collect();
+
+                // untouched dictionaries stay borrowed
+                let dict = if unique_words.len() == words.len() {
+                    dict.clone()
+                } else {
+                    Dict::from_words(&unique_words)
+                };
+
+                (dict, weight)
+            })
+            .collect())
+    }
+
+    /// Accepts either the bundled `len:word,word,...` format
+    /// or a plain list with one word per line, e.g. EFF or diceware word lists
+    /// where the leading dice numbers are ignored.
+    pub fn from_dict_str(dict_str: &str) -> Result<Self, XkpasswdError> {
+        let words = dict_str.lines().flat_map(|line| {
+            let line = line.trim();
+
+            match line.split_once(':') {
+                Some((len_str, words_csv)) if len_str.parse::<u8>().is_ok() => {
+                    words_csv.split(',').collect::<Vec<&str>>()
+                }
+                _ => line.split_whitespace().last().into_iter().collect(),
+            }
         });
 
-        let separator = &settings.rand_separator();
-        let mut words: Vec<String> = vec![];
+        Self::from_words(words)
+    }
+
+    pub fn from_dict_file<P: AsRef<Path>>(path: P) -> Result<Self, XkpasswdError> {
+        let path = path.as_ref();
+
+        match fs::read_to_string(path) {
+            Ok(dict_str) => Self::from_dict_str(&dict_str),
+            Err(err) => Err(XkpasswdError::Dictionary(format!(
+                "unable to read dictionary at {}: {}",
+                path.display(),
+                err
+            ))),
+        }
+    }
+
+    /// Never picks the given words, on top of the built-in blocklist of bundled languages.
+    /// Words are matched case-insensitively, the entropy reflects the reduced pool.
+    pub fn with_excluded_words<I, S>(&self, words: I) -> Result<Self, XkpasswdError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let excluded: HashSet<String> = words
+            .into_iter()
+            .map(|word| word.as_ref().trim().to_lowercase())
+            .collect();
+
+        let dicts: Vec<(Dict, u32)> = self
+            .dicts
+            .iter()
+            .filter_map(|(dict, weight)| {
+                let words = dict.words();
+                let kept_words: Vec<&str> = (0..words.len())
+                    .map(|idx| words.get(idx))
+                    .filter(|word| !excluded.contains(*word))
+                    .collect();
 
-        let (prefix_symbols, prefix_digits) = settings.rand_prefix();
-        if !prefix_digits.is_empty() {
-            words.push(prefix_digits);
+                log::debug!(
+                    "excluded {} word(s) from dictionary",
+                    words.len() - kept_words.len()
+                );
+
+                // untouched dictionaries stay borrowed, emptied ones are dropped from the mix
+                match kept_words.len() {
+                    0 => None,
+                    len if len == words.len() => Some((dict.clone(), *weight)),
+                    _ => Some((Dict::from_words(&kept_words), *weight)),
+                }
+            })
+            .collect();
+
+        if dicts.is_empty() {
+            return Err(XkpasswdError::Dictionary(
+                "no words left after exclusions".to_string(),
+            ));
         }
 
-        words.extend(settings.rand_words(&all_words));
+        Ok(Xkpasswd::from_dicts(dicts))
+    }
+
+    /// Same format as the built-in blocklists: one word per line, `#` starting a comment line
+    pub fn with_excluded_words_file<P: AsRef<Path>>(&self, path: P) -> Result<Self, XkpasswdError> {
+        let path = path.as_ref();
 
-        let (suffix_digits, suffix_symbols) = settings.rand_suffix();
-        if !suffix_digits.is_empty() {
-            words.push(suffix_digits);
+        match fs::read_to_string(path) {
+            Ok(words_str) => self.with_excluded_words(
+                words_str
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#')),
+            ),
+            Err(err) => Err(XkpasswdError::Dictionary(format!(
+                "unable to read excluded words at {}: {}",
+                path.display(),
+                err
+            ))),
         }
+    }
+
+    /// Fails when passwords can't be generated as the settings require from this dictionary,
+    /// same as `Xkpasswd::check_settings`
+    pub fn gen_pass<S: Randomizer>(
+        &self,
+        settings: &S,
+    ) -> Result<(String, Entropy), XkpasswdError> {
+        self.gen_pass_with_rng(settings, &mut rand::thread_rng())
+    }
+
+    /// Deterministic generation: the same seed, settings & dictionary
+    /// always produce the same password on any platform with the same crate version,
+    /// later versions may draw differently.
+    pub fn gen_pass_with_seed<S: Randomizer>(
+        &self,
+        settings: &S,
+        seed: u64,
+    ) -> Result<(String, Entropy), XkpasswdError> {
+        self.gen_pass_with_rng(settings, &mut ChaCha20Rng::seed_from_u64(seed))
+    }
 
-        let passwd = format!(
-            "{}{}{}",
-            prefix_symbols,
-            words.join(separator),
-            suffix_symbols
-        );
-
-        let passwd = match settings.adjust_padding(passwd.len()) {
-            PaddingResult::Unchanged => passwd,
-            PaddingResult::TrimTo(len) => passwd[..len].to_string(),
-            PaddingResult::Pad(padded_symbols) => passwd + &padded_symbols,
+    pub fn gen_pass_with_rng<S: Randomizer, R: RngCore + CryptoRng>(
+        &self,
+        settings: &S,
+        rng: &mut R,
+    ) -> Result<(String, Entropy), XkpasswdError> {
+        let pool = self.words_pool(settings);
+        let state = settings.pool_state(&pool);
+        settings.check_pool_with(&pool, &state)?;
+
+        let passwd = assemble_passwd(settings, rng, &pool, &state)?;
+        let entropy = self.cached_entropy(settings, &pool, Some(&state));
+
+        Ok((passwd, entropy))
+    }
+
+    /// Fails when passwords can't be generated as the settings require from this dictionary,
+    /// e.g. no words fitting an exact padding length
+    pub fn check_settings<S: Randomizer>(&self, settings: &S) -> Result<(), XkpasswdError> {
+        settings.check_pool(&self.words_pool(settings))
+    }
+
+    /// Entropy of passwords generated with the settings, without generating any
+    pub fn calc_entropy<S: Randomizer>(&self, settings: &S) -> Entropy {
+        self.cached_entropy(settings, &self.words_pool(settings), None)
+    }
+
+    pub fn gen_passes<S: Randomizer>(
+        &self,
+        settings: &S,
+        count: usize,
+    ) -> Result<Vec<(String, Entropy)>, XkpasswdError> {
+        self.gen_passes_with_rng(settings, count, &mut rand::thread_rng())
+    }
+
+    pub fn gen_passes_with_seed<S: Randomizer>(
+        &self,
+        settings: &S,
+        count: usize,
+        seed: u64,
+    ) -> Result<Vec<(String, Entropy)>, XkpasswdError> {
+        self.gen_passes_with_rng(settings, count, &mut ChaCha20Rng::seed_from_u64(seed))
+    }
+
+    pub fn gen_passes_with_rng<S: Randomizer, R: RngCore + CryptoRng>(
+        &self,
+        settings: &S,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<(String, Entropy)>, XkpasswdError> {
+        self.iter_passes(settings, rng)?.take(count).collect()
+    }
+
+    /// Regenerates until a password complies with the policy, giving up after
+    /// `PasswordPolicy::max_attempts` tries. The entropy is the one of the settings
+    /// alone, i.e. an upper bound as rejected passwords shrink the search space.
+    pub fn gen_pass_matching<S: Randomizer>(
+        &self,
+        settings: &S,
+        policy: &PasswordPolicy,
+    ) -> Result<(String, Entropy), XkpasswdError> {
+        self.gen_pass_matching_with_rng(settings, policy, &mut rand::thread_rng())
+    }
+
+    pub fn gen_pass_matching_with_seed<S: Randomizer>(
+        &self,
+        settings: &S,
+        policy: &PasswordPolicy,
+        seed: u64,
+    ) -> Result<(String, Entropy), XkpasswdError> {
+        self.gen_pass_matching_with_rng(settings, policy, &mut ChaCha20Rng::seed_from_u64(seed))
+    }
+
+    pub fn gen_pass_matching_with_rng<S: Randomizer, R: RngCore + CryptoRng>(
+        &self,
+        settings: &S,
+        policy: &PasswordPolicy,
+        rng: &mut R,
+    ) -> Result<(String, Entropy), XkpasswdError> {
+        self.iter_passes(settings, rng)?.next_matching(policy)
+    }
+
+    pub fn gen_passes_matching<S: Randomizer>(
+        &self,
+        settings: &S,
+        policy: &PasswordPolicy,
+        count: usize,
+    ) -> Result<Vec<(String, Entropy)>, XkpasswdError> {
+        self.gen_passes_matching_with_rng(settings, policy, count, &mut rand::thread_rng())
+    }
+
+    pub fn gen_passes_matching_with_seed<S: Randomizer>(
+        &self,
+        settings: &S,
+        policy: &PasswordPolicy,
+        count: usize,
+        seed: u64,
+    ) -> Result<Vec<(String, Entropy)>, XkpasswdError> {
+        self.gen_passes_matching_with_rng(
+            settings,
+            policy,
+            count,
+            &mut ChaCha20Rng::seed_from_u64(seed),
+        )
+    }
+
+    pub fn gen_passes_matching_with_rng<S: Randomizer, R: RngCore + CryptoRng>(
+        &self,
+        settings: &S,
+        policy: &PasswordPolicy,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<(String, Entropy)>, XkpasswdError> {
+        let mut passwds = self.iter_passes(settings, rng)?;
+        (0..count).map(|_| passwds.next_matching(policy)).collect()
+    }
+
+    /// Endless stream of passwords sharing a single words pool, which is built & checked
+    /// along with its `Randomizer::PoolState` only once regardless of how many passwords are taken.
+    /// A password fails only if it runs out of attempts to fit the length range.
+    pub fn iter_passes<'a, S: Randomizer, R: RngCore + CryptoRng>(
+        &'a self,
+        settings: &'a S,
+        rng: R,
+    ) -> Result<Passwds<'a, S, R>, XkpasswdError> {
+        let pool = self.words_pool(settings);
+        let state = settings.pool_state(&pool);
+        settings.check_pool_with(&pool, &state)?;
+
+        let entropy = self.cached_entropy(settings, &pool, Some(&state));
+
+        Ok(Passwds {
+            pool,
+            state,
+            settings,
+            rng,
+            entropy,
+        })
+    }
+
+    fn from_dicts(dicts: Vec<(Dict, u32)>) -> Self {
+        Xkpasswd {
+            dicts,
+            entropies: Mutex::default(),
+        }
+    }
+
+    // the entropy model walks the whole pool, so it's computed once per settings,
+    // the pool state being built only if missing & needed
+    fn cached_entropy<S: Randomizer>(
+        &self,
+        settings: &S,
+        pool: &WeightedPool,
+        state: Option<&S::PoolState>,
+    ) -> Entropy {
+        let calc_entropy = || match state {
+            Some(state) => settings.calc_entropy_with(pool, state),
+            None => settings.calc_entropy(pool),
         };
 
-        let entropy = settings.calc_entropy(all_words.len());
+        let Some(key) = settings.cache_key() else {
+            return calc_entropy();
+        };
 
-        (passwd, entropy)
+        if let Some(entropy) = self.entropies.lock().unwrap().get(&key) {
+            return *entropy;
+        }
+
+        // computed unlocked, concurrent callers at worst computing it twice
+        let entropy = calc_entropy();
+        let mut entropies = self.entropies.lock().unwrap();
+
+        if entropies.len() >= MAX_CACHED_ENTROPIES {
+            entropies.clear();
+        }
+
+        entropies.insert(key, entropy);
+        entropy
+    }
+
+    pub(crate) fn words_pool<S: Randomizer>(&self, settings: &S) -> WeightedPool<'_> {
+        let pools = self
+            .dicts
+            .iter()
+            .map(|(dict, weight)| (dict.pool(settings.word_lengths()), *weight))
+            .collect();
+
+        WeightedPool::new(pools)
     }
 }
 
-fn load_dict(dict_bytes: &[u8]) -> Dict<'_> {
-    let dict_str = from_utf8(dict_bytes).unwrap_or("").trim();
-    let mut dict: Dict = HashMap::new();
+#[derive(Debug)]
+pub struct Passwds<'a, S: Randomizer, R: RngCore + CryptoRng> {
+    pool: WeightedPool<'a>,
+    state: S::PoolState,
+    settings: &'a S,
+    rng: R,
+    entropy: Entropy,
+}
 
-    log::debug!("loaded raw dict with {} lines", dict_str.lines().count());
+impl<S: Randomizer, R: RngCore + CryptoRng> Passwds<'_, S, R> {
+    /// Skips passwords breaking the policy, up to `PasswordPolicy::max_attempts` in a row
+    pub fn next_matching(
+        &mut self,
+        policy: &PasswordPolicy,
+    ) -> Result<(String, Entropy), XkpasswdError> {
+        let mut attempts = 0;
 
-    dict_str.lines().for_each(|line| {
-        let mut comps = line.trim().split(':');
+        loop {
+            let passwd = assemble_passwd(self.settings, &mut self.rng, &self.pool, &self.state)?;
+            attempts += 1;
 
-        if let Some(len_str) = comps.next() {
-            let len = match len_str.parse::<u8>() {
-                Ok(l) => l,
-                Err(_) => {
-                    log::warn!("skipping invalid word length in dictionary: {}", len_str);
-                    return;
+            match policy.check(&passwd) {
+                Ok(_) => return Ok((passwd, self.entropy)),
+                Err(violation) if attempts >= policy.max_attempts() => {
+                    return Err(XkpasswdError::Policy {
+                        attempts,
+                        violation,
+                    })
                 }
-            };
-            let words_csv = comps.next().unwrap_or("");
-            let words: Vec<&str> = words_csv.split(',').collect();
-            dict.insert(len, words);
+                Err(violation) => log::debug!("rejected password: {}", violation),
+            }
+        }
+    }
+}
+
+impl<S: Randomizer, R: RngCore + CryptoRng> Iterator for Passwds<'_, S, R> {
+    type Item = Result<(String, Entropy), XkpasswdError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let passwd = assemble_passwd(self.settings, &mut self.rng, &self.pool, &self.state);
+        Some(passwd.map(|passwd| (passwd, self.entropy)))
+    }
+}
+
+// gives up regenerating passwords out of the length range
+pub(crate) const MAX_LENGTH_ATTEMPTS: usize = 10_000;
+
+fn assemble_passwd<S: Randomizer, R: RngCore + CryptoRng, P: WordsPool + ?Sized>(
+    settings: &S,
+    rng: &mut R,
+    pool: &P,
+    state: &S::PoolState,
+) -> Result<String, XkpasswdError> {
+    for _ in 0..MAX_LENGTH_ATTEMPTS {
+        let passwd = assemble_candidate(settings, rng, pool, state);
+
+        if settings.fits_length(&passwd) {
+            return Ok(passwd);
         }
-    });
+    }
+
+    Err(XkpasswdError::LengthRangeAttempts {
+        attempts: MAX_LENGTH_ATTEMPTS,
+    })
+}
+
+fn assemble_candidate<S: Randomizer, R: RngCore + CryptoRng, P: WordsPool + ?Sized>(
+    settings: &S,
+    rng: &mut R,
+    pool: &P,
+    state: &S::PoolState,
+) -> String {
+    let mut words: Vec<String> = vec![];
+
+    let (prefix_symbols, prefix_digits) = settings.rand_prefix(rng);
+    if !prefix_digits.is_empty() {
+        words.push(prefix_digits);
+    }
+
+    words.extend(settings.rand_words_with(rng, pool, state));
+
+    let (suffix_digits, suffix_symbols) = settings.rand_suffix(rng);
+    if !suffix_digits.is_empty() {
+        words.push(suffix_digits);
+    }
+
+    let separators = settings.rand_separators(rng, words.len().saturating_sub(1));
+    let mut passwd = prefix_symbols;
+
+    for (idx, word) in words.iter().enumerate() {
+        if idx > 0 {
+            passwd.push_str(&separators[idx - 1]);
+        }
+        passwd.push_str(word);
+    }
+
+    passwd.push_str(&suffix_symbols);
 
-    log::debug!(
-        "parsed dict with {:?} entries",
-        dict.iter().fold(0, |acc, cur| acc + cur.1.len())
-    );
+    // lengths are counted in chars, multi-byte words & symbols shouldn't be cut in half
+    let passwd = match settings.adjust_padding(rng, passwd.chars().count()) {
+        PaddingResult::Unchanged => passwd,
+        PaddingResult::TrimTo(len) => passwd.chars().take(len).collect(),
+        PaddingResult::Pad(padded_symbols) => passwd + &padded_symbols,
+    };
 
-    dict
+    // last stage, so that no banned char slips in through padding
+    settings.substitute_chars(&passwd)
 }
@@ -1,4 +1,7 @@
 use super::*;
+use crate::bit_flags::{BitFlags, FieldSize, WordTransform};
+use crate::policy::{CharClass, PolicyViolation};
+use crate::settings::Settings;
 
 struct MockSettings {
     padding_digits: (usize, usize),
@@ -7,31 +10,40 @@ struct MockSettings {
 }
 
 impl Randomizer for MockSettings {
+    type PoolState = ();
+
     fn word_lengths(&self) -> Range<u8> {
         3..4
     }
 
-    fn rand_words(&self, _: &[&str]) -> Vec<String> {
+    fn pool_state<P: WordsPool + ?Sized>(&self, _: &P) {}
+
+    fn rand_words_with<R: RngCore + CryptoRng, P: WordsPool + ?Sized>(
+        &self,
+        _: &mut R,
+        _: &P,
+        _: &(),
+    ) -> Vec<String> {
         vec!["foo".to_string(), "bar".to_string(), "baz".to_string()]
     }
 
-    fn rand_separator(&self) -> String {
-        ".".to_string()
+    fn rand_separators<R: RngCore + CryptoRng>(&self, _: &mut R, gaps: usize) -> Vec<String> {
+        vec![".".to_string(); gaps]
     }
 
-    fn rand_prefix(&self) -> (String, String) {
+    fn rand_prefix<R: RngCore + CryptoRng>(&self, _: &mut R) -> (String, String) {
         let prefix_symbols = &"?????"[..self.padding_symbols.0];
         let prefix_digits = &"12345"[..self.padding_digits.0];
         (prefix_symbols.to_string(), prefix_digits.to_string())
     }
 
-    fn rand_suffix(&self) -> (String, String) {
+    fn rand_suffix<R: RngCore + CryptoRng>(&self, _: &mut R) -> (String, String) {
         let suffix_symbols = &"!!!!!!"[..self.padding_symbols.1];
         let suffix_digits = &"67890"[..self.padding_digits.1];
         (suffix_digits.to_string(), suffix_symbols.to_string())
     }
 
-    fn adjust_padding(&self, _: usize) -> PaddingResult {
+    fn adjust_padding<R: RngCore + CryptoRng>(&self, _: &mut R, _: usize) -> PaddingResult {
         match &self.padding_result {
             PaddingResult::Unchanged => PaddingResult::Unchanged,
             PaddingResult::TrimTo(len) => PaddingResult::TrimTo(*len),
@@ -39,148 +51,725 @@ impl Randomizer for MockSettings {
         }
     }
 
-    fn calc_entropy(&self, _: usize) -> Entropy {
+    fn substitute_chars(&self, passwd: &str) -> String {
+        passwd.to_string()
+    }
+
+    fn fits_length(&self, _: &str) -> bool {
+        true
+    }
+
+    fn check_pool_with<P: WordsPool + ?Sized>(&self, _: &P, _: &()) -> Result<(), XkpasswdError> {
+        Ok(())
+    }
+
+    fn calc_entropy_with<P: WordsPool + ?Sized>(&self, _: &P, _: &()) -> Entropy {
         Entropy::default()
     }
 }
 
+// words with a length in chars within the range, in dictionary order
+fn dict_words(pass: &Xkpasswd, lengths: Range<u8>) -> Vec<&str> {
+    let pool = pass.dicts[0].0.pool(lengths);
+    (0..pool.len()).map(|idx| pool.get(idx)).collect()
+}
+
+#[test]
+fn test_compile_words() {
+    let (blob, buckets) = dict_compiler::compile_words::<&str>(&[]);
+    assert_eq!("", blob);
+    assert!(buckets.is_empty());
+
+    let (blob, buckets) =
+        dict_compiler::compile_words(&["foo", "an", "été", "bar", "do", "août", "ours", "éte"]);
+
+    // by chars then bytes, otherwise in the original order
+    assert_eq!("andofoobaréteétéoursaoût", blob);
+    assert_eq!(
+        vec![
+            (2, 2, 0, 0, 2),
+            (3, 3, 4, 2, 2),
+            (3, 4, 10, 4, 1),
+            (3, 5, 14, 5, 1),
+            (4, 4, 19, 6, 1),
+            (4, 5, 23, 7, 1),
+        ],
+        buckets
+            .iter()
+            .map(|bucket| (
+                bucket.char_len,
+                bucket.byte_len,
+                bucket.offset,
+                bucket.first,
+                bucket.count
+            ))
+            .collect::<Vec<(u8, u32, u32, u32, u32)>>()
+    );
+}
+
+#[test]
+fn test_front_code() {
+    let (blob, buckets) = dict_compiler::compile_words(&[
+        "an", "do", "fob", "foo", "bar", "été", "étè", "ours", "août",
+    ]);
+    let coded = dict_compiler::front_code(&blob, &buckets);
+
+    // shared prefixes stop at char boundaries, each bucket starting afresh
+    assert_eq!("\0\0\0\u{2}\0\0\u{3}\0\0andofobobarétéèoursaoût", coded);
+    assert_eq!(blob, dict_compiler::expand(&coded, &buckets));
+
+    for language in [Language::English, Language::FrenchNative] {
+        let dict = Dict::bundled(language);
+        let words = dict.words();
+        assert!(dict.contains(words.get(0)));
+        assert!(dict.contains(words.get(words.len() - 1)));
+
+        // expanded once, clones sharing the same words
+        assert_eq!(words.get(0).as_ptr(), dict.clone().words().get(0).as_ptr());
+    }
+}
+
+#[test]
+fn test_dict_pool() {
+    let pass = Xkpasswd::from_words(["été", "foo", "an", "août", "bar", "ours", "éte"]).unwrap();
+
+    assert_eq!(vec!["an"], dict_words(&pass, 0..3));
+    assert_eq!(vec!["foo", "bar", "éte", "été"], dict_words(&pass, 3..4));
+
+    // looked up by length in chars & bytes
+    let dict = &pass.dicts[0].0;
+    assert!(dict.contains("éte") && dict.contains("été") && dict.contains("an"));
+    assert!(!dict.contains("ete") && !dict.contains("fo") && !dict.contains(""));
+    assert_eq!(
+        vec!["foo", "bar", "éte", "été", "ours", "août"],
+        dict_words(&pass, 3..10)
+    );
+    assert!(dict_words(&pass, 5..10).is_empty());
+
+    let pool = pass.dicts[0].0.pool(2..5);
+    assert_eq!(7, WordsPool::len(&pool));
+    assert!(!WordsPool::is_empty(&pool));
+    assert_eq!("été", pool.word(4));
+    assert_eq!("août", pool.word(6));
+    assert!(WordsPool::is_empty(&pass.dicts[0].0.pool(8..9)));
+}
+
 #[test]
-fn test_load_dict_blank() {
-    let dict = load_dict(&[]);
-    assert!(dict.is_empty());
+fn test_xkpasswd_from_words() {
+    let pass = Xkpasswd::from_words(["Foo", "bar", "foo", "café", "ab1c", "", "  baz  "]).unwrap();
 
-    let dict_bytes = "".as_bytes();
-    let dict = load_dict(dict_bytes);
-    assert!(dict.is_empty());
+    assert_eq!(4, dict_words(&pass, 0..u8::MAX).len());
+    assert_eq!(vec!["foo", "bar", "baz"], dict_words(&pass, 3..4));
+    assert_eq!(vec!["café"], dict_words(&pass, 4..5));
+
+    let result = Xkpasswd::from_words(["12", "a-b", " "]);
+    assert_eq!(
+        XkpasswdError::Dictionary("no valid words found in dictionary".to_string()),
+        result.err().unwrap()
+    );
 }
 
 #[test]
-fn test_load_dict_valid_data() {
+fn test_xkpasswd_gen_pass_multi_bytes() {
+    let pass = Xkpasswd::from_words([
+        "éléphant",
+        "château",
+        "forêt",
+        "straße",
+        "über",
+        "niño",
+        "pingüino",
+        "coração",
+    ])
+    .unwrap();
+
+    let settings = Settings::default()
+        .with_words_count(3)
+        .unwrap()
+        .with_word_transforms(
+            WordTransform::Titlecase | WordTransform::Uppercase | WordTransform::InversedTitlecase,
+        )
+        .unwrap()
+        .with_separators("⟪⟫·")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("€🦀");
+
+    for len in [8, 16, 40] {
+        let settings = settings
+            .with_padding_strategy(PaddingStrategy::Adaptive(len))
+            .unwrap();
+
+        for seed in 0..20 {
+            let (passwd, _) = pass.gen_pass_with_seed(&settings, seed).unwrap();
+            assert_eq!(len, passwd.chars().count(), "{}", passwd);
+        }
+    }
+
+    let settings = settings.with_padding_symbol_lengths(Some(2), Some(2));
+
+    for seed in 0..20 {
+        let (passwd, _) = pass.gen_pass_with_seed(&settings, seed).unwrap();
+        let symbols: Vec<char> = passwd.chars().collect();
+        let (prefix, suffix) = (&symbols[..2], &symbols[symbols.len() - 2..]);
+        assert!(prefix
+            .iter()
+            .chain(suffix)
+            .all(|char| "€🦀".contains(*char)));
+        assert!(passwd.chars().any(|char| "⟪⟫·".contains(char)));
+    }
+}
+
+#[test]
+fn test_xkpasswd_gen_pass_exact() {
+    let pass =
+        Xkpasswd::from_words(["éléphant", "forêt", "über", "niño", "pingüino", "coração"]).unwrap();
+
+    let settings = Settings::default()
+        .with_words_count(3)
+        .unwrap()
+        .with_word_transforms(WordTransform::Titlecase | WordTransform::Uppercase)
+        .unwrap()
+        .with_separators("·")
+        .with_padding_digits(Some(0), Some(2))
+        .with_padding_symbols("€🦀");
+
+    for len in [19, 24, 30] {
+        let settings = settings
+            .with_padding_strategy(PaddingStrategy::Exact(len))
+            .unwrap();
+        assert_eq!(Ok(()), pass.check_settings(&settings));
+
+        for seed in 0..20 {
+            let (passwd, _) = pass.gen_pass_with_seed(&settings, seed).unwrap();
+            assert_eq!(len, passwd.chars().count(), "{}", passwd);
+
+            // words & digits kept whole, symbols only padded after them
+            let blocks: Vec<&str> = passwd.trim_end_matches(['€', '🦀']).split('·').collect();
+            assert_eq!(4, blocks.len(), "{}", passwd);
+            assert!(blocks[3].chars().all(|char| char.is_ascii_digit()));
+        }
+    }
+
+    // too short for 3 words of 4 chars, 2 digits & 3 separators
+    let settings = settings
+        .with_padding_strategy(PaddingStrategy::Exact(16))
+        .unwrap();
+    let err = pass.check_settings(&settings).unwrap_err();
+    assert_eq!("adaptive_length", err.field());
+
+    // checked before generating, rather than falling back to the natural length
+    assert_eq!(Err(err.clone()), pass.gen_pass_with_seed(&settings, 42));
+    assert_eq!(
+        Some(err),
+        pass.iter_passes(&settings, ChaCha20Rng::seed_from_u64(42))
+            .err()
+    );
+}
+
+#[test]
+fn test_xkpasswd_gen_pass_length_range() {
+    let pass =
+        Xkpasswd::from_words(["éléphant", "forêt", "über", "niño", "pingüino", "coração"]).unwrap();
+
+    // 2 words of 4 ~ 8 chars & a separator
+    let settings = Settings::default()
+        .with_words_count(2)
+        .unwrap()
+        .with_separators("-")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("")
+        .with_length_range(Some(10), Some(12))
+        .unwrap();
+    assert_eq!(Ok(()), pass.check_settings(&settings));
+
+    for seed in 0..20 {
+        let (passwd, _) = pass.gen_pass_with_seed(&settings, seed).unwrap();
+        let len = passwd.chars().count();
+        assert!((10..=12).contains(&len), "{}", passwd);
+    }
+
+    // no password fits, nothing is generated
+    let settings = settings.with_length_range(Some(18), Some(20)).unwrap();
+    let err = pass.check_settings(&settings).unwrap_err();
+    assert_eq!("min_length", err.field());
+    assert_eq!(Err(err.clone()), pass.gen_pass(&settings));
+    assert_eq!(Err(err), pass.gen_passes(&settings, 3));
+
+    // regenerated a bounded number of times when not checked
+    let pool = pass.words_pool(&settings);
+    assert_eq!(
+        Err(XkpasswdError::LengthRangeAttempts {
+            attempts: MAX_LENGTH_ATTEMPTS
+        }),
+        assemble_passwd(
+            &settings,
+            &mut ChaCha20Rng::seed_from_u64(42),
+            &pool,
+            &settings.pool_state(&pool)
+        )
+    );
+}
+
+#[test]
+fn test_xkpasswd_from_dict_str() {
     let table = [
-        "2:an,do\n3:foo,bar",
-        r#"
-        2:an,do
-        3:foo,bar
-        "#,
+        // bundled format
+        "4:abbe,abby\n5:about,above",
+        // one word per line
+        "abbe\nabby\n\nabout\nabove\n",
+        // diceware format
+        "11111\tabbe\n11112\tabby\n11113\tabout\n11114\tabove",
     ];
 
     for dict_str in table {
-        let dict_bytes = dict_str.as_bytes();
-        let dict = load_dict(dict_bytes);
+        let pass = Xkpasswd::from_dict_str(dict_str).unwrap();
 
-        assert_eq!(2, dict.len());
-        assert_eq!(vec!["an", "do"], *dict.get(&2).unwrap());
-        assert_eq!(vec!["foo", "bar"], *dict.get(&3).unwrap());
-        assert!(dict.get(&4).is_none());
+        assert_eq!(4, dict_words(&pass, 0..u8::MAX).len());
+        assert_eq!(vec!["abbe", "abby"], dict_words(&pass, 4..5));
+        assert_eq!(vec!["about", "above"], dict_words(&pass, 5..6));
     }
+
+    assert!(Xkpasswd::from_dict_str("").is_err());
 }
 
 #[test]
-fn test_load_dict_invalid_data() {
-    // Invalid format (non-numeric length) should be gracefully skipped
-    let dict_bytes = "foo:bar,baz".as_bytes();
-    let dict = load_dict(dict_bytes);
-    assert!(dict.is_empty());
+fn test_xkpasswd_from_dict_file() {
+    use std::io::Write;
+
+    let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(temp_file, "correct\nhorse\nbattery\nstaple").unwrap();
+
+    let pass = Xkpasswd::from_dict_file(temp_file.path()).unwrap();
+    assert_eq!(vec!["horse"], dict_words(&pass, 5..6));
+    assert_eq!(vec!["staple"], dict_words(&pass, 6..7));
+    assert_eq!(vec!["correct", "battery"], dict_words(&pass, 7..8));
+
+    let settings = Settings::default()
+        .with_words_count(4)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Lowercase))
+        .unwrap()
+        .with_separators(" ")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbol_lengths(Some(0), Some(0));
+    let (passwd, _) = pass.gen_pass(&settings).unwrap();
+
+    let mut words: Vec<&str> = passwd.split(' ').collect();
+    words.sort();
+    assert_eq!(vec!["battery", "correct", "horse", "staple"], words);
+
+    let result = Xkpasswd::from_dict_file("/nonexistent/path/to/words.txt");
+    assert!(result
+        .err()
+        .unwrap()
+        .to_string()
+        .starts_with("unable to read dictionary at /nonexistent/path/to/words.txt"));
 }
 
 #[cfg(feature = "lang_en")]
 #[test]
 fn test_xkpasswd_for_en() {
     let pass = Xkpasswd::for_language(Language::English);
-    assert!(!pass.dict.is_empty());
+    assert!(dict_words(&pass, 2..3).is_empty());
+    assert!(dict_words(&pass, 3..4).is_empty());
 
-    assert!(pass.dict.get(&2).is_none());
-    assert!(pass.dict.get(&3).is_none());
+    assert_eq!(1483, dict_words(&pass, 4..5).len());
+    assert_eq!(1490, dict_words(&pass, 5..6).len());
+    assert_eq!(1495, dict_words(&pass, 6..7).len());
+    assert_eq!(1495, dict_words(&pass, 7..8).len());
+    assert_eq!(1495, dict_words(&pass, 8..9).len());
+    assert_eq!(1338, dict_words(&pass, 9..10).len());
+    assert_eq!(807, dict_words(&pass, 10..11).len());
 
-    assert_eq!(1500, pass.dict.get(&4).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&5).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&6).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&7).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&8).unwrap().len());
-    assert_eq!(1338, pass.dict.get(&9).unwrap().len());
-    assert_eq!(807, pass.dict.get(&10).unwrap().len());
-
-    assert!(pass.dict.get(&11).is_none());
+    assert!(dict_words(&pass, 11..12).is_empty());
 }
 
 #[cfg(feature = "lang_de")]
 #[test]
 fn test_xkpasswd_for_de() {
     let pass = Xkpasswd::for_language(Language::German);
-    assert!(!pass.dict.is_empty());
-
-    assert!(pass.dict.get(&2).is_none());
-    assert!(pass.dict.get(&3).is_none());
+    assert!(dict_words(&pass, 2..3).is_empty());
+    assert!(dict_words(&pass, 3..4).is_empty());
 
-    assert_eq!(1277, pass.dict.get(&4).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&5).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&6).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&7).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&8).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&9).unwrap().len());
-    assert_eq!(1185, pass.dict.get(&10).unwrap().len());
+    assert_eq!(1274, dict_words(&pass, 4..5).len());
+    assert_eq!(1490, dict_words(&pass, 5..6).len());
+    assert_eq!(1497, dict_words(&pass, 6..7).len());
+    assert_eq!(1500, dict_words(&pass, 7..8).len());
+    assert_eq!(1498, dict_words(&pass, 8..9).len());
+    assert_eq!(1498, dict_words(&pass, 9..10).len());
+    assert_eq!(1184, dict_words(&pass, 10..11).len());
 
-    assert!(pass.dict.get(&11).is_none());
+    assert!(dict_words(&pass, 11..12).is_empty());
 }
 
 #[cfg(feature = "lang_es")]
 #[test]
 fn test_xkpasswd_for_es() {
     let pass = Xkpasswd::for_language(Language::Spanish);
-    assert!(!pass.dict.is_empty());
+    assert!(dict_words(&pass, 2..3).is_empty());
+    assert!(dict_words(&pass, 3..4).is_empty());
 
-    assert!(pass.dict.get(&2).is_none());
-    assert!(pass.dict.get(&3).is_none());
+    assert_eq!(1107, dict_words(&pass, 4..5).len());
+    assert_eq!(1497, dict_words(&pass, 5..6).len());
+    assert_eq!(1492, dict_words(&pass, 6..7).len());
+    assert_eq!(1499, dict_words(&pass, 7..8).len());
+    assert_eq!(1499, dict_words(&pass, 8..9).len());
+    assert_eq!(1500, dict_words(&pass, 9..10).len());
+    assert_eq!(1128, dict_words(&pass, 10..11).len());
 
-    assert_eq!(1111, pass.dict.get(&4).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&5).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&6).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&7).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&8).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&9).unwrap().len());
-    assert_eq!(1129, pass.dict.get(&10).unwrap().len());
-
-    assert!(pass.dict.get(&11).is_none());
+    assert!(dict_words(&pass, 11..12).is_empty());
 }
 
 #[cfg(feature = "lang_fr")]
 #[test]
 fn test_xkpasswd_for_fr() {
     let pass = Xkpasswd::for_language(Language::French);
-    assert!(!pass.dict.is_empty());
-
-    assert!(pass.dict.get(&2).is_none());
-    assert!(pass.dict.get(&3).is_none());
+    assert!(dict_words(&pass, 2..3).is_empty());
+    assert!(dict_words(&pass, 3..4).is_empty());
 
-    assert_eq!(1212, pass.dict.get(&4).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&5).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&6).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&7).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&8).unwrap().len());
-    assert_eq!(1438, pass.dict.get(&9).unwrap().len());
-    assert_eq!(902, pass.dict.get(&10).unwrap().len());
+    assert_eq!(1207, dict_words(&pass, 4..5).len());
+    assert_eq!(1494, dict_words(&pass, 5..6).len());
+    assert_eq!(1494, dict_words(&pass, 6..7).len());
+    assert_eq!(1497, dict_words(&pass, 7..8).len());
+    assert_eq!(1498, dict_words(&pass, 8..9).len());
+    assert_eq!(1438, dict_words(&pass, 9..10).len());
+    assert_eq!(902, dict_words(&pass, 10..11).len());
 
-    assert!(pass.dict.get(&11).is_none());
+    assert!(dict_words(&pass, 11..12).is_empty());
 }
 
 #[cfg(feature = "lang_pt")]
 #[test]
 fn test_xkpasswd_for_pt() {
     let pass = Xkpasswd::for_language(Language::Portuguese);
-    assert!(!pass.dict.is_empty());
+    assert!(dict_words(&pass, 2..3).is_empty());
+    assert!(dict_words(&pass, 3..4).is_empty());
+
+    assert_eq!(1126, dict_words(&pass, 4..5).len());
+    assert_eq!(1496, dict_words(&pass, 5..6).len());
+    assert_eq!(1499, dict_words(&pass, 6..7).len());
+    assert_eq!(1499, dict_words(&pass, 7..8).len());
+    assert_eq!(1500, dict_words(&pass, 8..9).len());
+    assert_eq!(1396, dict_words(&pass, 9..10).len());
+    assert_eq!(925, dict_words(&pass, 10..11).len());
+
+    assert!(dict_words(&pass, 11..12).is_empty());
+}
+
+#[cfg(feature = "lang_de_native")]
+#[test]
+fn test_xkpasswd_for_de_native() {
+    let pass = Xkpasswd::for_language(Language::GermanNative);
+    assert!(dict_words(&pass, 2..3).is_empty());
+    assert!(dict_words(&pass, 3..4).is_empty());
+
+    assert_eq!(1322, dict_words(&pass, 4..5).len());
+    assert_eq!(1490, dict_words(&pass, 5..6).len());
+    assert_eq!(1497, dict_words(&pass, 6..7).len());
+    assert_eq!(1500, dict_words(&pass, 7..8).len());
+    assert_eq!(1498, dict_words(&pass, 8..9).len());
+    assert_eq!(1497, dict_words(&pass, 9..10).len());
+    assert_eq!(1187, dict_words(&pass, 10..11).len());
+
+    assert!(dict_words(&pass, 11..12).is_empty());
+
+    // words keep their native spelling & are grouped by chars count
+    assert!(dict_words(&pass, 6..7).contains(&"barfuß"));
+    assert!((4..11).all(|len| dict_words(&pass, len..len + 1)
+        .iter()
+        .all(|word| word.chars().count() == len as usize)));
+}
+
+#[cfg(feature = "lang_es_native")]
+#[test]
+fn test_xkpasswd_for_es_native() {
+    let pass = Xkpasswd::for_language(Language::SpanishNative);
+    assert!(dict_words(&pass, 2..3).is_empty());
+    assert!(dict_words(&pass, 3..4).is_empty());
+
+    assert_eq!(1190, dict_words(&pass, 4..5).len());
+    assert_eq!(1498, dict_words(&pass, 5..6).len());
+    assert_eq!(1492, dict_words(&pass, 6..7).len());
+    assert_eq!(1499, dict_words(&pass, 7..8).len());
+    assert_eq!(1499, dict_words(&pass, 8..9).len());
+    assert_eq!(1500, dict_words(&pass, 9..10).len());
+    assert_eq!(1135, dict_words(&pass, 10..11).len());
+
+    assert!(dict_words(&pass, 11..12).is_empty());
+
+    // words keep their native spelling & are grouped by chars count
+    assert!(dict_words(&pass, 4..5).contains(&"niño"));
+    assert!((4..11).all(|len| dict_words(&pass, len..len + 1)
+        .iter()
+        .all(|word| word.chars().count() == len as usize)));
+}
+
+#[cfg(feature = "lang_fr_native")]
+#[test]
+fn test_xkpasswd_for_fr_native() {
+    let pass = Xkpasswd::for_language(Language::FrenchNative);
+    assert!(dict_words(&pass, 2..3).is_empty());
+    assert!(dict_words(&pass, 3..4).is_empty());
+
+    assert_eq!(1305, dict_words(&pass, 4..5).len());
+    assert_eq!(1494, dict_words(&pass, 5..6).len());
+    assert_eq!(1495, dict_words(&pass, 6..7).len());
+    assert_eq!(1497, dict_words(&pass, 7..8).len());
+    assert_eq!(1498, dict_words(&pass, 8..9).len());
+    assert_eq!(1477, dict_words(&pass, 9..10).len());
+    assert_eq!(921, dict_words(&pass, 10..11).len());
 
-    assert!(pass.dict.get(&2).is_none());
-    assert!(pass.dict.get(&3).is_none());
+    assert!(dict_words(&pass, 11..12).is_empty());
 
-    assert_eq!(1130, pass.dict.get(&4).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&5).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&6).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&7).unwrap().len());
-    assert_eq!(1500, pass.dict.get(&8).unwrap().len());
-    assert_eq!(1397, pass.dict.get(&9).unwrap().len());
-    assert_eq!(925, pass.dict.get(&10).unwrap().len());
+    // words keep their native spelling & are grouped by chars count
+    assert!(dict_words(&pass, 4..5).contains(&"août"));
+    assert!((4..11).all(|len| dict_words(&pass, len..len + 1)
+        .iter()
+        .all(|word| word.chars().count() == len as usize)));
+}
+
+#[cfg(feature = "lang_pt_native")]
+#[test]
+fn test_xkpasswd_for_pt_native() {
+    let pass = Xkpasswd::for_language(Language::PortugueseNative);
+    assert!(dict_words(&pass, 2..3).is_empty());
+    assert!(dict_words(&pass, 3..4).is_empty());
+
+    assert_eq!(1161, dict_words(&pass, 4..5).len());
+    assert_eq!(1496, dict_words(&pass, 5..6).len());
+    assert_eq!(1499, dict_words(&pass, 6..7).len());
+    assert_eq!(1499, dict_words(&pass, 7..8).len());
+    assert_eq!(1500, dict_words(&pass, 8..9).len());
+    assert_eq!(1402, dict_words(&pass, 9..10).len());
+    assert_eq!(930, dict_words(&pass, 10..11).len());
 
-    assert!(pass.dict.get(&11).is_none());
+    assert!(dict_words(&pass, 11..12).is_empty());
+
+    // words keep their native spelling & are grouped by chars count
+    assert!(dict_words(&pass, 7..8).contains(&"coração"));
+    assert!((4..11).all(|len| dict_words(&pass, len..len + 1)
+        .iter()
+        .all(|word| word.chars().count() == len as usize)));
+}
+
+#[cfg(all(feature = "lang_en", feature = "lang_fr_native"))]
+#[test]
+fn test_xkpasswd_builtin_blocklist() {
+    let pass = Xkpasswd::for_language(Language::English);
+    let words = dict_words(&pass, 4..11);
+    assert!(words.contains(&"house"));
+    assert!(!words.contains(&"fuck"));
+    assert!(!words.contains(&"nazi"));
+
+    // native dictionaries share the blocklist of their language
+    let pass = Xkpasswd::for_language(Language::FrenchNative);
+    let words = dict_words(&pass, 4..11);
+    assert!(words.contains(&"maison"));
+    assert!(!words.contains(&"merde"));
+    assert!(!words.contains(&"pédé"));
+}
+
+#[test]
+fn test_xkpasswd_with_excluded_words() {
+    let pass = Xkpasswd::from_words(["correct", "horse", "battery", "staple"]).unwrap();
+
+    let filtered = pass
+        .with_excluded_words([" HORSE", "staple", "unknown"])
+        .unwrap();
+    assert_eq!(vec!["correct", "battery"], dict_words(&filtered, 4..11));
+    assert_eq!(4, dict_words(&pass, 4..11).len());
+
+    let untouched = pass.with_excluded_words(["unknown"]).unwrap();
+    assert_eq!(dict_words(&pass, 4..11), dict_words(&untouched, 4..11));
+
+    let result = pass.with_excluded_words(["correct", "horse", "battery", "staple"]);
+    assert_eq!(
+        "no words left after exclusions",
+        result.err().unwrap().to_string()
+    );
+}
+
+#[test]
+fn test_xkpasswd_with_excluded_words_file() {
+    use std::io::Write;
+
+    let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(temp_file, "# not for customers\nhorse\n\n  staple  ").unwrap();
+
+    let pass = Xkpasswd::from_words(["correct", "horse", "battery", "staple"]).unwrap();
+    let filtered = pass.with_excluded_words_file(temp_file.path()).unwrap();
+    assert_eq!(vec!["correct", "battery"], dict_words(&filtered, 4..11));
+
+    let result = pass.with_excluded_words_file("/nonexistent/path/to/blocklist.txt");
+    assert!(result
+        .err()
+        .unwrap()
+        .to_string()
+        .starts_with("unable to read excluded words at /nonexistent/path/to/blocklist.txt"));
+}
+
+#[cfg(all(feature = "lang_en", feature = "lang_de"))]
+#[test]
+fn test_xkpasswd_with_excluded_words_mixed() {
+    let pass = Xkpasswd::for_weighted_languages(&[(Language::English, 2), (Language::German, 1)])
+        .unwrap()
+        .with_excluded_words(["house", "haus"])
+        .unwrap();
+
+    assert_eq!(2, pass.dicts.len());
+    assert_eq!(2, pass.dicts[0].1);
+    assert!(!dict_words(&pass, 4..11).contains(&"house"));
+}
+
+#[test]
+fn test_weighted_pool() {
+    let dict = dict::Dict::from_words(&["foo", "bar", "fooo"]);
+    let other_dict = dict::Dict::from_words(&["baz", "quux"]);
+
+    let pool = dict::WeightedPool::new(vec![(dict.pool(3..4), 3), (other_dict.pool(3..4), 1)]);
+    assert_eq!(3, pool.len());
+    assert_eq!(
+        vec!["foo", "bar", "baz"],
+        (0..pool.len())
+            .map(|idx| pool.word(idx))
+            .collect::<Vec<&str>>()
+    );
+    assert_eq!(
+        vec![0.375, 0.375, 0.25],
+        (0..pool.len())
+            .map(|idx| pool.prob(idx))
+            .collect::<Vec<f64>>()
+    );
+
+    // a language is drawn by weight, then a word from it
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(42);
+    let mut counts = [0usize; 3];
+    for _ in 0..8000 {
+        counts[pool.draw(&mut rng)] += 1;
+    }
+    assert!((2700..3300).contains(&counts[0]), "counts {:?}", counts);
+    assert!((2700..3300).contains(&counts[1]), "counts {:?}", counts);
+    assert!((1700..2300).contains(&counts[2]), "counts {:?}", counts);
+
+    // entropy follows the chance of each word, the most likely ones ruled out first
+    let settings = Settings::default()
+        .with_words_count(1)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Lowercase))
+        .unwrap()
+        .with_separators("-")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("");
+    let first_bits = 0.75 * (8.0f64 / 3.0).log2() + 0.5;
+    let entropy = settings.calc_entropy(&pool).seen;
+    assert!((first_bits - entropy).abs() < 1e-9, "got {} bits", entropy);
+
+    let entropy = settings
+        .with_words_count(2)
+        .unwrap()
+        .calc_entropy(&pool)
+        .seen;
+    let bits = first_bits + (5.0f64 / 3.0).log2();
+    assert!((bits - entropy).abs() < 1e-9, "got {} bits", entropy);
+
+    let pool = dict::WeightedPool::new(vec![(dict.pool(5..6), 3), (other_dict.pool(4..5), 1)]);
+    assert_eq!(vec!["quux"], vec![pool.word(0)]);
+    assert_eq!(1.0, pool.prob(0));
+    assert!(WordsPool::is_empty(&dict::WeightedPool::new(vec![])));
+}
+
+#[test]
+fn test_xkpasswd_for_weighted_languages_errors() {
+    assert_eq!(
+        XkpasswdError::NoLanguage,
+        Xkpasswd::for_languages(&[]).err().unwrap()
+    );
+
+    assert_eq!(
+        XkpasswdError::LanguageWeight {
+            language: Language::German,
+            value: 0,
+            min: 1,
+        },
+        Xkpasswd::for_weighted_languages(&[(Language::English, 1), (Language::German, 0)])
+            .err()
+            .unwrap()
+    );
+}
+
+#[cfg(all(feature = "lang_en", feature = "lang_de"))]
+#[test]
+fn test_xkpasswd_for_languages() {
+    let settings = Settings::default();
+    let pool_words = |pass: &Xkpasswd| -> Vec<String> {
+        let pool = pass.words_pool(&settings);
+        (0..pool.len())
+            .map(|idx| pool.word(idx).to_string())
+            .collect()
+    };
+
+    let en_words = pool_words(&Xkpasswd::for_language(Language::English));
+    let de_words = pool_words(&Xkpasswd::for_language(Language::German));
+    let en_set: HashSet<&String> = en_words.iter().collect();
+    let de_unique: Vec<&String> = de_words.iter().filter(|w| !en_set.contains(w)).collect();
+    assert!(de_unique.len() < de_words.len(), "no shared words to test");
+
+    let pass = Xkpasswd::for_languages(&[Language::English, Language::German]).unwrap();
+    let words = pool_words(&pass);
+
+    // union of both languages, shared words kept once
+    assert_eq!(en_words.len() + de_unique.len(), words.len());
+    assert_eq!(words.len(), words.iter().collect::<HashSet<_>>().len());
+    assert!(en_words.iter().all(|word| words.contains(word)));
+
+    // entropy of the union
+    let (_, en_entropy) = Xkpasswd::for_language(Language::English)
+        .gen_pass(&settings)
+        .unwrap();
+    let (_, entropy) = pass.gen_pass(&settings).unwrap();
+    assert!(entropy.seen > en_entropy.seen + 1.0);
+
+    // repeated languages add up, only relative weights matter
+    let table = [
+        (vec![(Language::English, 2), (Language::German, 2)], 1),
+        (vec![(Language::English, 2), (Language::German, 1)], 2),
+        (
+            vec![
+                (Language::English, 1),
+                (Language::German, 1),
+                (Language::English, 1),
+            ],
+            2,
+        ),
+    ];
+
+    for (weights, en_weight) in table {
+        let pass = Xkpasswd::for_weighted_languages(&weights).unwrap();
+        let pool = pass.words_pool(&settings);
+        assert_eq!(
+            en_words.len() + de_unique.len(),
+            pool.len(),
+            "weights {:?}",
+            weights
+        );
+
+        // words aren't repeated, their chances follow the weight of their language
+        let en_prob: f64 = (0..pool.len())
+            .filter(|&idx| en_set.contains(&pool.word(idx).to_string()))
+            .map(|idx| pool.prob(idx))
+            .sum();
+        let expected = en_weight as f64 / (en_weight + 1) as f64;
+        assert!(
+            (en_prob - expected).abs() < 1e-9,
+            "weights {:?}: {} != {}",
+            weights,
+            en_prob,
+            expected
+        );
+    }
+
+    let pass = Xkpasswd::for_languages(&[Language::English, Language::English]).unwrap();
+    assert_eq!(en_words, pool_words(&pass));
 }
 
 #[test]
@@ -254,130 +843,467 @@ fn test_xkpasswd_gen_pass() {
     ];
 
     for (expected, settings) in table {
-        let (passwd, _) = pass.gen_pass(&settings);
+        let (passwd, _) = pass.gen_pass(&settings).unwrap();
         assert_eq!(expected, passwd);
     }
 }
 
 #[test]
-fn test_guess_time_display() {
-    // Test "more than a billion years"
-    let time = GuessTime {
-        years: 2_000_000_000,
-        months: 0,
-        days: 0,
-    };
-    assert_eq!("more than a billion years", time.to_string());
+fn test_xkpasswd_gen_pass_substitutions() {
+    let pass = Xkpasswd::default();
+    let settings = Settings::default()
+        .with_separators("| ")
+        .with_padding_symbols("|'~")
+        .with_padding_strategy(PaddingStrategy::Adaptive(40))
+        .unwrap();
+    let substituted = settings
+        .with_substitutions(&[
+            ('|', "!".to_string()),
+            (' ', "_".to_string()),
+            ('\'', "".to_string()),
+        ])
+        .unwrap();
 
-    // Test "more than a million years"
-    let time = GuessTime {
-        years: 5_000_000,
-        months: 0,
-        days: 0,
-    };
-    assert_eq!("more than a million years", time.to_string());
+    // padded symbols are substituted too
+    for seed in 0..20 {
+        let (passwd, _) = pass.gen_pass_with_seed(&settings, seed).unwrap();
+        let (substituted_passwd, _) = pass.gen_pass_with_seed(&substituted, seed).unwrap();
 
-    // Test "more than a thousand years"
-    let time = GuessTime {
-        years: 5_000,
-        months: 0,
-        days: 0,
-    };
-    assert_eq!("more than a thousand years", time.to_string());
+        assert!(!substituted_passwd.contains(['|', ' ', '\'']));
+        assert_eq!(
+            passwd.replace('|', "!").replace(' ', "_").replace('\'', ""),
+            substituted_passwd
+        );
+    }
 
-    // Test years, months, days combination
-    let time = GuessTime {
-        years: 5,
-        months: 3,
-        days: 10,
-    };
-    assert_eq!("5 years 3 months 10 days", time.to_string());
+    // separators collapsed into a single one are worth less
+    let (_, entropy) = pass.gen_pass(&settings).unwrap();
+    let (_, collapsed_entropy) = pass
+        .gen_pass(
+            &settings
+                .with_substitutions(&[('|', " ".to_string())])
+                .unwrap(),
+        )
+        .unwrap();
+    assert!(collapsed_entropy.seen < entropy.seen);
+}
 
-    // Test months and days only
-    let time = GuessTime {
-        years: 0,
-        months: 6,
-        days: 15,
-    };
-    assert_eq!("6 months 15 days", time.to_string());
+#[test]
+fn test_parse_substitution() {
+    let table = [
+        ("|=!", ('|', "!")),
+        (" =_", (' ', "_")),
+        ("~=", ('~', "")),
+        ("==", ('=', "")),
+        ("é=e&", ('é', "e&")),
+    ];
 
-    // Test days only
-    let time = GuessTime {
-        years: 0,
-        months: 0,
-        days: 20,
-    };
-    assert_eq!("20 days", time.to_string());
+    for (rule, (from, to)) in table {
+        assert_eq!(Ok((from, to.to_string())), parse_substitution(rule));
+    }
 
-    // Test less than a day
-    let time = GuessTime {
-        years: 0,
-        months: 0,
-        days: 0,
+    for rule in ["", "|", "|!", "ab=c"] {
+        let err = parse_substitution(rule).err().unwrap();
+        assert_eq!("substitutions", err.field(), "{}", rule);
+    }
+}
+
+#[test]
+fn test_xkpasswd_gen_pass_with_seed() {
+    let pass = Xkpasswd::default();
+    let settings = Settings::default();
+
+    for seed in [0, 42, u64::MAX] {
+        let expected = pass.gen_pass_with_seed(&settings, seed).unwrap();
+        assert_eq!(expected, pass.gen_pass_with_seed(&settings, seed).unwrap());
+
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        assert_eq!(
+            expected,
+            pass.gen_pass_with_rng(&settings, &mut rng).unwrap()
+        );
+    }
+
+    let (passwd, _) = pass.gen_pass_with_seed(&settings, 1).unwrap();
+    let (other_passwd, _) = pass.gen_pass_with_seed(&settings, 2).unwrap();
+    assert_ne!(passwd, other_passwd);
+}
+
+// draws may change between crate versions only, update along with the version
+#[cfg(feature = "lang_en")]
+#[test]
+fn test_xkpasswd_gen_pass_with_seed_stability() {
+    let pass = Xkpasswd::for_language(Language::English);
+    let table = [
+        (Preset::Default, "latter.GONG.DARLIN.65||"),
+        (Preset::Web32, "*46|CORN|fern|FAYE|meth|84@"),
+        (Preset::Xkcd, "ferris-antony-HURT-pods"),
+    ];
+
+    for (preset, expected) in table {
+        let (passwd, _) = pass
+            .gen_pass_with_seed(&Settings::from_preset(preset), 42)
+            .unwrap();
+        assert_eq!(
+            expected,
+            passwd,
+            "{:?} (crate {})",
+            preset,
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+}
+
+#[test]
+fn test_xkpasswd_calc_entropy() {
+    let pass = Xkpasswd::default();
+
+    for settings in [Settings::default(), Settings::from_preset(Preset::Xkcd)] {
+        let (_, entropy) = pass.gen_pass(&settings).unwrap();
+        assert_eq!(entropy, pass.calc_entropy(&settings));
+        assert_eq!(
+            settings.calc_entropy(&pass.words_pool(&settings)),
+            pass.calc_entropy(&settings)
+        );
+    }
+
+    // computed once per settings
+    assert_eq!(2, pass.entropies.lock().unwrap().len());
+    assert_eq!(
+        Settings::default().cache_key(),
+        Settings::default().cache_key()
+    );
+    assert_ne!(
+        Settings::default().cache_key(),
+        Settings::from_preset(Preset::Xkcd).cache_key()
+    );
+
+    // settings without a key are never cached
+    let mock_settings = MockSettings {
+        padding_digits: (0, 0),
+        padding_symbols: (0, 0),
+        padding_result: PaddingResult::Unchanged,
     };
-    assert_eq!("less than a day", time.to_string());
+    pass.gen_pass(&mock_settings).unwrap();
+    assert_eq!(2, pass.entropies.lock().unwrap().len());
+
+    // bounded whatever the number of settings
+    pass.entropies
+        .lock()
+        .unwrap()
+        .extend((0..MAX_CACHED_ENTROPIES as u64).map(|key| (key, Entropy::default())));
+    pass.calc_entropy(&Settings::default().with_words_count(5).unwrap());
+    assert_eq!(1, pass.entropies.lock().unwrap().len());
 }
 
 #[test]
-fn test_guess_time_for_entropy_edge_cases() {
-    // Test entropy > 64 (more than a billion years)
-    let time = GuessTime::for_entropy(65);
-    assert_eq!(1_000_000_001, time.years);
+fn test_xkpasswd_gen_passes() {
+    let pass = Xkpasswd::default();
+    let settings = Settings::default();
 
-    // Test exact boundary at 64 (should still be > 64 branch)
-    let time = GuessTime::for_entropy(64);
-    assert_eq!(1_000_001, time.years); // Falls into > 54 branch, not > 64
+    assert!(pass.gen_passes(&settings, 0).unwrap().is_empty());
 
-    // Test entropy 55-64 (more than a million years)
-    let time = GuessTime::for_entropy(55);
-    assert_eq!(1_000_001, time.years);
+    let passwds = pass.gen_passes(&settings, 10).unwrap();
+    assert_eq!(10, passwds.len());
 
-    // Test exact boundary at 54 (should fall into > 44 branch)
-    let time = GuessTime::for_entropy(54);
-    assert_eq!(1001, time.years);
+    let (_, expected_entropy) = pass.gen_pass(&settings).unwrap();
+    for (_, entropy) in passwds {
+        assert_eq!(expected_entropy, entropy);
+    }
+
+    // batch & sequential generation consume the same random draws
+    let mut rng = ChaCha20Rng::seed_from_u64(42);
+    let expected: Vec<(String, Entropy)> = (0..5)
+        .map(|_| pass.gen_pass_with_rng(&settings, &mut rng).unwrap())
+        .collect();
+    assert_eq!(
+        expected,
+        pass.gen_passes_with_seed(&settings, 5, 42).unwrap()
+    );
+}
 
-    // Test entropy 45-54 (more than a thousand years)
-    let time = GuessTime::for_entropy(45);
-    assert_eq!(1001, time.years);
+#[test]
+fn test_xkpasswd_iter_passes() {
+    let pass = Xkpasswd::default();
+    let settings = Settings::default();
+
+    let passwds: Vec<(String, Entropy)> = pass
+        .iter_passes(&settings, ChaCha20Rng::seed_from_u64(42))
+        .unwrap()
+        .take(3)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        pass.gen_passes_with_seed(&settings, 3, 42).unwrap(),
+        passwds
+    );
+}
+
+#[test]
+fn test_xkpasswd_gen_pass_matching() {
+    let pass = Xkpasswd::default();
+    let settings = Settings::default()
+        .with_word_transforms(WordTransform::Lowercase | WordTransform::Uppercase)
+        .unwrap();
+    let policy = PasswordPolicy::default()
+        .with_required_classes(&[CharClass::Uppercase, CharClass::Lowercase])
+        .with_forbidden_chars("-")
+        .with_max_repeated_chars(2)
+        .unwrap();
+
+    for seed in 0..20 {
+        let (passwd, entropy) = pass
+            .gen_pass_matching_with_seed(&settings, &policy, seed)
+            .unwrap();
+        assert_eq!(Ok(()), policy.check(&passwd));
+        assert_eq!(pass.gen_pass(&settings).unwrap().1, entropy);
+
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        assert_eq!(
+            Ok((passwd, entropy)),
+            pass.gen_pass_matching_with_rng(&settings, &policy, &mut rng)
+        );
+    }
+
+    let (passwd, _) = pass.gen_pass_matching(&settings, &policy).unwrap();
+    assert_eq!(Ok(()), policy.check(&passwd));
+
+    // lax policy keeps the very first password
+    let lax_policy = PasswordPolicy::default();
+    assert_eq!(
+        pass.gen_pass_with_seed(&settings, 42),
+        pass.gen_pass_matching_with_seed(&settings, &lax_policy, 42)
+    );
+}
+
+#[test]
+fn test_xkpasswd_gen_pass_matching_exhausted() {
+    let pass = Xkpasswd::default();
+    let settings = Settings::default().with_words_count(4).unwrap();
+
+    // words alone are already longer than that
+    let policy = PasswordPolicy::default()
+        .with_lengths(None, Some(8))
+        .unwrap()
+        .with_max_attempts(10)
+        .unwrap();
+
+    match pass.gen_pass_matching_with_seed(&settings, &policy, 42) {
+        Err(XkpasswdError::Policy {
+            attempts,
+            violation: PolicyViolation::TooLong { max, .. },
+        }) => {
+            assert_eq!(10, attempts);
+            assert_eq!(8, max);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    // lowercase words & digits only
+    let settings = Settings::default()
+        .with_word_transforms(WordTransform::Lowercase as FieldSize)
+        .unwrap();
+    let policy = PasswordPolicy::default().with_required_classes(&[CharClass::Uppercase]);
+
+    let err = pass.gen_pass_matching(&settings, &policy).err().unwrap();
+    assert_eq!("policy", err.field());
+    assert_eq!(
+        format!(
+            "no password matched the policy after {} attempts: missing uppercase letter",
+            PasswordPolicy::DEFAULT_MAX_ATTEMPTS
+        ),
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_xkpasswd_gen_passes_matching() {
+    let pass = Xkpasswd::default();
+    let settings = Settings::default();
+    let policy = PasswordPolicy::default()
+        .with_forbidden_chars("13579")
+        .with_required_classes(&[CharClass::Digit]);
+
+    let passwds = pass
+        .gen_passes_matching_with_seed(&settings, &policy, 10, 42)
+        .unwrap();
+    assert_eq!(10, passwds.len());
+    passwds
+        .iter()
+        .for_each(|(passwd, _)| assert_eq!(Ok(()), policy.check(passwd)));
+
+    // batch & sequential generation consume the same random draws
+    let mut rng = ChaCha20Rng::seed_from_u64(42);
+    let expected: Vec<(String, Entropy)> = (0..10)
+        .map(|_| {
+            pass.gen_pass_matching_with_rng(&settings, &policy, &mut rng)
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(expected, passwds);
+
+    assert_eq!(
+        3,
+        pass.gen_passes_matching(&settings, &policy, 3)
+            .unwrap()
+            .len()
+    );
+    assert!(pass
+        .gen_passes_matching(&settings, &policy, 0)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_guess_time_display() {
+    let table = [
+        ((0.0, 2.5e9, 0, 0), "2.50e9 years"),
+        ((0.0, 1_234_567.0, 11, 20), "1.23e6 years"),
+        ((0.0, 5_000.0, 0, 0), "5000 years"),
+        ((0.0, 5.0, 3, 10), "5 years 3 months 10 days"),
+        ((0.0, 0.0, 6, 15), "6 months 15 days"),
+        ((0.0, 0.0, 0, 20), "20 days"),
+        // less than a day
+        ((7_384.9, 0.0, 0, 0), "2 hours 3 minutes 4 seconds"),
+        ((3_600.0, 0.0, 0, 0), "1 hours"),
+        ((59.0, 0.0, 0, 0), "59 seconds"),
+        ((0.5, 0.0, 0, 0), "less than a second"),
+    ];
+
+    for ((seconds, years, months, days), expected) in table {
+        let time = GuessTime {
+            seconds,
+            years,
+            months,
+            days,
+        };
+        assert_eq!(expected, time.to_string());
+    }
+}
+
+#[test]
+fn test_guess_time_for_entropy() {
+    // no more buckets for high entropies
+    let time = GuessTime::for_entropy(65.0, 1e3);
+    assert_eq!(2f64.powi(65) / 1e3, time.seconds);
+    assert_eq!(1_169_884_834.0, time.years);
+
+    let time = GuessTime::for_entropy(54.0, 1e3);
+    assert_eq!(571_232.0, time.years);
+    assert_eq!((10, 2), (time.months, time.days));
+
+    let time = GuessTime::for_entropy(30.0, 1e3);
+    assert_eq!(0.0, time.years);
+    assert_eq!((0, 12), (time.months, time.days));
+    assert_eq!("12 days", time.to_string());
+
+    // faster attackers break the same password sooner
+    let time = GuessTime::for_entropy(30.0, 1e6);
+    assert_eq!(2f64.powi(30) / 1e6, time.seconds);
+    assert_eq!("17 minutes 53 seconds", time.to_string());
+
+    // past 1024 bits, e.g. 90 words, times stay finite
+    let time = GuessTime::for_entropy(1030.0, 1e3);
+    assert_eq!((1030.0 - 1e3f64.log2()).exp2(), time.seconds);
+    assert!(time.years.is_finite());
+    assert_eq!("3.65e299 years", time.to_string());
+
+    let time = GuessTime::for_entropy(1290.0, 1e3);
+    assert_eq!(f64::MAX, time.seconds);
+    assert!(time.years.is_finite());
+    assert!(!time.to_string().contains("inf"));
+}
+
+#[test]
+fn test_attacker() {
+    let table = [
+        (AttackerProfile::OnlineThrottled, 1e3),
+        (AttackerProfile::OfflineSlowHash, 1e5),
+        (AttackerProfile::OfflineFastHash, 1e10),
+        (AttackerProfile::GpuCluster, 1e12),
+    ];
+
+    for (profile, guesses_per_sec) in table {
+        let attacker = Attacker::new(profile);
+        assert_eq!(profile, attacker.profile());
+        assert_eq!(guesses_per_sec, attacker.guesses_per_sec());
+        assert_eq!(
+            GuessTime::for_entropy(50.0, guesses_per_sec),
+            attacker.guess_time(50.0)
+        );
+    }
+
+    assert_eq!(
+        Attacker::new(AttackerProfile::OnlineThrottled),
+        Attacker::default()
+    );
+
+    let attacker = Attacker::new(AttackerProfile::GpuCluster)
+        .with_guesses_per_sec(5e11)
+        .unwrap();
+    assert_eq!(AttackerProfile::GpuCluster, attacker.profile());
+    assert_eq!(5e11, attacker.guesses_per_sec());
+
+    for invalid in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let err = attacker.with_guesses_per_sec(invalid).err().unwrap();
+        assert_eq!("guesses_per_sec", err.field());
+    }
+}
+
+#[test]
+fn test_entropy_with_attacker() {
+    let entropy = Entropy {
+        seen: 50.0,
+        ..Entropy::default()
+    };
+    let gpu_cluster = Attacker::new(AttackerProfile::GpuCluster);
 
-    // Test exact boundary at 44 (should do actual calculation)
-    let time = GuessTime::for_entropy(44);
-    assert!(time.years < 1001);
+    let entropy = entropy.with_attacker(gpu_cluster);
+    assert_eq!(gpu_cluster, entropy.attacker);
+    assert_eq!(gpu_cluster.guess_time(50.0), entropy.guess_time);
+    assert_eq!("18 minutes 45 seconds", entropy.guess_time.to_string());
 
-    // Test lower entropy values (actual calculation)
-    let time = GuessTime::for_entropy(30);
-    assert!(time.years < 1000);
+    let online = Attacker::default();
+    assert_eq!(online.guess_time(50.0), entropy.guess_time_for(online));
+    assert!(entropy.guess_time_for(online).seconds > entropy.guess_time.seconds);
 }
 
 #[test]
 fn test_entropy_display() {
     // Test when blind_min == blind_max
     let entropy = Entropy {
-        blind_min: 50,
-        blind_max: 50,
-        seen: 40,
+        blind_min: 50.0,
+        blind_max: 50.0,
+        seen: 40.5,
+        attacker: Attacker::default(),
         guess_time: GuessTime {
-            years: 100,
+            seconds: 0.0,
+            years: 100.0,
             months: 0,
             days: 0,
         },
     };
     let display = entropy.to_string();
-    assert!(display.contains("50 bits blind"));
-    assert!(display.contains("40 bits with full knowledge"));
+    assert!(display.contains("50.00 bits blind"));
+    assert!(display.contains("40.50 bits with full knowledge"));
+    assert!(display
+        .ends_with("takes an online throttled attacker 100 years to break at 1000 guesses/sec"));
 
     // Test when blind_min != blind_max
     let entropy = Entropy {
-        blind_min: 45,
-        blind_max: 55,
-        seen: 35,
+        blind_min: 45.0,
+        blind_max: 55.25,
+        seen: 35.123,
+        attacker: Attacker::new(AttackerProfile::GpuCluster),
         guess_time: GuessTime {
-            years: 0,
+            seconds: 0.0,
+            years: 0.0,
             months: 6,
             days: 0,
         },
     };
     let display = entropy.to_string();
-    assert!(display.contains("between 45 & 55 bits"));
-    assert!(display.contains("35 bits with full knowledge"));
+    assert!(display.contains("between 45.00 & 55.25 bits"));
+    assert!(display.contains("35.12 bits with full knowledge"));
+    assert!(display.ends_with("takes a GPU cluster 6 months to break at 1000000000000 guesses/sec"));
 }
@@ -0,0 +1,401 @@
+#[cfg(test)]
+mod tests;
+
+use crate::case;
+use crate::prelude::{Dict, L10n, Language};
+
+// dictionary words have 4 letters or more, shorter runs of letters are spelled out,
+// e.g. those left around leetspeak digits or trimmed by adaptive padding
+const MIN_WORD_LEN: usize = 4;
+
+// same code words whatever the language, as used on the radio
+const NATO_ALPHABET: [&str; 26] = [
+    "alfa", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliett",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+    "uniform", "victor", "whiskey", "x-ray", "yankee", "zulu",
+];
+
+// printable ASCII symbols, named in the same order by each language
+const SYMBOLS: &str = " !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+#[derive(Debug)]
+struct Phrases {
+    digits: [&'static str; 10],
+    symbols: [&'static str; 33],
+    lowercase: &'static str,
+    uppercase: &'static str,
+    capitalized: &'static str,
+    inversed_capitalized: &'static str,
+    // `{}` standing for the spelled letter
+    capital_letter: &'static str,
+    // `{}` standing for the capital letter then its position, counted from 1
+    capital_at: &'static str,
+    // `{}` standing for the capital letter
+    capital_at_end: &'static str,
+}
+
+const ENGLISH: Phrases = Phrases {
+    digits: [
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ],
+    symbols: [
+        "space",
+        "exclamation mark",
+        "double quote",
+        "hash",
+        "dollar sign",
+        "percent sign",
+        "ampersand",
+        "apostrophe",
+        "opening parenthesis",
+        "closing parenthesis",
+        "asterisk",
+        "plus sign",
+        "comma",
+        "hyphen",
+        "dot",
+        "slash",
+        "colon",
+        "semicolon",
+        "less-than sign",
+        "equals sign",
+        "greater-than sign",
+        "question mark",
+        "at sign",
+        "opening bracket",
+        "backslash",
+        "closing bracket",
+        "caret",
+        "underscore",
+        "backtick",
+        "opening brace",
+        "vertical bar",
+        "closing brace",
+        "tilde",
+    ],
+    lowercase: "all lowercase",
+    uppercase: "all uppercase",
+    capitalized: "capitalized",
+    inversed_capitalized: "uppercase but the first letter",
+    capital_letter: "capital {}",
+    capital_at: "{} in position {}",
+    capital_at_end: "{} at the end",
+};
+
+const FRENCH: Phrases = Phrases {
+    digits: [
+        "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+    ],
+    symbols: [
+        "espace",
+        "point d'exclamation",
+        "guillemet",
+        "dièse",
+        "dollar",
+        "pourcent",
+        "esperluette",
+        "apostrophe",
+        "parenthèse ouvrante",
+        "parenthèse fermante",
+        "astérisque",
+        "plus",
+        "virgule",
+        "tiret",
+        "point",
+        "barre oblique",
+        "deux-points",
+        "point-virgule",
+        "inférieur",
+        "égal",
+        "supérieur",
+        "point d'interrogation",
+        "arobase",
+        "crochet ouvrant",
+        "barre oblique inversée",
+        "crochet fermant",
+        "accent circonflexe",
+        "tiret bas",
+        "accent grave",
+        "accolade ouvrante",
+        "barre verticale",
+        "accolade fermante",
+        "tilde",
+    ],
+    lowercase: "tout en minuscules",
+    uppercase: "tout en majuscules",
+    capitalized: "première lettre en majuscule",
+    inversed_capitalized: "en majuscules sauf la première lettre",
+    capital_letter: "{} majuscule",
+    capital_at: "{} en position {}",
+    capital_at_end: "{} à la fin",
+};
+
+const GERMAN: Phrases = Phrases {
+    digits: [
+        "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+    ],
+    symbols: [
+        "Leerzeichen",
+        "Ausrufezeichen",
+        "Anführungszeichen",
+        "Raute",
+        "Dollarzeichen",
+        "Prozentzeichen",
+        "Und-Zeichen",
+        "Apostroph",
+        "runde Klammer auf",
+        "runde Klammer zu",
+        "Sternchen",
+        "Pluszeichen",
+        "Komma",
+        "Bindestrich",
+        "Punkt",
+        "Schrägstrich",
+        "Doppelpunkt",
+        "Semikolon",
+        "Kleiner-als-Zeichen",
+        "Gleichheitszeichen",
+        "Größer-als-Zeichen",
+        "Fragezeichen",
+        "At-Zeichen",
+        "eckige Klammer auf",
+        "umgekehrter Schrägstrich",
+        "eckige Klammer zu",
+        "Zirkumflex",
+        "Unterstrich",
+        "Gravis",
+        "geschweifte Klammer auf",
+        "senkrechter Strich",
+        "geschweifte Klammer zu",
+        "Tilde",
+    ],
+    lowercase: "alles klein",
+    uppercase: "alles groß",
+    capitalized: "erster Buchstabe groß",
+    inversed_capitalized: "alles groß außer dem ersten Buchstaben",
+    capital_letter: "großes {}",
+    capital_at: "{} an Position {}",
+    capital_at_end: "{} am Ende",
+};
+
+const PORTUGUESE: Phrases = Phrases {
+    digits: [
+        "zero", "um", "dois", "três", "quatro", "cinco", "seis", "sete", "oito", "nove",
+    ],
+    symbols: [
+        "espaço",
+        "ponto de exclamação",
+        "aspas",
+        "cerquilha",
+        "cifrão",
+        "por cento",
+        "e comercial",
+        "apóstrofo",
+        "abre parênteses",
+        "fecha parênteses",
+        "asterisco",
+        "mais",
+        "vírgula",
+        "hífen",
+        "ponto",
+        "barra",
+        "dois-pontos",
+        "ponto e vírgula",
+        "menor que",
+        "igual",
+        "maior que",
+        "ponto de interrogação",
+        "arroba",
+        "abre colchetes",
+        "barra invertida",
+        "fecha colchetes",
+        "acento circunflexo",
+        "sublinhado",
+        "acento grave",
+        "abre chaves",
+        "barra vertical",
+        "fecha chaves",
+        "til",
+    ],
+    lowercase: "tudo em minúsculas",
+    uppercase: "tudo em maiúsculas",
+    capitalized: "primeira letra maiúscula",
+    inversed_capitalized: "em maiúsculas exceto a primeira letra",
+    capital_letter: "{} maiúsculo",
+    capital_at: "{} na posição {}",
+    capital_at_end: "{} no final",
+};
+
+const SPANISH: Phrases = Phrases {
+    digits: [
+        "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+    ],
+    symbols: [
+        "espacio",
+        "signo de exclamación",
+        "comillas",
+        "almohadilla",
+        "signo de dólar",
+        "signo de porcentaje",
+        "et",
+        "apóstrofo",
+        "paréntesis de apertura",
+        "paréntesis de cierre",
+        "asterisco",
+        "signo más",
+        "coma",
+        "guion",
+        "punto",
+        "barra",
+        "dos puntos",
+        "punto y coma",
+        "signo menor que",
+        "signo igual",
+        "signo mayor que",
+        "signo de interrogación",
+        "arroba",
+        "corchete de apertura",
+        "barra invertida",
+        "corchete de cierre",
+        "acento circunflejo",
+        "guion bajo",
+        "acento grave",
+        "llave de apertura",
+        "barra vertical",
+        "llave de cierre",
+        "virgulilla",
+    ],
+    lowercase: "todo en minúsculas",
+    uppercase: "todo en mayúsculas",
+    capitalized: "primera letra en mayúscula",
+    inversed_capitalized: "en mayúsculas salvo la primera letra",
+    capital_letter: "{} mayúscula",
+    capital_at: "{} en la posición {}",
+    capital_at_end: "{} al final",
+};
+
+/// Spoken form of passwords, e.g. to read them over the phone: words as they are
+/// with their case called out, separators & symbols by name, digits one by one
+/// and stray letters in the NATO alphabet
+#[derive(Clone, Copy, Debug)]
+pub struct Readout {
+    phrases: &'static Phrases,
+    // words of mixed case found in it are said once, otherwise spelled out
+    language: Language,
+}
+
+impl L10n for Readout {
+    fn for_language(language: Language) -> Self {
+        let phrases = match language {
+            Language::English => &ENGLISH,
+            Language::French | Language::FrenchNative => &FRENCH,
+            Language::German | Language::GermanNative => &GERMAN,
+            Language::Portuguese | Language::PortugueseNative => &PORTUGUESE,
+            Language::Spanish | Language::SpanishNative => &SPANISH,
+        };
+
+        Readout { phrases, language }
+    }
+}
+
+impl Readout {
+    /// Pieces to be read one after the other, words of mixed case being said once followed by
+    /// the position of each capital, e.g. "ensign" then "capital november at the end",
+    /// or spelled out when not in the bundled dictionary of the language
+    pub fn read(&self, passwd: &str) -> Vec<String> {
+        let chars: Vec<char> = passwd.chars().collect();
+        let mut pieces: Vec<String> = vec![];
+        let mut idx = 0;
+
+        while idx < chars.len() {
+            let letters = chars[idx..]
+                .iter()
+                .take_while(|char| char.is_alphabetic())
+                .count();
+
+            if letters >= MIN_WORD_LEN {
+                pieces.extend(self.read_word(&chars[idx..idx + letters]));
+                idx += letters;
+            } else {
+                pieces.push(self.read_char(chars[idx]));
+                idx += 1;
+            }
+        }
+
+        pieces
+    }
+
+    fn read_word(&self, letters: &[char]) -> Vec<String> {
+        // uncased letters fit any case
+        let lower = |char: &char| !char.is_uppercase();
+        let upper = |char: &char| !char.is_lowercase();
+        let (first, rest) = (&letters[..1], &letters[1..]);
+
+        let case = if letters.iter().all(lower) {
+            self.phrases.lowercase
+        } else if letters.iter().all(upper) {
+            self.phrases.uppercase
+        } else if first.iter().all(upper) && rest.iter().all(lower) {
+            self.phrases.capitalized
+        } else if first.iter().all(lower) && rest.iter().all(upper) {
+            self.phrases.inversed_capitalized
+        } else {
+            return self.read_mixed_case(letters);
+        };
+
+        let word: String = letters.iter().collect();
+        vec![format!("{} ({})", word, case)]
+    }
+
+    fn read_mixed_case(&self, letters: &[char]) -> Vec<String> {
+        let word = case::to_lowercase(&letters.iter().collect::<String>());
+        if !Dict::find_bundled(self.language).is_some_and(|dict| dict.contains(&word)) {
+            return letters.iter().map(|&char| self.read_char(char)).collect();
+        }
+
+        let capitals = letters
+            .iter()
+            .enumerate()
+            .filter(|(_, char)| char.is_uppercase())
+            .map(|(idx, &char)| {
+                let letter = self.read_char(char);
+                if idx == letters.len() - 1 {
+                    self.phrases.capital_at_end.replace("{}", &letter)
+                } else {
+                    self.phrases.capital_at.replacen("{}", &letter, 1).replacen(
+                        "{}",
+                        &(idx + 1).to_string(),
+                        1,
+                    )
+                }
+            });
+
+        [word].into_iter().chain(capitals).collect()
+    }
+
+    fn read_char(&self, char: char) -> String {
+        if let Some(digit) = char.to_digit(10) {
+            return self.phrases.digits[digit as usize].to_string();
+        }
+
+        if let Some(idx) = SYMBOLS.chars().position(|symbol| symbol == char) {
+            return self.phrases.symbols[idx].to_string();
+        }
+
+        if !char.is_alphabetic() {
+            return char.to_string();
+        }
+
+        let letter = case::char_to_lowercase(char);
+        let spelled = match letter.as_bytes() {
+            [byte @ b'a'..=b'z'] => NATO_ALPHABET[(byte - b'a') as usize].to_string(),
+            _ => letter,
+        };
+
+        if char.is_uppercase() {
+            self.phrases.capital_letter.replace("{}", &spelled)
+        } else {
+            spelled
+        }
+    }
+}
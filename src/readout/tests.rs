@@ -0,0 +1,141 @@
+use super::*;
+
+#[test]
+fn test_read() {
+    let readout = Readout::for_language(Language::English);
+
+    assert_eq!(
+        vec![
+            "correct (all lowercase)",
+            "dot",
+            "HORSE (all uppercase)",
+            "dot",
+            "Battery (capitalized)",
+            "hyphen",
+            "sTAPLE (uppercase but the first letter)",
+            "four",
+            "two",
+            "exclamation mark",
+            "exclamation mark",
+        ],
+        readout.read("correct.HORSE.Battery-sTAPLE42!!")
+    );
+
+    assert!(readout.read("").is_empty());
+}
+
+#[test]
+fn test_read_stray_letters() {
+    let readout = Readout::for_language(Language::English);
+
+    // leetspeak leaves runs of letters too short to be words
+    assert_eq!(
+        vec!["hotel", "zero", "uniform", "sierra", "three"],
+        readout.read("h0us3")
+    );
+
+    // mixed case words out of the dictionary are spelled out
+    assert_eq!(
+        vec!["hotel", "oscar", "capital uniform", "sierra", "zulu"],
+        readout.read("hoUsz")
+    );
+
+    // letters & symbols out of the NATO alphabet & ASCII are kept as is
+    assert_eq!(
+        vec!["forêt (all lowercase)", "€", "capital é", "🦀"],
+        readout.read("forêt€É🦀")
+    );
+}
+
+#[test]
+fn test_read_mixed_case() {
+    let readout = Readout::for_language(Language::English);
+
+    // dictionary words are said once, followed by the position of each capital
+    let table = [
+        ("ensigN", vec!["ensign", "capital november at the end"]),
+        ("hoUse", vec!["house", "capital uniform in position 3"]),
+        (
+            "HoUse",
+            vec![
+                "house",
+                "capital hotel in position 1",
+                "capital uniform in position 3",
+            ],
+        ),
+    ];
+
+    for (passwd, expected) in table {
+        assert_eq!(expected, readout.read(passwd), "{}", passwd);
+    }
+
+    assert_eq!(
+        vec!["maison", "november majuscule à la fin"],
+        Readout::for_language(Language::French).read("maisoN")
+    );
+}
+
+#[test]
+fn test_read_symbols() {
+    let readout = Readout::for_language(Language::English);
+    let pieces = readout.read(SYMBOLS);
+
+    assert_eq!(33, pieces.len());
+    assert_eq!("space", pieces[0]);
+    assert_eq!("tilde", pieces[32]);
+
+    // every symbol has its own name
+    let names: std::collections::HashSet<&String> = pieces.iter().collect();
+    assert_eq!(33, names.len());
+}
+
+#[test]
+fn test_read_localized() {
+    let table = [
+        (
+            Language::French,
+            vec![
+                "Maison (première lettre en majuscule)",
+                "tiret bas",
+                "sept",
+                "charlie majuscule",
+            ],
+        ),
+        (
+            Language::GermanNative,
+            vec![
+                "Maison (erster Buchstabe groß)",
+                "Unterstrich",
+                "sieben",
+                "großes charlie",
+            ],
+        ),
+        (
+            Language::Portuguese,
+            vec![
+                "Maison (primeira letra maiúscula)",
+                "sublinhado",
+                "sete",
+                "charlie maiúsculo",
+            ],
+        ),
+        (
+            Language::SpanishNative,
+            vec![
+                "Maison (primera letra en mayúscula)",
+                "guion bajo",
+                "siete",
+                "charlie mayúscula",
+            ],
+        ),
+    ];
+
+    for (language, expected) in table {
+        assert_eq!(
+            expected,
+            Readout::for_language(language).read("Maison_7C"),
+            "{:?}",
+            language
+        );
+    }
+}
@@ -0,0 +1,807 @@
+use super::{substitute, transform_variants, ExactWords, Settings, MAX_PADDING_DIGITS};
+use crate::bit_flags::{BitFlags, WordTransform};
+use crate::prelude::{PaddingStrategy, SeparatorMode, SymbolMode, WordsPool};
+use std::collections::HashMap;
+
+// Seen entropy is the Shannon entropy of generated passwords for an attacker knowing
+// both the settings & the words pool, derived from how each part is actually sampled.
+//
+// It's exact as long as a password can be split back into its parts, i.e. separators
+// & symbols aren't letters and words are separated, distinct words never give the same
+// output & no trimming can happen. Otherwise it's a lower bound: colliding words are
+// credited with their min-entropy, parts possibly trimmed by adaptive padding
+// are credited only for the chars kept even with the longest words, and words
+// with a unique prefix are credited as if each pick ruled out the largest group
+// of words sharing a prefix.
+//
+// Words from weighted languages are credited for the chance of each to be drawn. When
+// picks rule words out, i.e. for later words of a password, they're credited as if the
+// most likely words were ruled out first, which keeps it a lower bound.
+//
+// With exact padding, words are credited as a whole list drawn uniformly among the
+// fitting ones, each word for the least random transforms any of them gets.
+//
+// Passwords out of the length range are regenerated, which costs the log of the chance
+// of a password to fit, computed from the lengths each part can take. It's exact as long
+// as passwords are equally likely, words of several output lengths being considered
+// as independent picks though.
+//
+// Substitutions apply to the final password, so each part is credited for its
+// substituted outputs, chars or words collapsing into the same output merging
+// their chances. When rules replacing a char by none or several make a run of
+// symbols or digits readable in more than one way, only a single char of the run
+// is credited. Parts themselves are assumed to stay apart once substituted.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Part {
+    Symbols(usize),
+    Digits(u8),
+    Separator,
+    Word(usize),
+}
+
+/// Exact padding draws its words from `exact_words`, the table built for the pool
+pub fn seen_entropy<P: WordsPool + ?Sized>(
+    settings: &Settings,
+    pool: &P,
+    exact_words: Option<&ExactWords>,
+) -> f64 {
+    let words = WordsModel::new(settings, pool);
+    let parts = password_parts(settings, words.count);
+
+    let symbol_bits = chars_entropy(&settings.padding_symbols, &settings.substitutions);
+    // a run of independent symbols only splits back into its draws if it reads one way
+    let symbol_mode = if prefix_free(&settings.padding_symbols, &settings.substitutions) {
+        settings.symbol_mode
+    } else {
+        SymbolMode::Repeat
+    };
+    let separator_bits = chars_entropy(&settings.separators, &settings.substitutions);
+
+    let limit = match settings.padding_strategy {
+        PaddingStrategy::Fixed | PaddingStrategy::Exact(_) => usize::MAX,
+        PaddingStrategy::Adaptive(len) => len,
+    };
+    let exact = matches!(settings.padding_strategy, PaddingStrategy::Exact(_));
+
+    let mut bits = 0.0;
+    let mut separator_credited = false;
+    // where the current part starts when all previous words are at their longest
+    let mut max_start = 0;
+
+    for &part in &parts {
+        let max_len = part_len(settings, &words, part, Bound::Max);
+        let kept = max_len.min(limit.saturating_sub(max_start));
+
+        bits += match part {
+            Part::Symbols(_) => match symbol_mode {
+                SymbolMode::Repeat if kept > 0 => symbol_bits,
+                SymbolMode::Repeat => 0.0,
+                SymbolMode::Independent => kept as f64 * symbol_bits,
+            },
+            Part::Digits(count) => digits_entropy(count, kept, &settings.substitutions),
+            Part::Separator if kept == 0 => 0.0,
+            Part::Separator => match settings.separator_mode {
+                SeparatorMode::Repeat if separator_credited => 0.0,
+                SeparatorMode::Repeat | SeparatorMode::Independent => {
+                    separator_credited = true;
+                    separator_bits
+                }
+            },
+            // credited together with the padding
+            Part::Word(_) if exact => 0.0,
+            Part::Word(idx) if kept == max_len => words.conditional_entropy(idx),
+            Part::Word(_) => 0.0,
+        };
+
+        max_start += max_len;
+    }
+
+    if let PaddingStrategy::Adaptive(len) = settings.padding_strategy {
+        let draws = padding_draws(settings, &words, &parts, len, symbol_mode);
+        log::debug!("entropy: {:.2} padding symbol draw(s)", draws);
+        bits += draws * symbol_bits;
+    }
+
+    if let (&PaddingStrategy::Exact(len), Some(exact_words)) =
+        (&settings.padding_strategy, exact_words)
+    {
+        bits += exact_entropy(settings, pool, exact_words, len, symbol_mode, symbol_bits);
+    }
+
+    bits
+}
+
+// words drawn among the lists fitting the length, then as many symbols as they leave room for
+fn exact_entropy<P: WordsPool + ?Sized>(
+    settings: &Settings,
+    pool: &P,
+    exact_words: &ExactWords,
+    len: usize,
+    symbol_mode: SymbolMode,
+    symbol_bits: f64,
+) -> f64 {
+    let count = settings.words_count as usize;
+    let exact = settings.padding_symbols.is_empty();
+
+    // passwords fall back to any words at their natural length
+    let Some(budget) = settings.words_budget(len) else {
+        return 0.0;
+    };
+    let totals = exact_words.totals(budget, exact);
+    if totals.is_empty() {
+        return 0.0;
+    }
+
+    let lists_bits = exact_words.entropy(budget, exact);
+
+    // fitting words aren't uniformly likely, transforms are credited for the least random word
+    let eligible = exact_words.words(pool);
+    let words = WordsModel::new(settings, &eligible);
+    let transform_bits: f64 = (0..count)
+        .map(|idx| {
+            let outputs = words.outputs_at(idx);
+            if outputs.has_collisions {
+                -(outputs.max_output_prob * eligible.len() as f64).log2()
+            } else {
+                outputs.min_transform_entropy
+            }
+        })
+        .sum();
+
+    let draws: f64 = totals
+        .iter()
+        .map(|&(total, prob)| {
+            let padded = budget - total;
+            prob * match symbol_mode {
+                SymbolMode::Repeat if padded > 0 => 1.0,
+                SymbolMode::Repeat => 0.0,
+                SymbolMode::Independent => padded as f64,
+            }
+        })
+        .sum();
+    log::debug!(
+        "entropy: {:.2} padding symbol draw(s) for exact padding",
+        draws
+    );
+
+    (lists_bits + transform_bits).max(0.0) + draws * symbol_bits
+}
+
+/// Chance of a password to fit the length range of the settings
+pub fn length_acceptance<P: WordsPool + ?Sized>(settings: &Settings, pool: &P) -> f64 {
+    let (min, max) = settings.length_range;
+    if (min, max) == (None, None) {
+        return 1.0;
+    }
+
+    length_distribution(settings, pool)
+        .iter()
+        .enumerate()
+        .filter(|&(len, _)| min.is_none_or(|min| len >= min) && max.is_none_or(|max| len <= max))
+        .map(|(_, prob)| prob)
+        .sum()
+}
+
+/// Chance of each total length of passwords once substituted, indexed by length
+pub fn length_distribution<P: WordsPool + ?Sized>(settings: &Settings, pool: &P) -> Vec<f64> {
+    // padding fits the length before substitutions, resizing ones aren't accounted for
+    if let PaddingStrategy::Adaptive(len) | PaddingStrategy::Exact(len) = settings.padding_strategy
+    {
+        let mut lengths = vec![0.0; len + 1];
+        lengths[len] = 1.0;
+        return lengths;
+    }
+
+    let words = WordsModel::new(settings, pool);
+    let parts = password_parts(settings, words.count);
+
+    let symbol_lens = chars_lengths(&settings.padding_symbols, &settings.substitutions);
+    let separator_lens = chars_lengths(&settings.separators, &settings.substitutions);
+    let gaps = parts
+        .iter()
+        .filter(|&&part| part == Part::Separator)
+        .count();
+
+    let separators_lens = match settings.separator_mode {
+        SeparatorMode::Repeat => repeat_lengths(&separator_lens, gaps),
+        SeparatorMode::Independent => {
+            (0..gaps).fold(vec![1.0], |acc, _| convolve(&acc, &separator_lens))
+        }
+    };
+    let mut lengths = convolve(&separators_lens, &words_lengths(settings, pool, &words));
+
+    for part in parts {
+        lengths = match part {
+            Part::Symbols(count) => match settings.symbol_mode {
+                SymbolMode::Repeat => convolve(&lengths, &repeat_lengths(&symbol_lens, count)),
+                SymbolMode::Independent => {
+                    (0..count).fold(lengths, |acc, _| convolve(&acc, &symbol_lens))
+                }
+            },
+            Part::Digits(count) => {
+                let first = chars_lengths("123456789", &settings.substitutions);
+                let other = chars_lengths("0123456789", &settings.substitutions);
+                (1..count.min(MAX_PADDING_DIGITS))
+                    .fold(convolve(&lengths, &first), |acc, _| convolve(&acc, &other))
+            }
+            Part::Separator | Part::Word(_) => lengths,
+        };
+    }
+
+    lengths
+}
+
+/// Seen entropy of the words alone for each count up to the settings one,
+/// all sharing a single model of the pool
+pub fn words_entropies<P: WordsPool + ?Sized>(settings: &Settings, pool: &P) -> Vec<f64> {
+    let mut words = WordsModel::new(settings, pool);
+
+    (1..=words.count)
+        .map(|count| {
+            words.count = count;
+            (0..count).map(|idx| words.conditional_entropy(idx)).sum()
+        })
+        .collect()
+}
+
+// chance of each total length of the words once substituted, exact when each word gives
+// outputs of a single length & picks are uniform, as if words were picked independently otherwise
+fn words_lengths<P: WordsPool + ?Sized>(
+    settings: &Settings,
+    pool: &P,
+    words: &WordsModel,
+) -> Vec<f64> {
+    let independent = || {
+        (0..words.count).fold(vec![1.0], |acc, idx| {
+            convolve(&acc, &words.outputs_at(idx).len_probs)
+        })
+    };
+
+    if settings.unique_prefix > 0 {
+        return independent();
+    }
+
+    let transforms: Vec<WordTransform> = (0..2)
+        .flat_map(|idx| settings.transform_choices(idx))
+        .collect();
+    let output_len = |word: &str| {
+        let word = settings.fold_word(word);
+        let mut lens = transforms
+            .iter()
+            .flat_map(|&transform| transform_variants(&word, transform))
+            .map(|output| substitute(&output, &settings.substitutions).chars().count());
+
+        let len = lens.next()?;
+        lens.all(|other| other == len).then_some(len)
+    };
+
+    // weighted words aren't drawn uniformly among the lists of each total length
+    if !words.uniform {
+        return independent();
+    }
+
+    let exact_words = ExactWords::new(pool, words.count, output_len);
+    if exact_words.words(pool).len() < pool.len() {
+        return independent();
+    }
+
+    let mut lengths: Vec<f64> = vec![];
+    for (total, prob) in exact_words.totals(usize::MAX, false) {
+        lengths.resize(total + 1, 0.0);
+        lengths[total] = prob;
+    }
+
+    lengths
+}
+
+// chance of each length of a char picked uniformly from the pool once substituted,
+// no char giving no length at all
+fn chars_lengths(pool: &str, substitutions: &[(char, String)]) -> Vec<f64> {
+    let total = pool.chars().count();
+    if total == 0 {
+        return vec![1.0];
+    }
+
+    let mut lengths: Vec<f64> = vec![];
+    for c in pool.chars() {
+        let len = substitute_char(c, substitutions).chars().count();
+        if lengths.len() <= len {
+            lengths.resize(len + 1, 0.0);
+        }
+        lengths[len] += 1.0 / total as f64;
+    }
+
+    lengths
+}
+
+// lengths of the same char repeated
+fn repeat_lengths(lengths: &[f64], count: usize) -> Vec<f64> {
+    let mut repeated = vec![0.0; (lengths.len() - 1) * count + 1];
+    for (len, prob) in lengths.iter().enumerate() {
+        repeated[len * count] += prob;
+    }
+
+    repeated
+}
+
+// lengths of two independent parts put together
+fn convolve(lengths: &[f64], other: &[f64]) -> Vec<f64> {
+    let mut total = vec![0.0; lengths.len() + other.len() - 1];
+    for (len, prob) in lengths.iter().enumerate() {
+        for (other_len, other_prob) in other.iter().enumerate() {
+            total[len + other_len] += prob * other_prob;
+        }
+    }
+
+    total
+}
+
+/// Shannon entropy of a char picked uniformly from the pool once substituted,
+/// repeated chars & chars substituted the same way being more likely
+pub fn chars_entropy(pool: &str, substitutions: &[(char, String)]) -> f64 {
+    let counts = substituted_counts(pool, substitutions);
+    let total = pool.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| (count as f64 / total) * (total / count as f64).log2())
+        .sum()
+}
+
+/// Entropy of the first `kept` digits of a random number without leading zero, once substituted
+pub fn digits_entropy(count: u8, kept: usize, substitutions: &[(char, String)]) -> f64 {
+    if kept == 0 {
+        return 0.0;
+    }
+
+    let first_bits = chars_entropy("123456789", substitutions);
+    let digit_bits = chars_entropy("0123456789", substitutions);
+
+    // digits reading several ways once substituted are only credited for a single one
+    if !prefix_free("0123456789", substitutions) {
+        return if count >= MAX_PADDING_DIGITS {
+            0.0
+        } else if kept > 1 {
+            first_bits.max(digit_bits)
+        } else {
+            first_bits
+        };
+    }
+
+    if count < MAX_PADDING_DIGITS {
+        return first_bits + (kept - 1) as f64 * digit_bits;
+    }
+
+    // numbers between 10^19 & u64::MAX aren't uniform digit by digit
+    if kept < MAX_PADDING_DIGITS as usize {
+        return 0.0;
+    }
+
+    // each substituted digit hides at most as many digits as are substituted the same way
+    let groups = substituted_counts("0123456789", substitutions);
+    let max_group = groups.into_values().max().unwrap_or(1);
+
+    let bits = ((u64::MAX - 10u64.pow(MAX_PADDING_DIGITS as u32 - 1)) as f64).log2();
+    (bits - kept as f64 * (max_group as f64).log2()).max(0.0)
+}
+
+fn substitute_char(c: char, substitutions: &[(char, String)]) -> String {
+    substitute(c.encode_utf8(&mut [0; 4]), substitutions)
+}
+
+// how many chars of the pool give each output once substituted
+fn substituted_counts(pool: &str, substitutions: &[(char, String)]) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    pool.chars()
+        .for_each(|c| *counts.entry(substitute_char(c, substitutions)).or_default() += 1);
+
+    counts
+}
+
+// whether chars of the pool, once substituted, can be concatenated & still read one way only
+fn prefix_free(pool: &str, substitutions: &[(char, String)]) -> bool {
+    let outputs = substituted_counts(pool, substitutions);
+
+    outputs.keys().all(|output| {
+        outputs
+            .keys()
+            .all(|other| other == output || !other.starts_with(output.as_str()))
+    })
+}
+
+// same order as the assembled password: symbols, digits, words, digits & symbols,
+// with a separator between each words & digits blocks
+fn password_parts(settings: &Settings, words_count: usize) -> Vec<Part> {
+    let (prefix_symbols, suffix_symbols) = settings.padding_symbol_lengths;
+    let (prefix_digits, suffix_digits) = settings.padding_digits;
+
+    let mut blocks: Vec<Part> = vec![];
+
+    if prefix_digits > 0 {
+        blocks.push(Part::Digits(prefix_digits));
+    }
+
+    blocks.extend((0..words_count).map(Part::Word));
+
+    if suffix_digits > 0 {
+        blocks.push(Part::Digits(suffix_digits));
+    }
+
+    let mut parts: Vec<Part> = vec![];
+
+    if prefix_symbols > 0 {
+        parts.push(Part::Symbols(prefix_symbols as usize));
+    }
+
+    for (idx, block) in blocks.into_iter().enumerate() {
+        if idx > 0 {
+            parts.push(Part::Separator);
+        }
+        parts.push(block);
+    }
+
+    if suffix_symbols > 0 {
+        parts.push(Part::Symbols(suffix_symbols as usize));
+    }
+
+    parts
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    Min,
+    Max,
+}
+
+fn part_len(settings: &Settings, words: &WordsModel, part: Part, bound: Bound) -> usize {
+    match part {
+        Part::Symbols(_) if settings.padding_symbols.is_empty() => 0,
+        Part::Symbols(count) => count,
+        Part::Digits(count) => count.min(MAX_PADDING_DIGITS) as usize,
+        Part::Separator if settings.separators.is_empty() => 0,
+        Part::Separator => 1,
+        Part::Word(idx) => {
+            let outputs = words.outputs_at(idx);
+            match bound {
+                Bound::Min => outputs.min_len,
+                Bound::Max => outputs.max_len,
+            }
+        }
+    }
+}
+
+// expected number of padding symbols drawn separately, or a lower bound if trimming can happen
+fn padding_draws(
+    settings: &Settings,
+    words: &WordsModel,
+    parts: &[Part],
+    len: usize,
+    symbol_mode: SymbolMode,
+) -> f64 {
+    let (mut min_total, mut max_total, mut mean_total) = (0, 0, 0.0);
+
+    for &part in parts {
+        let min_len = part_len(settings, words, part, Bound::Min);
+        let max_len = part_len(settings, words, part, Bound::Max);
+
+        min_total += min_len;
+        max_total += max_len;
+        mean_total += match part {
+            Part::Word(idx) => words.outputs_at(idx).mean_len,
+            _ => max_len as f64,
+        };
+    }
+
+    let len_f = len as f64;
+
+    match symbol_mode {
+        // E[max(0, len - total)] >= max(0, len - E[total]), even when trimming can happen
+        SymbolMode::Independent => (len_f - mean_total).max(0.0),
+        SymbolMode::Repeat if len > max_total => 1.0,
+        SymbolMode::Repeat if len == max_total => 1.0 - words.all_longest_probability(),
+        // Markov's inequality: P(total >= len) <= E[total - min] / (len - min)
+        SymbolMode::Repeat if len > min_total => {
+            let excess = (mean_total - min_total as f64) / (len - min_total) as f64;
+            (1.0 - excess).max(0.0)
+        }
+        SymbolMode::Repeat => 0.0,
+    }
+}
+
+// outputs of every word in the pool under the transforms picked at a given position,
+// words weighing as much as their chance to be drawn
+#[derive(Debug)]
+struct WordOutputs {
+    // chance of each word giving an output as long as the longest one
+    longest_probs: Vec<f64>,
+    // chance of a drawn word giving an output as long as the longest one
+    longest_prob: f64,
+    // entropy of transforms per word, averaged over the pool
+    mean_transform_entropy: f64,
+    // entropy of transforms of the word getting the fewest outputs
+    min_transform_entropy: f64,
+    // entropy of a single output drawn with replacement from the pool
+    mixed_entropy: f64,
+    // highest chance of any output for a single draw, summed over all words giving it
+    max_output_prob: f64,
+    has_collisions: bool,
+    min_len: usize,
+    max_len: usize,
+    mean_len: f64,
+    // chance of each length of an output once substituted, indexed by length
+    len_probs: Vec<f64>,
+}
+
+impl WordOutputs {
+    fn new<P: WordsPool + ?Sized>(
+        settings: &Settings,
+        pool: &P,
+        transforms: &[WordTransform],
+    ) -> Self {
+        let transform_prob = 1.0 / transforms.len() as f64;
+
+        // chance of each output across words
+        let mut outputs: HashMap<String, f64> = HashMap::new();
+        let mut has_collisions = false;
+
+        let mut word_lens: Vec<Vec<(usize, f64)>> = Vec::with_capacity(pool.len());
+        let mut mean_transform_entropy = 0.0;
+        let mut min_transform_entropy = f64::INFINITY;
+        let mut mean_len = 0.0;
+        let mut len_probs: Vec<f64> = vec![];
+
+        for word_idx in 0..pool.len() {
+            let word_prob = pool.prob(word_idx);
+            let word = settings.fold_word(pool.word(word_idx));
+            let mut word_outputs: HashMap<String, f64> = HashMap::new();
+
+            for &transform in transforms {
+                let variants = transform_variants(&word, transform);
+                let variant_prob = transform_prob / variants.len() as f64;
+
+                for output in variants {
+                    *word_outputs.entry(output).or_default() += variant_prob;
+                }
+            }
+
+            let mut lens: Vec<(usize, f64)> = vec![];
+            // padding & trimming happen before substitutions, unlike collisions
+            let mut substituted: HashMap<String, f64> = HashMap::new();
+
+            for (output, prob) in word_outputs {
+                mean_len += word_prob * prob * output.chars().count() as f64;
+                lens.push((output.chars().count(), prob));
+
+                *substituted
+                    .entry(substitute(&output, &settings.substitutions))
+                    .or_default() += prob;
+            }
+
+            let mut word_transform_entropy = 0.0;
+
+            for (output, prob) in substituted {
+                word_transform_entropy -= prob * prob.log2();
+
+                let len = output.chars().count();
+                if len_probs.len() <= len {
+                    len_probs.resize(len + 1, 0.0);
+                }
+                len_probs[len] += prob * word_prob;
+
+                // outputs of a single word are distinct, any known one came from another word
+                match outputs.get_mut(&output) {
+                    Some(total) => {
+                        has_collisions = true;
+                        *total += prob * word_prob;
+                    }
+                    None => {
+                        outputs.insert(output, prob * word_prob);
+                    }
+                }
+            }
+
+            mean_transform_entropy += word_transform_entropy * word_prob;
+            min_transform_entropy = min_transform_entropy.min(word_transform_entropy);
+            word_lens.push(lens);
+        }
+
+        let lens = word_lens.iter().flatten().map(|&(len, _)| len);
+        let min_len = lens.clone().min().unwrap_or(0);
+        let max_len = lens.max().unwrap_or(0);
+
+        let longest_probs: Vec<f64> = word_lens
+            .iter()
+            .map(|lens| {
+                lens.iter()
+                    .filter(|&&(len, _)| len == max_len)
+                    .map(|&(_, prob)| prob)
+                    .sum()
+            })
+            .collect();
+        let longest_prob = longest_probs
+            .iter()
+            .enumerate()
+            .map(|(word_idx, prob)| prob * pool.prob(word_idx))
+            .sum();
+
+        let mixed_entropy = outputs.values().map(|&prob| -prob * prob.log2()).sum();
+
+        let max_output_prob = outputs.values().copied().fold(0.0, f64::max);
+
+        WordOutputs {
+            longest_probs,
+            longest_prob,
+            mean_transform_entropy,
+            min_transform_entropy: if pool.is_empty() {
+                0.0
+            } else {
+                min_transform_entropy
+            },
+            mixed_entropy,
+            max_output_prob,
+            has_collisions,
+            min_len,
+            max_len,
+            mean_len,
+            len_probs,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct WordsModel {
+    pool_size: usize,
+    count: usize,
+    // words drawn with the same chance, e.g. not from weighted languages
+    uniform: bool,
+    // chances of the most & least likely words
+    max_prob: f64,
+    min_prob: f64,
+    // Shannon entropy of a single word drawn from the pool
+    pick_entropy: f64,
+    // largest share of a single word in that entropy
+    max_pick_share: f64,
+    // most words sharing a prefix, each pick rules out at most as many
+    max_prefix_group: usize,
+    // a single entry for randomized transforms, or one per parity for alternating ones
+    outputs: Vec<WordOutputs>,
+}
+
+impl WordsModel {
+    fn new<P: WordsPool + ?Sized>(settings: &Settings, pool: &P) -> Self {
+        // an empty pool gives no words at all
+        let count = if pool.is_empty() {
+            0
+        } else {
+            settings.words_count as usize
+        };
+
+        let alternating = settings
+            .word_transforms
+            .has_flag(WordTransform::AltercaseLowerFirst)
+            || settings
+                .word_transforms
+                .has_flag(WordTransform::AltercaseUpperFirst);
+
+        let classes = if alternating { 2 } else { 1 };
+        let outputs = (0..classes.min(count))
+            .map(|idx| WordOutputs::new(settings, pool, &settings.transform_choices(idx)))
+            .collect();
+
+        let probs: Vec<f64> = (0..pool.len()).map(|idx| pool.prob(idx)).collect();
+        let max_prob = probs.iter().copied().fold(0.0, f64::max);
+        let min_prob = probs.iter().copied().fold(f64::INFINITY, f64::min);
+        let shares = probs.iter().map(|&prob| -prob * prob.log2());
+
+        let mut prefix_groups: HashMap<String, usize> = HashMap::new();
+
+        if settings.unique_prefix > 0 {
+            for word_idx in 0..pool.len() {
+                *prefix_groups
+                    .entry(settings.word_prefix(pool.word(word_idx)))
+                    .or_default() += 1;
+            }
+        }
+
+        WordsModel {
+            pool_size: pool.len(),
+            count,
+            uniform: max_prob == min_prob,
+            max_prob,
+            min_prob,
+            pick_entropy: shares.clone().sum(),
+            max_pick_share: shares.fold(0.0, f64::max),
+            max_prefix_group: prefix_groups.into_values().max().unwrap_or(1),
+            outputs,
+        }
+    }
+
+    fn outputs_at(&self, idx: usize) -> &WordOutputs {
+        &self.outputs[idx % self.outputs.len()]
+    }
+
+    // chance left to the words not ruled out yet, at least one word being always left
+    fn remaining_prob(&self, ruled_out: usize) -> f64 {
+        (1.0 - ruled_out as f64 * self.max_prob).max(self.min_prob)
+    }
+
+    // entropy of a word drawn once some words are ruled out: its min-entropy, or its Shannon
+    // entropy less the share of the words ruled out, renormalized, whichever is higher
+    fn pick_entropy(&self, ruled_out: usize) -> f64 {
+        let remaining_prob = self.remaining_prob(ruled_out);
+        let min_entropy = (remaining_prob / self.max_prob).log2();
+
+        let removed_prob = ruled_out as f64 * self.max_prob;
+        if removed_prob >= 1.0 {
+            return min_entropy;
+        }
+
+        let shannon_entropy = self.pick_entropy + (1.0 - removed_prob).log2()
+            - ruled_out as f64 * self.max_pick_share;
+        min_entropy.max(shannon_entropy)
+    }
+
+    // entropy of the word at a position knowing all the previous ones
+    fn conditional_entropy(&self, idx: usize) -> f64 {
+        let outputs = self.outputs_at(idx);
+
+        // not enough words, picked with replacement
+        if self.pool_size < self.count {
+            return outputs.mixed_entropy;
+        }
+
+        let ruled_out = (idx * self.max_prefix_group).min(self.pool_size);
+
+        if outputs.has_collisions {
+            (self.remaining_prob(ruled_out) / outputs.max_output_prob)
+                .log2()
+                .max(0.0)
+        } else {
+            self.pick_entropy(ruled_out) + outputs.mean_transform_entropy
+        }
+    }
+
+    // chance of all words being at their longest, or an upper bound if that depends
+    // on the position of alternating transforms, on prefixes or on weighted words
+    fn all_longest_probability(&self) -> f64 {
+        if self.count == 0 {
+            return 1.0;
+        }
+
+        if self.pool_size < self.count {
+            return (0..self.count)
+                .map(|idx| self.outputs_at(idx).longest_prob)
+                .product();
+        }
+
+        // the first word is still drawn independently, later ones depend on prefixes & weights
+        if self.max_prefix_group > 1 || !self.uniform {
+            return self.outputs[0].longest_prob;
+        }
+
+        let longest_probs: Vec<f64> = (0..self.pool_size)
+            .map(|word_idx| {
+                self.outputs
+                    .iter()
+                    .map(|outputs| outputs.longest_probs[word_idx])
+                    .fold(0.0, f64::max)
+            })
+            .collect();
+
+        // mean product over all word subsets of each size, i.e. elementary symmetric
+        // polynomials normalized by binomial coefficients, to avoid overflows
+        let mut means = vec![0.0; self.count + 1];
+        means[0] = 1.0;
+
+        for (seen, prob) in longest_probs.iter().enumerate() {
+            let total = (seen + 1) as f64;
+
+            for size in (1..=self.count.min(seen + 1)).rev() {
+                means[size] = means[size] * (total - size as f64) / total
+                    + prob * means[size - 1] * size as f64 / total;
+            }
+        }
+
+        means[self.count]
+    }
+}
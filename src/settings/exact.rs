@@ -0,0 +1,209 @@
+use super::rand_index;
+use crate::prelude::WordsPool;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::{index, SliceRandom};
+use rand::{CryptoRng, RngCore};
+
+// Exact padding never trims: words are drawn among the lists whose total length
+// fits the budget left by digits & separators, padding symbols making up the rest.
+//
+// Lists are uniformly likely: the total length is drawn first, weighted by how many
+// lists add up to it, then how many words of each length, then the words themselves
+// & finally their order. Counts are kept as natural logs, they overflow any integer
+// or float for long passwords drawn from large dictionaries.
+//
+// Building the table walks the whole pool, it's built once per settings & pool
+// then kept alongside the pool, hence words are referred to by their index in it.
+
+#[derive(Debug)]
+pub struct ExactWords {
+    // indices of the words in the pool, grouped by their length once transformed
+    groups: Vec<(usize, Vec<usize>)>,
+    count: usize,
+    // not enough words to distinguishably randomize, picked with replacement
+    with_replacement: bool,
+    // ln of the number of ways to pick `j` words totalling `t` chars from the first `g` groups,
+    // up to the order of the words
+    ways: Vec<Vec<Vec<f64>>>,
+}
+
+impl ExactWords {
+    pub fn new<P: WordsPool + ?Sized, F: Fn(&str) -> Option<usize>>(
+        pool: &P,
+        count: usize,
+        word_len: F,
+    ) -> Self {
+        let mut groups: Vec<(usize, Vec<usize>)> = vec![];
+
+        for idx in 0..pool.len() {
+            // words whose length depends on their transform can't be budgeted
+            let Some(len) = word_len(pool.word(idx)) else {
+                continue;
+            };
+
+            match groups.iter_mut().find(|(group_len, _)| *group_len == len) {
+                Some((_, words)) => words.push(idx),
+                None => groups.push((len, vec![idx])),
+            }
+        }
+
+        groups.sort_by_key(|(len, _)| *len);
+
+        let with_replacement = pool.len() < count;
+        let max_total = count * groups.last().map_or(0, |(len, _)| *len);
+
+        let mut ways = vec![vec![vec![f64::NEG_INFINITY; max_total + 1]; count + 1]];
+        ways[0][0][0] = 0.0;
+
+        for (len, words) in &groups {
+            let last = ways.last().unwrap();
+            let mut next = vec![vec![f64::NEG_INFINITY; max_total + 1]; count + 1];
+
+            for (picked, totals) in next.iter_mut().enumerate() {
+                for (total, ways_to) in totals.iter_mut().enumerate() {
+                    let terms: Vec<f64> = (0..=picked)
+                        .take_while(|m| m * len <= total)
+                        .map(|m| {
+                            last[picked - m][total - m * len]
+                                + ln_weight(words.len(), m, with_replacement)
+                        })
+                        .collect();
+                    *ways_to = ln_sum(&terms);
+                }
+            }
+
+            ways.push(next);
+        }
+
+        ExactWords {
+            groups,
+            count,
+            with_replacement,
+            ways,
+        }
+    }
+
+    /// Words of the pool the table was built from that can be budgeted,
+    /// i.e. of the same length whatever their transform
+    pub fn words<'a, P: WordsPool + ?Sized>(&self, pool: &'a P) -> Vec<&'a str> {
+        self.groups
+            .iter()
+            .flat_map(|(_, words)| words.iter().map(|&idx| pool.word(idx)))
+            .collect()
+    }
+
+    /// Chance of each total length of words fitting the budget, exactly or at most,
+    /// empty if none fits
+    pub fn totals(&self, budget: usize, exact: bool) -> Vec<(usize, f64)> {
+        let ways = &self.ways[self.groups.len()][self.count];
+        let min = if exact { budget } else { 0 };
+
+        let fitting: Vec<(usize, f64)> = (min..=budget.min(ways.len() - 1))
+            .map(|total| (total, ways[total]))
+            .filter(|(_, ways)| ways.is_finite())
+            .collect();
+
+        let all = ln_sum(&fitting.iter().map(|&(_, ways)| ways).collect::<Vec<f64>>());
+
+        fitting
+            .into_iter()
+            .map(|(total, ways)| (total, (ways - all).exp()))
+            .collect()
+    }
+
+    /// Entropy in bits of a list of words drawn uniformly among the fitting ones,
+    /// negative infinity if none fits
+    pub fn entropy(&self, budget: usize, exact: bool) -> f64 {
+        let ways = &self.ways[self.groups.len()][self.count];
+        let min = if exact { budget } else { 0 };
+        let fitting: Vec<f64> = ways
+            .iter()
+            .take(budget.saturating_add(1))
+            .skip(min)
+            .copied()
+            .collect();
+
+        // any order of the picked words
+        let orders: f64 = (1..=self.count).map(|n| (n as f64).ln()).sum();
+
+        (ln_sum(&fitting) + orders) / std::f64::consts::LN_2
+    }
+
+    /// Words drawn from the pool the table was built from
+    pub fn sample<'a, R: RngCore + CryptoRng, P: WordsPool + ?Sized>(
+        &self,
+        rng: &mut R,
+        pool: &'a P,
+        budget: usize,
+        exact: bool,
+    ) -> Option<Vec<&'a str>> {
+        let totals = self.totals(budget, exact);
+        let weights = WeightedIndex::new(totals.iter().map(|&(_, prob)| prob)).ok()?;
+        let (mut total, _) = totals[weights.sample(rng)];
+        let mut picked = self.count;
+        let mut words: Vec<usize> = vec![];
+
+        for (group, (len, group_words)) in self.groups.iter().enumerate().rev() {
+            let last = &self.ways[group];
+            let ways_to = self.ways[group + 1][picked][total];
+
+            // how many words of this length, weighted by the ways to pick the others
+            let counts: Vec<f64> = (0..=picked)
+                .map(|m| match m * len <= total {
+                    true => (last[picked - m][total - m * len]
+                        + ln_weight(group_words.len(), m, self.with_replacement)
+                        - ways_to)
+                        .exp(),
+                    false => 0.0,
+                })
+                .collect();
+            let m = WeightedIndex::new(&counts).ok()?.sample(rng);
+
+            if self.with_replacement {
+                words.extend((0..m).map(|_| group_words[rand_index(rng, group_words.len())]));
+            } else {
+                words.extend(
+                    index::sample(rng, group_words.len(), m)
+                        .iter()
+                        .map(|idx| group_words[idx]),
+                );
+            }
+
+            picked -= m;
+            total -= m * len;
+        }
+
+        words.shuffle(rng);
+        Some(words.into_iter().map(|idx| pool.word(idx)).collect())
+    }
+}
+
+// ln of the ways to pick `m` words of a group of `n` regardless of their order,
+// i.e. n choose m, or n^m / m! when picked with replacement
+fn ln_weight(n: usize, m: usize, with_replacement: bool) -> f64 {
+    if !with_replacement && m > n {
+        return f64::NEG_INFINITY;
+    }
+
+    (0..m)
+        .map(|idx| {
+            let choices = if with_replacement { n } else { n - idx };
+            (choices as f64).ln() - ((idx + 1) as f64).ln()
+        })
+        .sum()
+}
+
+// ln of the sum of values given as natural logs
+fn ln_sum(terms: &[f64]) -> f64 {
+    let max = terms.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+
+    max + terms
+        .iter()
+        .map(|term| (term - max).exp())
+        .sum::<f64>()
+        .ln()
+}
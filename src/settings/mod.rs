@@ -1,31 +1,54 @@
+mod entropy;
+mod exact;
+#[cfg(feature = "serde")]
+mod schema;
 #[cfg(test)]
 mod tests;
 
 use crate::bit_flags::{BitFlags, FieldSize, WordTransform};
+use crate::case;
 use crate::prelude::{
-    Builder, Entropy, GuessTime, PaddingResult, PaddingStrategy, Preset, Randomizer,
+    Attacker, Builder, Entropy, PaddingResult, PaddingStrategy, Preset, Randomizer, SeparatorMode,
+    SymbolMode, WordsPool, XkpasswdError, MAX_LENGTH_ATTEMPTS,
 };
-use rand::distributions::{Distribution, Uniform};
-use rand::Rng;
+use exact::ExactWords;
+use rand::distributions::{Distribution, Uniform, WeightedIndex};
+use rand::{CryptoRng, Rng, RngCore};
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::ops::Range;
 use std::result::Result;
 
-const MIN_WORD_LENGTH_ERR: &str = "min word length must be 4 or higher";
-const MAX_WORD_LENGTH_ERR: &str = "max word length must be 10 or lower";
+// longest padding digits fitting in a u64
+const MAX_PADDING_DIGITS: u8 = 20;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+// lowest chance of a password to fit the length range, running out of attempts
+// to regenerate one being then less likely than e^-20
+const MIN_LENGTH_ACCEPTANCE: f64 = 20.0 / MAX_LENGTH_ATTEMPTS as f64;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "schema::SettingsSchema", into = "schema::SettingsSchema")
+)]
 pub struct Settings {
     words_count: u8,
     word_lengths: (u8, u8),
-    word_transforms: u8,
+    word_transforms: FieldSize,
+    ascii_fold: bool,
+    unique_prefix: u8,
     separators: String,
+    separator_mode: SeparatorMode,
     padding_digits: (u8, u8),
     padding_symbols: String,
     padding_symbol_lengths: (u8, u8),
     padding_strategy: PaddingStrategy,
+    symbol_mode: SymbolMode,
+    substitutions: Vec<(char, String)>,
+    length_range: (Option<usize>, Option<usize>),
 }
 
 impl Default for Settings {
@@ -34,11 +57,17 @@ impl Default for Settings {
             words_count: Self::DEFAULT_WORDS_COUNT,
             word_lengths: Self::DEFAULT_WORD_LENGTHS,
             word_transforms: Self::DEFAULT_WORD_TRANSFORMS,
+            ascii_fold: false,
+            unique_prefix: 0,
             separators: Self::DEFAULT_SEPARATORS.to_string(),
+            separator_mode: SeparatorMode::default(),
             padding_digits: (0, Self::DEFAULT_PADDING_LENGTH),
             padding_symbols: Self::DEFAULT_SYMBOLS.to_string(),
             padding_symbol_lengths: (0, Self::DEFAULT_PADDING_LENGTH),
             padding_strategy: Self::DEFAULT_PADDING_STRATEGY,
+            symbol_mode: SymbolMode::default(),
+            substitutions: vec![],
+            length_range: (None, None),
         }
     }
 }
@@ -62,8 +91,26 @@ impl fmt::Display for Settings {
             desc.push(format!("{} only", word_transforms[0]))
         }
 
-        if self.separators.len() > 1 {
-            desc.push(format!("a separator from ⟪{}⟫", self.separators));
+        if self.ascii_fold {
+            desc.push("accents folded to ASCII".to_string());
+        }
+
+        if self.unique_prefix > 0 {
+            desc.push(format!(
+                "no words sharing their first {} char(s)",
+                self.unique_prefix
+            ));
+        }
+
+        if self.separators.chars().count() > 1 {
+            match self.separator_mode {
+                SeparatorMode::Repeat => {
+                    desc.push(format!("a separator from ⟪{}⟫", self.separators))
+                }
+                SeparatorMode::Independent => {
+                    desc.push(format!("separators each drawn from ⟪{}⟫", self.separators))
+                }
+            }
         } else {
             desc.push(format!("'{}' as separator", self.separators));
         }
@@ -81,8 +128,13 @@ impl fmt::Display for Settings {
             desc.push(format!("{} digit(s) after", suffix));
         }
 
-        let padding_symbols = if self.padding_symbols.len() > 1 {
-            format!("from ⟪{}⟫", self.padding_symbols)
+        let padding_symbols = if self.padding_symbols.chars().count() > 1 {
+            match self.symbol_mode {
+                SymbolMode::Repeat => format!("from ⟪{}⟫", self.padding_symbols),
+                SymbolMode::Independent => {
+                    format!("each drawn from ⟪{}⟫", self.padding_symbols)
+                }
+            }
         } else {
             format!("of '{}'", self.padding_symbols)
         };
@@ -111,10 +163,32 @@ impl fmt::Display for Settings {
             PaddingStrategy::Adaptive(len) => {
                 format!("pad/trim symbols {} to fit {} chars", padding_symbols, len)
             }
+            PaddingStrategy::Exact(len) => format!(
+                "words picked & symbols {} padded to fit exactly {} chars",
+                padding_symbols, len
+            ),
         };
 
         desc.push(padding);
 
+        if !self.substitutions.is_empty() {
+            let rules: Vec<String> = self
+                .substitutions
+                .iter()
+                .map(|(from, to)| format!("'{}' → '{}'", from, to))
+                .collect();
+            desc.push(format!("chars substituted as {}", rules.join(", ")));
+        }
+
+        match self.length_range {
+            (Some(min), Some(max)) => {
+                desc.push(format!("passwords from {} to {} chars only", min, max))
+            }
+            (Some(min), None) => desc.push(format!("passwords of {} chars or more only", min)),
+            (None, Some(max)) => desc.push(format!("passwords of {} chars or less only", max)),
+            (None, None) => {}
+        }
+
         let len = desc.len();
         if len > 1 {
             write!(
@@ -130,9 +204,12 @@ impl fmt::Display for Settings {
 }
 
 impl Builder for Settings {
-    fn with_words_count(&self, words_count: u8) -> Result<Self, String> {
-        if words_count == 0 {
-            return Err("only positive integer is allowed for words count".to_string());
+    fn with_words_count(&self, words_count: u8) -> Result<Self, XkpasswdError> {
+        if words_count < Self::MIN_WORDS_COUNT {
+            return Err(XkpasswdError::WordsCount {
+                value: words_count,
+                min: Self::MIN_WORDS_COUNT,
+            });
         }
 
         let mut cloned = self.clone();
@@ -144,7 +221,7 @@ impl Builder for Settings {
         &self,
         min_length: Option<u8>,
         max_length: Option<u8>,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, XkpasswdError> {
         let min_length = min_length.unwrap_or(self.word_lengths.0);
         let max_length = max_length.unwrap_or(self.word_lengths.1);
 
@@ -152,11 +229,11 @@ impl Builder for Settings {
         let max = cmp::max(min_length, max_length);
 
         if min < Self::MIN_WORD_LENGTH {
-            return Err(MIN_WORD_LENGTH_ERR.to_string());
+            return Err(Self::word_length_err("word_min", min));
         }
 
         if max > Self::MAX_WORD_LENGTH {
-            return Err(MAX_WORD_LENGTH_ERR.to_string());
+            return Err(Self::word_length_err("word_max", max));
         }
 
         let mut cloned = self.clone();
@@ -203,14 +280,21 @@ impl Builder for Settings {
         cloned
     }
 
-    fn with_padding_strategy(&self, strategy: PaddingStrategy) -> Result<Self, String> {
+    fn with_padding_strategy(&self, strategy: PaddingStrategy) -> Result<Self, XkpasswdError> {
         let mut cloned = self.clone();
 
         match strategy {
             PaddingStrategy::Adaptive(0) => {
-                return Err("invalid adaptive padding number".to_string())
+                return Err(XkpasswdError::AdaptiveLength { value: 0, min: 1 })
             }
-            PaddingStrategy::Adaptive(_) => {
+            PaddingStrategy::Exact(0) => {
+                return Err(XkpasswdError::ExactLength { value: 0, min: 1 })
+            }
+            PaddingStrategy::Adaptive(_) | PaddingStrategy::Exact(_) => {
+                if let PaddingStrategy::Exact(_) = strategy {
+                    check_exact_substitutions(&self.substitutions)?;
+                }
+
                 cloned.padding_strategy = strategy;
                 cloned.padding_symbol_lengths = (0, 0);
             }
@@ -222,7 +306,19 @@ impl Builder for Settings {
         Ok(cloned)
     }
 
-    fn with_word_transforms(&self, transforms: FieldSize) -> Result<Self, String> {
+    fn with_separator_mode(&self, mode: SeparatorMode) -> Self {
+        let mut cloned = self.clone();
+        cloned.separator_mode = mode;
+        cloned
+    }
+
+    fn with_symbol_mode(&self, mode: SymbolMode) -> Self {
+        let mut cloned = self.clone();
+        cloned.symbol_mode = mode;
+        cloned
+    }
+
+    fn with_word_transforms(&self, transforms: FieldSize) -> Result<Self, XkpasswdError> {
         let mut cloned = self.clone();
 
         // handle group transforms first
@@ -237,12 +333,11 @@ impl Builder for Settings {
         }
 
         // no transform matched
-        if !transforms.has_flag(WordTransform::Lowercase)
-            && !transforms.has_flag(WordTransform::Titlecase)
-            && !transforms.has_flag(WordTransform::Uppercase)
-            && !transforms.has_flag(WordTransform::InversedTitlecase)
+        if !Self::ALL_SINGLE_WORD_TRANSFORMS
+            .iter()
+            .any(|&transform| transforms.has_flag(transform))
         {
-            return Err("invalid transform".to_string());
+            return Err(XkpasswdError::WordTransforms { value: transforms });
         }
 
         let mut cloned = self.clone();
@@ -250,77 +345,176 @@ impl Builder for Settings {
         Ok(cloned)
     }
 
+    fn with_ascii_fold(&self, ascii_fold: bool) -> Self {
+        let mut cloned = self.clone();
+        cloned.ascii_fold = ascii_fold;
+        cloned
+    }
+
+    fn with_unique_prefix(&self, length: u8) -> Self {
+        let mut cloned = self.clone();
+        cloned.unique_prefix = length;
+        cloned
+    }
+
+    fn with_substitutions(&self, rules: &[(char, String)]) -> Result<Self, XkpasswdError> {
+        let repeated = rules
+            .iter()
+            .enumerate()
+            .find(|(idx, (from, _))| rules[..*idx].iter().any(|(other, _)| other == from));
+
+        if let Some((_, (from, _))) = repeated {
+            return Err(XkpasswdError::Substitution {
+                rule: from.to_string(),
+                reason: "char substituted more than once",
+            });
+        }
+
+        if let PaddingStrategy::Exact(_) = self.padding_strategy {
+            check_exact_substitutions(rules)?;
+        }
+
+        let mut cloned = self.clone();
+        cloned.substitutions = rules.to_vec();
+        Ok(cloned)
+    }
+
+    fn with_length_range(
+        &self,
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+    ) -> Result<Self, XkpasswdError> {
+        let min_length = min_length.or(self.length_range.0);
+        let max_length = max_length.or(self.length_range.1);
+
+        if let (Some(min), Some(max)) = (min_length, max_length) {
+            if min > max {
+                return Err(XkpasswdError::LengthRange {
+                    field: "max_length",
+                    min,
+                    max,
+                });
+            }
+        }
+
+        let mut cloned = self.clone();
+        cloned.length_range = (min_length, max_length);
+        Ok(cloned)
+    }
+
     fn from_preset(preset: Preset) -> Self {
         match preset {
             Preset::AppleID => Settings {
                 words_count: 3,
                 word_lengths: (5, 7),
                 word_transforms: WordTransform::Lowercase | WordTransform::Uppercase,
+                ascii_fold: false,
+                unique_prefix: 0,
                 separators: "-:.,".to_string(),
+                separator_mode: SeparatorMode::Repeat,
                 padding_digits: (2, 2),
                 padding_symbols: "!?@&".to_string(),
                 padding_symbol_lengths: (1, 1),
                 padding_strategy: PaddingStrategy::Fixed,
+                symbol_mode: SymbolMode::Repeat,
+                substitutions: vec![],
+                length_range: (None, None),
             },
             Preset::WindowsNtlmV1 => Settings {
                 words_count: 2,
                 word_lengths: (5, 5),
                 word_transforms: FieldSize::from_flag(WordTransform::InversedTitlecase),
+                ascii_fold: false,
+                unique_prefix: 0,
                 separators: "-+=.*_|~,".to_string(),
+                separator_mode: SeparatorMode::Repeat,
                 padding_digits: (1, 0),
                 padding_symbols: "!@$%^&*+=:|~?".to_string(),
                 padding_symbol_lengths: (0, 1),
                 padding_strategy: PaddingStrategy::Fixed,
+                symbol_mode: SymbolMode::Repeat,
+                substitutions: vec![],
+                length_range: (None, None),
             },
             Preset::SecurityQuestions => Settings {
                 words_count: 6,
                 word_lengths: (4, 8),
                 word_transforms: FieldSize::from_flag(WordTransform::Lowercase),
+                ascii_fold: false,
+                unique_prefix: 0,
                 separators: " ".to_string(),
+                separator_mode: SeparatorMode::Repeat,
                 padding_digits: (0, 0),
                 padding_symbols: ".!?".to_string(),
                 padding_symbol_lengths: (0, 1),
                 padding_strategy: PaddingStrategy::Fixed,
+                symbol_mode: SymbolMode::Repeat,
+                substitutions: vec![],
+                length_range: (None, None),
             },
             Preset::Web16 => Settings {
                 words_count: 3,
                 word_lengths: (4, 4),
                 word_transforms: WordTransform::Lowercase | WordTransform::Uppercase,
+                ascii_fold: false,
+                unique_prefix: 0,
                 separators: "-+=.*_|~,".to_string(),
+                separator_mode: SeparatorMode::Repeat,
                 padding_digits: (0, 0),
                 padding_symbols: "!@$%^&*+=:|~?".to_string(),
                 padding_symbol_lengths: (1, 1),
                 padding_strategy: PaddingStrategy::Fixed,
+                symbol_mode: SymbolMode::Repeat,
+                substitutions: vec![],
+                length_range: (None, None),
             },
             Preset::Web32 => Settings {
                 words_count: 4,
                 word_lengths: (4, 5),
                 word_transforms: FieldSize::from_flag(WordTransform::AltercaseUpperFirst),
+                ascii_fold: false,
+                unique_prefix: 0,
                 separators: "-+=.*_|~,".to_string(),
+                separator_mode: SeparatorMode::Repeat,
                 padding_digits: (2, 2),
                 padding_symbols: "!@$%^&*+=:|~?".to_string(),
                 padding_symbol_lengths: (1, 1),
                 padding_strategy: PaddingStrategy::Fixed,
+                symbol_mode: SymbolMode::Repeat,
+                substitutions: vec![],
+                length_range: (None, None),
             },
             Preset::Wifi => Settings {
                 words_count: 6,
                 word_lengths: (4, 8),
                 word_transforms: WordTransform::Lowercase | WordTransform::Uppercase,
+                ascii_fold: false,
+                unique_prefix: 0,
                 separators: "-+=.*_|~,".to_string(),
+                separator_mode: SeparatorMode::Repeat,
                 padding_digits: (4, 4),
                 padding_symbols: "!@$%^&*+=:|~?".to_string(),
                 padding_symbol_lengths: (0, 0),
                 padding_strategy: PaddingStrategy::Adaptive(63),
+                symbol_mode: SymbolMode::Repeat,
+                substitutions: vec![],
+                length_range: (None, None),
             },
             Preset::Xkcd => Settings {
                 words_count: 4,
                 word_lengths: (4, 8),
                 word_transforms: WordTransform::Lowercase | WordTransform::Uppercase,
+                ascii_fold: false,
+                unique_prefix: 0,
                 separators: "-".to_string(),
+                separator_mode: SeparatorMode::Repeat,
                 padding_digits: (0, 0),
                 padding_symbols: "".to_string(),
                 padding_symbol_lengths: (0, 0),
                 padding_strategy: PaddingStrategy::Fixed,
+                symbol_mode: SymbolMode::Repeat,
+                substitutions: vec![],
+                length_range: (None, None),
             },
             _ => Self::default(),
         }
@@ -328,13 +522,31 @@ impl Builder for Settings {
 }
 
 impl Randomizer for Settings {
+    // the exact padding table, only built for exact padding
+    type PoolState = Option<ExactWords>;
+
     fn word_lengths(&self) -> Range<u8> {
         let (min, max) = self.word_lengths;
         min..(max + 1)
     }
 
-    fn rand_words(&self, pool: &[&str]) -> Vec<String> {
-        let words_list = self.build_words_list(pool);
+    fn pool_state<P: WordsPool + ?Sized>(&self, pool: &P) -> Option<ExactWords> {
+        let PaddingStrategy::Exact(_) = self.padding_strategy else {
+            return None;
+        };
+
+        Some(ExactWords::new(pool, self.words_count as usize, |word| {
+            self.transformed_len(word)
+        }))
+    }
+
+    fn rand_words_with<R: RngCore + CryptoRng, P: WordsPool + ?Sized>(
+        &self,
+        rng: &mut R,
+        pool: &P,
+        exact_words: &Option<ExactWords>,
+    ) -> Vec<String> {
+        let words_list = self.build_words_list(rng, pool, exact_words.as_ref());
 
         log::debug!(
             "randomizing {} words from a pool of {} entries",
@@ -342,7 +554,7 @@ impl Randomizer for Settings {
             pool.len()
         );
 
-        let transforms_list = self.build_transforms_list();
+        let transforms_list = self.build_transforms_list(rng);
 
         log::debug!(
             "transforming words in order of [{}]",
@@ -352,33 +564,50 @@ impl Randomizer for Settings {
         words_list
             .iter()
             .zip(transforms_list.iter())
-            .map(|(word, &transform)| transform_word(word, transform))
+            .map(|(word, &transform)| {
+                // only random capitals give several outputs to pick from
+                let mut variants = transform_variants(&self.fold_word(word), transform);
+                if variants.len() > 1 {
+                    variants.swap_remove(rand_index(rng, variants.len()))
+                } else {
+                    variants.swap_remove(0)
+                }
+            })
             .collect()
     }
 
-    fn rand_separator(&self) -> String {
-        rand_chars(&self.separators, 1)
+    fn rand_separators<R: RngCore + CryptoRng>(&self, rng: &mut R, gaps: usize) -> Vec<String> {
+        match self.separator_mode {
+            SeparatorMode::Repeat => vec![rand_chars(rng, &self.separators, 1); gaps],
+            SeparatorMode::Independent => (0..gaps)
+                .map(|_| rand_chars(rng, &self.separators, 1))
+                .collect(),
+        }
     }
 
-    fn rand_prefix(&self) -> (String, String) {
+    fn rand_prefix<R: RngCore + CryptoRng>(&self, rng: &mut R) -> (String, String) {
         let (prefix_digits, _) = self.padding_digits;
         let (prefix_symbols, _) = self.padding_symbol_lengths;
         (
-            rand_chars(&self.padding_symbols, prefix_symbols as usize),
-            rand_digits(prefix_digits),
+            self.rand_symbols(rng, prefix_symbols as usize),
+            rand_digits(rng, prefix_digits),
         )
     }
 
-    fn rand_suffix(&self) -> (String, String) {
+    fn rand_suffix<R: RngCore + CryptoRng>(&self, rng: &mut R) -> (String, String) {
         let (_, suffix_digits) = self.padding_digits;
         let (_, suffix_symbols) = self.padding_symbol_lengths;
         (
-            rand_digits(suffix_digits),
-            rand_chars(&self.padding_symbols, suffix_symbols as usize),
+            rand_digits(rng, suffix_digits),
+            self.rand_symbols(rng, suffix_symbols as usize),
         )
     }
 
-    fn adjust_padding(&self, pass_length: usize) -> PaddingResult {
+    fn adjust_padding<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+        pass_length: usize,
+    ) -> PaddingResult {
         match self.padding_strategy {
             PaddingStrategy::Fixed => PaddingResult::Unchanged,
             PaddingStrategy::Adaptive(len) => match len.cmp(&pass_length) {
@@ -392,70 +621,143 @@ impl Randomizer for Settings {
                     PaddingResult::TrimTo(len)
                 }
                 cmp::Ordering::Greater => {
-                    let padded_symbols = rand_chars(&self.padding_symbols, len - pass_length);
+                    let padded_symbols = self.rand_symbols(rng, len - pass_length);
 
                     log::debug!(
                         "padded {} symbols to fit padding strategy",
-                        padded_symbols.len()
+                        padded_symbols.chars().count()
                     );
 
                     PaddingResult::Pad(padded_symbols)
                 }
             },
+            PaddingStrategy::Exact(len) if len > pass_length => {
+                let padded_symbols = self.rand_symbols(rng, len - pass_length);
+
+                log::debug!(
+                    "padded {} symbols to fit exact padding",
+                    padded_symbols.chars().count()
+                );
+
+                PaddingResult::Pad(padded_symbols)
+            }
+            // words never get trimmed, even when they couldn't fit
+            PaddingStrategy::Exact(_) => PaddingResult::Unchanged,
         }
     }
 
-    fn calc_entropy(&self, pool_size: usize) -> Entropy {
-        let (min_total_len, max_total_len) = match self.padding_strategy {
-            PaddingStrategy::Adaptive(len) => (len, len),
-            PaddingStrategy::Fixed => {
-                let prefix_digits = if self.padding_digits.0 > 0 {
-                    self.padding_digits.0 + 1
-                } else {
-                    self.padding_digits.0
-                };
+    fn substitute_chars(&self, passwd: &str) -> String {
+        if self.substitutions.is_empty() {
+            return passwd.to_string();
+        }
 
-                let suffix_digits = if self.padding_digits.1 > 0 {
-                    self.padding_digits.1 + 1
-                } else {
-                    self.padding_digits.1
-                };
+        log::debug!("substituting chars by {} rule(s)", self.substitutions.len());
 
-                let non_alpha_len = self.padding_symbol_lengths.0
-                    + self.padding_symbol_lengths.1
-                    + prefix_digits
-                    + suffix_digits
-                    + self.words_count
-                    - 1;
-
-                let count = self.words_count as usize;
-                let (min, max) = self.word_lengths;
-                (
-                    count * (min as usize) + (non_alpha_len as usize),
-                    count * (max as usize) + (non_alpha_len as usize),
-                )
-            }
+        substitute(passwd, &self.substitutions)
+    }
+
+    fn fits_length(&self, passwd: &str) -> bool {
+        let (min_length, max_length) = self.length_range;
+        let len = passwd.chars().count();
+        min_length.is_none_or(|min| len >= min) && max_length.is_none_or(|max| len <= max)
+    }
+
+    fn check_pool_with<P: WordsPool + ?Sized>(
+        &self,
+        pool: &P,
+        exact_words: &Option<ExactWords>,
+    ) -> Result<(), XkpasswdError> {
+        self.check_length_range(pool)?;
+
+        let (&PaddingStrategy::Exact(len), Some(exact_words)) =
+            (&self.padding_strategy, exact_words)
+        else {
+            return Ok(());
+        };
+
+        if self.unique_prefix > 0 {
+            return Err(XkpasswdError::Incompatible {
+                field: "unique_prefix",
+                with: "exact padding",
+            });
+        }
+
+        let exact = self.padding_symbols.is_empty();
+        let fits = self
+            .words_budget(len)
+            .is_some_and(|budget| !exact_words.totals(budget, exact).is_empty());
+
+        if fits {
+            return Ok(());
+        }
+
+        Err(XkpasswdError::ExactPaddingUnfit {
+            value: len,
+            words_count: self.words_count,
+            padded: !exact,
+        })
+    }
+
+    fn cache_key(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    fn calc_entropy_with<P: WordsPool + ?Sized>(
+        &self,
+        pool: &P,
+        exact_words: &Option<ExactWords>,
+    ) -> Entropy {
+        let (min_total_len, max_total_len) = match self.padding_strategy {
+            PaddingStrategy::Adaptive(len) | PaddingStrategy::Exact(len) => (len, len),
+            PaddingStrategy::Fixed => self.unpadded_lengths(),
         };
 
+        // regenerated passwords never go out of the range
+        let (min_length, max_length) = self.length_range;
+        let min_total_len = min_total_len.max(min_length.unwrap_or(0));
+        let max_total_len = max_total_len
+            .min(max_length.unwrap_or(usize::MAX))
+            .max(min_total_len);
+
         log::debug!(
             "entropy: blind length of {} ~ {}",
             min_total_len,
             max_total_len
         );
 
-        let single_word_transform = self.word_transforms
-            == FieldSize::from_flag(WordTransform::Lowercase)
-            || self.word_transforms == FieldSize::from_flag(WordTransform::Uppercase);
+        // leetspeak & reversed words stay lowercase
+        let lowercase_only = self.word_transforms
+            & !(WordTransform::Lowercase | WordTransform::Leetspeak | WordTransform::Reversed)
+            == 0;
+        let uppercase_only = self.word_transforms == FieldSize::from_flag(WordTransform::Uppercase);
+
+        // substitutions may bring in chars of any class
+        let replacements: String = self
+            .substitutions
+            .iter()
+            .map(|(_, to)| to.as_str())
+            .collect();
+        let single_word_transform = (lowercase_only
+            && !replacements.chars().any(char::is_uppercase))
+            || (uppercase_only && !replacements.chars().any(char::is_lowercase));
 
         let mut blind_pool_size = if single_word_transform { 26 } else { 52 };
 
-        if self.padding_digits != (0, 0) {
+        if self.padding_digits != (0, 0)
+            || self.word_transforms.has_flag(WordTransform::Leetspeak)
+            || replacements.chars().any(|c| c.is_ascii_digit())
+        {
             blind_pool_size += 10
         } else {
             blind_pool_size += 0
         };
 
-        if !self.separators.is_empty() || !self.padding_symbols.is_empty() {
+        if !self.separators.is_empty()
+            || !self.padding_symbols.is_empty()
+            || replacements.chars().any(|c| !c.is_alphanumeric())
+        {
             blind_pool_size += 32;
         }
 
@@ -465,45 +767,92 @@ impl Randomizer for Settings {
         let blind_min = (min_total_len as f64) * blind_pool;
         let blind_max = (max_total_len as f64) * blind_pool;
 
-        let seen_words_pool_size = pool_size * self.word_transforms.to_flags().len();
-        log::debug!("entropy: seen words pool size of {}", seen_words_pool_size);
+        log::debug!("entropy: seen words pool size of {}", pool.len());
+        let seen = entropy::seen_entropy(self, pool, exact_words.as_ref());
 
-        let seen_words_entropy = (self.words_count as f64) * (seen_words_pool_size as f64).log2();
+        let acceptance = entropy::length_acceptance(self, pool);
+        log::debug!("entropy: {} of passwords fit the length range", acceptance);
+        let seen = (seen + acceptance.log2()).max(0.0);
 
-        let seen_separator_entropy = if self.separators.is_empty() {
-            0.0
-        } else {
-            (self.separators.len() as f64).log2()
-        };
+        Entropy {
+            blind_min,
+            blind_max,
+            seen,
+            ..Entropy::default()
+        }
+        .with_attacker(Attacker::default())
+    }
+}
 
-        let seen_digits_entropy = if self.padding_digits == (0, 0) {
-            0.0
-        } else {
-            f64::from(self.padding_digits.0 + self.padding_digits.1) * 10f64.log2()
-        };
+impl Settings {
+    pub fn words_count(&self) -> u8 {
+        self.words_count
+    }
 
-        let seen_symbols_entropy = if self.padding_symbols.is_empty() {
-            0.0
-        } else {
-            (self.padding_symbols.len() as f64).log2()
-        };
+    pub fn word_length_bounds(&self) -> (u8, u8) {
+        self.word_lengths
+    }
 
-        let seen = (seen_words_entropy
-            + seen_separator_entropy
-            + seen_digits_entropy
-            + seen_symbols_entropy)
-            .round() as usize;
+    pub fn word_transforms(&self) -> FieldSize {
+        self.word_transforms
+    }
 
-        Entropy {
-            blind_min: blind_min.round() as usize,
-            blind_max: blind_max.round() as usize,
-            seen,
-            guess_time: GuessTime::for_entropy(seen),
-        }
+    pub fn ascii_fold(&self) -> bool {
+        self.ascii_fold
+    }
+
+    /// Length of the prefix no two words of a password share, 0 when words may share any
+    pub fn unique_prefix(&self) -> u8 {
+        self.unique_prefix
+    }
+
+    pub fn separators(&self) -> &str {
+        &self.separators
+    }
+
+    pub fn padding_digits(&self) -> (u8, u8) {
+        self.padding_digits
+    }
+
+    pub fn padding_symbols(&self) -> &str {
+        &self.padding_symbols
+    }
+
+    pub fn padding_symbol_lengths(&self) -> (u8, u8) {
+        self.padding_symbol_lengths
+    }
+
+    pub fn padding_strategy(&self) -> &PaddingStrategy {
+        &self.padding_strategy
+    }
+
+    pub fn separator_mode(&self) -> SeparatorMode {
+        self.separator_mode
+    }
+
+    pub fn symbol_mode(&self) -> SymbolMode {
+        self.symbol_mode
+    }
+
+    /// Rules replacing a char of generated passwords by a string, possibly empty
+    pub fn substitutions(&self) -> &[(char, String)] {
+        &self.substitutions
+    }
+
+    /// Bounds of the total length of passwords, others being regenerated
+    pub fn length_range(&self) -> (Option<usize>, Option<usize>) {
+        self.length_range
+    }
+
+    /// Seen entropy of the words alone for each count up to `words_count`,
+    /// cheaper than as many `calc_entropy` calls
+    pub(crate) fn words_entropies<P: WordsPool + ?Sized>(&self, pool: &P) -> Vec<f64> {
+        entropy::words_entropies(self, pool)
     }
 }
 
 impl Settings {
+    const MIN_WORDS_COUNT: u8 = 1;
     const MIN_WORD_LENGTH: u8 = 4;
     const MAX_WORD_LENGTH: u8 = 10;
     const DEFAULT_PADDING_LENGTH: u8 = 2;
@@ -513,48 +862,277 @@ impl Settings {
     const DEFAULT_WORDS_COUNT: u8 = 3;
     const DEFAULT_WORD_LENGTHS: (u8, u8) = (Self::MIN_WORD_LENGTH, Self::MAX_WORD_LENGTH);
     const DEFAULT_WORD_TRANSFORMS: FieldSize = 0b00000101; // WordTransform::Lowercase | WordTransform::Uppercase
+    const MAX_PREFIX_REJECTIONS: usize = 100;
 
-    const ALL_SINGLE_WORD_TRANSFORMS: [WordTransform; 4] = [
+    const ALL_SINGLE_WORD_TRANSFORMS: [WordTransform; 8] = [
         WordTransform::Lowercase,
         WordTransform::Titlecase,
         WordTransform::Uppercase,
         WordTransform::InversedTitlecase,
+        WordTransform::Leetspeak,
+        WordTransform::RandomCapital,
+        WordTransform::LastCapital,
+        WordTransform::Reversed,
     ];
 
-    fn build_words_list<'a>(&self, pool: &[&'a str]) -> Vec<&'a str> {
+    fn word_length_err(field: &'static str, value: u8) -> XkpasswdError {
+        XkpasswdError::WordLength {
+            field,
+            value,
+            min: Self::MIN_WORD_LENGTH,
+            max: Self::MAX_WORD_LENGTH,
+        }
+    }
+
+    fn rand_symbols<R: RngCore + CryptoRng>(&self, rng: &mut R, count: usize) -> String {
+        match self.symbol_mode {
+            SymbolMode::Repeat => rand_chars(rng, &self.padding_symbols, count),
+            SymbolMode::Independent => (0..count)
+                .map(|_| rand_chars(rng, &self.padding_symbols, 1))
+                .collect(),
+        }
+    }
+
+    fn check_length_range<P: WordsPool + ?Sized>(&self, pool: &P) -> Result<(), XkpasswdError> {
+        let (min_length, max_length) = self.length_range;
+        if (min_length, max_length) == (None, None) {
+            return Ok(());
+        }
+
+        let acceptance = entropy::length_acceptance(self, pool);
+        if acceptance >= MIN_LENGTH_ACCEPTANCE {
+            return Ok(());
+        }
+
+        if acceptance > 0.0 {
+            return Err(XkpasswdError::LengthRangeUnlikely {
+                min: min_length,
+                max: max_length,
+                acceptance,
+                min_acceptance: MIN_LENGTH_ACCEPTANCE,
+            });
+        }
+
+        let lengths = entropy::length_distribution(self, pool);
+        let shortest = lengths.iter().position(|&prob| prob > 0.0).unwrap_or(0);
+        let longest = lengths.iter().rposition(|&prob| prob > 0.0).unwrap_or(0);
+
+        Err(XkpasswdError::LengthRangeUnfit {
+            min: min_length,
+            max: max_length,
+            shortest,
+            longest,
+        })
+    }
+
+    // min & max lengths of a password before adaptive padding
+    fn unpadded_lengths(&self) -> (usize, usize) {
+        let prefix_digits = if self.padding_digits.0 > 0 {
+            self.padding_digits.0 + 1
+        } else {
+            self.padding_digits.0
+        };
+
+        let suffix_digits = if self.padding_digits.1 > 0 {
+            self.padding_digits.1 + 1
+        } else {
+            self.padding_digits.1
+        };
+
+        let non_alpha_len = self.padding_symbol_lengths.0
+            + self.padding_symbol_lengths.1
+            + prefix_digits
+            + suffix_digits
+            + self.words_count
+            - 1;
+
+        let count = self.words_count as usize;
+        let (min, max) = self.word_lengths;
+        (
+            count * (min as usize) + (non_alpha_len as usize),
+            count * (max as usize) + (non_alpha_len as usize),
+        )
+    }
+
+    // chars left to words once digits & separators are in, none if they already overflow
+    fn words_budget(&self, len: usize) -> Option<usize> {
+        let (prefix_digits, suffix_digits) = self.padding_digits;
+        let digits: Vec<usize> = [prefix_digits, suffix_digits]
+            .into_iter()
+            .filter(|&count| count > 0)
+            .map(|count| count.min(MAX_PADDING_DIGITS) as usize)
+            .collect();
+
+        let blocks = self.words_count as usize + digits.len();
+        let separators = if self.separators.is_empty() {
+            0
+        } else {
+            blocks - 1
+        };
+
+        len.checked_sub(digits.iter().sum::<usize>() + separators)
+    }
+
+    // length of a word whatever its transform, none if a transform changes it, e.g. 'ß' to "SS"
+    fn transformed_len(&self, word: &str) -> Option<usize> {
+        let word = self.fold_word(word);
+
+        // no char maps to nothing, so counts only match if each char maps to one
+        let len = word.chars().count();
+        if case::to_uppercase(&word).chars().count() != len
+            || case::to_lowercase(&word).chars().count() != len
+        {
+            return None;
+        }
+
+        Some(len)
+    }
+
+    // words are folded before being transformed, so that transforms see plain letters,
+    // e.g. leetspeak turning 'é' into '3'
+    fn fold_word(&self, word: &str) -> String {
+        if self.ascii_fold {
+            fold_ascii(word)
+        } else {
+            word.to_string()
+        }
+    }
+
+    fn build_words_list<'a, R: RngCore + CryptoRng, P: WordsPool + ?Sized>(
+        &self,
+        rng: &mut R,
+        pool: &'a P,
+        exact_words: Option<&ExactWords>,
+    ) -> Vec<&'a str> {
         if pool.is_empty() {
             return vec![];
         }
 
-        let mut rng = rand::thread_rng();
-        let word_indices = Uniform::from(0..pool.len());
+        if let (&PaddingStrategy::Exact(len), Some(exact_words)) =
+            (&self.padding_strategy, exact_words)
+        {
+            let sampled = self.words_budget(len).and_then(|budget| {
+                exact_words.sample(rng, pool, budget, self.padding_symbols.is_empty())
+            });
+
+            match sampled {
+                Some(words_list) => return words_list,
+                // rejected by `check_pool` before `Xkpasswd` generates anything,
+                // passwords then come out at their natural length
+                None => log::warn!("no words fit in exactly {} chars", len),
+            }
+        }
 
         // not enough words to distinguishably randomize
         if pool.len() < self.words_count as usize {
             return (0..self.words_count)
-                .map(|_| {
-                    let index: usize = word_indices.sample(&mut rng);
-                    pool[index]
-                })
+                .map(|_| pool.word(pool.draw(rng)))
                 .collect();
         }
 
+        if self.unique_prefix > 0 {
+            return self.build_unique_prefix_words_list(rng, pool);
+        }
+
         // enough words, ensure no duplicates
-        let mut index_marker: HashMap<usize, bool> = HashMap::new();
-        (0..self.words_count)
-            .map(|_| loop {
-                let index: usize = word_indices.sample(&mut rng);
-                let word = pool[index];
+        let mut picked_words: Vec<&str> = vec![];
+        for _ in 0..self.words_count {
+            let word = loop {
+                let word = pool.word(pool.draw(rng));
 
-                if let std::collections::hash_map::Entry::Vacant(e) = index_marker.entry(index) {
-                    e.insert(true);
+                if !picked_words.contains(&word) {
                     break word;
                 }
+            };
+            picked_words.push(word);
+        }
+
+        picked_words
+    }
+
+    // picks follow the pool chances among words neither already picked nor sharing a prefix
+    // with them, shared prefixes are allowed again only once all prefixes are taken
+    fn build_unique_prefix_words_list<'a, R: RngCore + CryptoRng, P: WordsPool + ?Sized>(
+        &self,
+        rng: &mut R,
+        pool: &'a P,
+    ) -> Vec<&'a str> {
+        // a few words at most, scanned faster than hashed
+        let mut picked_words: Vec<&str> = vec![];
+        let mut picked_prefixes: Vec<String> = vec![];
+
+        (0..self.words_count)
+            .map(|_| {
+                let is_free = |index: &usize, prefixes: &[String]| {
+                    let word = pool.word(*index);
+                    !picked_words.contains(&word) && !prefixes.contains(&self.word_prefix(word))
+                };
+
+                // rejection sampling is fast while most words are free
+                let sampled = (0..Self::MAX_PREFIX_REJECTIONS)
+                    .map(|_| pool.draw(rng))
+                    .find(|index| is_free(index, &picked_prefixes));
+
+                let index = match sampled {
+                    Some(index) => index,
+                    None => {
+                        let mut free: Vec<usize> = (0..pool.len())
+                            .filter(|index| is_free(index, &picked_prefixes))
+                            .collect();
+
+                        if free.is_empty() {
+                            free = (0..pool.len())
+                                .filter(|&index| !picked_words.contains(&pool.word(index)))
+                                .collect();
+                        }
+
+                        match WeightedIndex::new(free.iter().map(|&index| pool.prob(index))) {
+                            Ok(weights) => free[weights.sample(rng)],
+                            Err(_) => free[rand_index(rng, free.len())],
+                        }
+                    }
+                };
+
+                picked_words.push(pool.word(index));
+                picked_prefixes.push(self.word_prefix(pool.word(index)));
+                pool.word(index)
             })
             .collect()
     }
 
-    fn build_transforms_list(&self) -> Vec<WordTransform> {
+    // what two words of a password must not share, compared as they would be output
+    fn word_prefix(&self, word: &str) -> String {
+        let word = case::to_lowercase(&self.fold_word(word));
+
+        word.chars().take(self.unique_prefix as usize).collect()
+    }
+
+    // transforms a word at a given position is equally likely to get
+    fn transform_choices(&self, idx: usize) -> Vec<WordTransform> {
+        let alternating = if self
+            .word_transforms
+            .has_flag(WordTransform::AltercaseLowerFirst)
+        {
+            Some((WordTransform::Lowercase, WordTransform::Uppercase))
+        } else if self
+            .word_transforms
+            .has_flag(WordTransform::AltercaseUpperFirst)
+        {
+            Some((WordTransform::Uppercase, WordTransform::Lowercase))
+        } else {
+            None
+        };
+
+        match alternating {
+            Some((even, odd)) => vec![[even, odd][idx % 2]],
+            None => Self::ALL_SINGLE_WORD_TRANSFORMS
+                .into_iter()
+                .filter(|&transform| self.word_transforms & transform)
+                .collect(),
+        }
+    }
+
+    fn build_transforms_list<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Vec<WordTransform> {
         if self
             .word_transforms
             .has_flag(WordTransform::AltercaseLowerFirst)
@@ -590,27 +1168,26 @@ impl Settings {
             .filter(|&&transform| self.word_transforms & transform)
             .collect();
 
-        let mut rng = rand::thread_rng();
-        let transform_indices = Uniform::from(0..whitelisted_transforms.len());
+        let transform_indices = Uniform::from(0..whitelisted_transforms.len() as u64);
 
         (0..self.words_count)
             .map(|_| {
-                let index: usize = transform_indices.sample(&mut rng);
+                let index = transform_indices.sample(rng) as usize;
                 *whitelisted_transforms[index]
             })
             .collect()
     }
 }
 
-fn rand_digits(count: u8) -> String {
+fn rand_digits<R: RngCore + CryptoRng>(rng: &mut R, count: u8) -> String {
     if count == 0 {
         return "".to_string();
     }
 
-    let affordable_count = 20u32.min(count as u32);
+    let affordable_count = count.min(MAX_PADDING_DIGITS) as u32;
 
     let lower_bound = 10u64.pow(affordable_count - 1);
-    let upper_bound = if affordable_count < 20 {
+    let upper_bound = if affordable_count < MAX_PADDING_DIGITS as u32 {
         10u64.pow(affordable_count)
     } else {
         log::debug!(
@@ -621,31 +1198,152 @@ fn rand_digits(count: u8) -> String {
         u64::MAX
     };
 
-    let mut rng = rand::thread_rng();
-    let padding_digits: u64 = Uniform::from(lower_bound..upper_bound).sample(&mut rng);
+    let padding_digits: u64 = Uniform::from(lower_bound..upper_bound).sample(rng);
     padding_digits.to_string()
 }
 
-fn rand_chars(pool: &str, count: usize) -> String {
+// indices are drawn as `u64`, `usize` ranges being sampled differently on 32 & 64-bit targets
+fn rand_index<R: Rng + ?Sized>(rng: &mut R, len: usize) -> usize {
+    rng.gen_range(0..len as u64) as usize
+}
+
+fn rand_chars<R: RngCore + CryptoRng>(rng: &mut R, pool: &str, count: usize) -> String {
     if pool.is_empty() {
         return "".to_string();
     }
 
-    let mut rng = rand::thread_rng();
-    let idx = rng.gen_range(0..pool.len());
-    pool.chars()
-        .nth(idx)
-        .unwrap()
-        .to_string()
-        .repeat(count as _)
+    let chars: Vec<char> = pool.chars().collect();
+    let idx = rand_index(rng, chars.len());
+    chars[idx].to_string().repeat(count)
 }
 
 fn transform_word(word: &str, transform: WordTransform) -> String {
+    let mut chars = word.chars();
+    let first = match chars.next() {
+        Some(first) => first,
+        None => return "".to_string(),
+    };
+
     match transform {
-        WordTransform::Titlecase => word[..1].to_uppercase() + &word[1..],
-        WordTransform::Uppercase => word.to_uppercase(),
-        WordTransform::InversedTitlecase => word[..1].to_lowercase() + &word[1..].to_uppercase(),
+        WordTransform::Titlecase => case::char_to_uppercase(first) + chars.as_str(),
+        WordTransform::Uppercase => case::to_uppercase(word),
+        WordTransform::InversedTitlecase => {
+            case::char_to_lowercase(first) + &case::to_uppercase(chars.as_str())
+        }
+        WordTransform::Leetspeak => case::to_lowercase(word)
+            .chars()
+            .map(|char| match char {
+                'a' => '4',
+                'e' => '3',
+                'i' => '1',
+                'o' => '0',
+                's' => '5',
+                't' => '7',
+                _ => char,
+            })
+            .collect(),
+        WordTransform::LastCapital => {
+            let word = case::to_lowercase(word);
+            capitalize_at(&word, word.chars().count() - 1)
+        }
+        WordTransform::Reversed => case::to_lowercase(word).chars().rev().collect(),
         // lowercase by default
-        _ => word.to_lowercase(),
+        _ => case::to_lowercase(word),
+    }
+}
+
+// all outputs a transform may give, equally likely
+fn transform_variants(word: &str, transform: WordTransform) -> Vec<String> {
+    match transform {
+        WordTransform::RandomCapital if !word.is_empty() => {
+            let word = case::to_lowercase(word);
+            (0..word.chars().count())
+                .map(|idx| capitalize_at(&word, idx))
+                .collect()
+        }
+        _ => vec![transform_word(word, transform)],
     }
 }
+
+fn capitalize_at(word: &str, idx: usize) -> String {
+    word.chars()
+        .enumerate()
+        .map(|(char_idx, char)| {
+            if char_idx == idx {
+                case::char_to_uppercase(char)
+            } else {
+                char.to_string()
+            }
+        })
+        .collect()
+}
+
+// every char replaced by its rule in a single pass, substituted chars are never substituted again
+fn substitute(text: &str, rules: &[(char, String)]) -> String {
+    let mut substituted = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match rules.iter().find(|(from, _)| *from == char) {
+            Some((_, to)) => substituted.push_str(to),
+            None => substituted.push(char),
+        }
+    }
+
+    substituted
+}
+
+// a char replaced by none or several
+fn changes_length((_, to): &(char, String)) -> bool {
+    to.chars().count() != 1
+}
+
+// exact padding counts chars before substitutions, whichever of both is set last
+fn check_exact_substitutions(rules: &[(char, String)]) -> Result<(), XkpasswdError> {
+    match rules.iter().find(|rule| changes_length(rule)) {
+        Some((from, to)) => Err(XkpasswdError::Substitution {
+            rule: format!("{}={}", from, to),
+            reason: "changing lengths can't be combined with exact padding",
+        }),
+        None => Ok(()),
+    }
+}
+
+// replaces accented & special Latin letters with their closest ASCII spelling, keeping the case
+fn fold_ascii(word: &str) -> String {
+    let mut folded = String::with_capacity(word.len());
+
+    for char in word.chars() {
+        let replacement = match char {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => "a",
+            'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' => "A",
+            'ç' => "c",
+            'Ç' => "C",
+            'è' | 'é' | 'ê' | 'ë' => "e",
+            'È' | 'É' | 'Ê' | 'Ë' => "E",
+            'ì' | 'í' | 'î' | 'ï' => "i",
+            'Ì' | 'Í' | 'Î' | 'Ï' => "I",
+            'ñ' => "n",
+            'Ñ' => "N",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => "o",
+            'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' => "O",
+            'ù' | 'ú' | 'û' | 'ü' => "u",
+            'Ù' | 'Ú' | 'Û' | 'Ü' => "U",
+            'ý' | 'ÿ' => "y",
+            'Ý' | 'Ÿ' => "Y",
+            'æ' => "ae",
+            'Æ' => "AE",
+            'œ' => "oe",
+            'Œ' => "OE",
+            'ß' => "ss",
+            'ẞ' => "SS",
+            _ => {
+                folded.push(char);
+                continue;
+            }
+        };
+
+        folded.push_str(replacement);
+    }
+
+    folded
+}
@@ -0,0 +1,136 @@
+use super::Settings;
+use crate::bit_flags::{BitFlags, FieldSize, WordTransform};
+use crate::prelude::{
+    parse_substitution, Builder, PaddingStrategy, SeparatorMode, SymbolMode, XkpasswdError,
+};
+use serde::{Deserialize, Serialize};
+
+// flat representation of Settings, using the same keys as xkpasswd.toml
+// so that a serialized policy can be dropped into a config file as is
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SettingsSchema {
+    words_count: u8,
+    word_min: u8,
+    word_max: u8,
+    transforms: Vec<WordTransform>,
+    ascii_fold: bool,
+    unique_prefix: u8,
+    separators: String,
+    separator_mode: SeparatorMode,
+    digits_before: u8,
+    digits_after: u8,
+    symbols: String,
+    symbols_before: u8,
+    symbols_after: u8,
+    symbol_mode: SymbolMode,
+    substitutions: Vec<String>,
+    padding: Padding,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    adaptive_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_length: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Padding {
+    Fixed,
+    Adaptive,
+    Exact,
+}
+
+impl Default for SettingsSchema {
+    fn default() -> Self {
+        Settings::default().into()
+    }
+}
+
+impl From<Settings> for SettingsSchema {
+    fn from(settings: Settings) -> Self {
+        let (word_min, word_max) = settings.word_lengths;
+        let (digits_before, digits_after) = settings.padding_digits;
+        let (symbols_before, symbols_after) = settings.padding_symbol_lengths;
+        let (min_length, max_length) = settings.length_range;
+
+        let (padding, adaptive_length) = match settings.padding_strategy {
+            PaddingStrategy::Fixed => (Padding::Fixed, None),
+            PaddingStrategy::Adaptive(len) => (Padding::Adaptive, Some(len)),
+            PaddingStrategy::Exact(len) => (Padding::Exact, Some(len)),
+        };
+
+        let substitutions = settings
+            .substitutions
+            .iter()
+            .map(|(from, to)| format!("{}={}", from, to))
+            .collect();
+
+        SettingsSchema {
+            words_count: settings.words_count,
+            word_min,
+            word_max,
+            transforms: settings.word_transforms.to_flags(),
+            ascii_fold: settings.ascii_fold,
+            unique_prefix: settings.unique_prefix,
+            separators: settings.separators,
+            separator_mode: settings.separator_mode,
+            digits_before,
+            digits_after,
+            symbols: settings.padding_symbols,
+            symbols_before,
+            symbols_after,
+            symbol_mode: settings.symbol_mode,
+            substitutions,
+            padding,
+            adaptive_length,
+            min_length,
+            max_length,
+        }
+    }
+}
+
+impl TryFrom<SettingsSchema> for Settings {
+    type Error = XkpasswdError;
+
+    fn try_from(schema: SettingsSchema) -> Result<Self, Self::Error> {
+        let transforms = schema
+            .transforms
+            .iter()
+            .fold(0, |acc: FieldSize, transform| acc | *transform);
+
+        let padding_strategy = match (schema.padding, schema.adaptive_length) {
+            (Padding::Fixed, _) => PaddingStrategy::Fixed,
+            (Padding::Adaptive, Some(len)) => PaddingStrategy::Adaptive(len),
+            (Padding::Exact, Some(len)) => PaddingStrategy::Exact(len),
+            (Padding::Adaptive | Padding::Exact, None) => {
+                return Err(XkpasswdError::AdaptiveLengthRequired)
+            }
+        };
+
+        let substitutions = schema
+            .substitutions
+            .iter()
+            .map(|rule| parse_substitution(rule))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let settings = Settings::default()
+            .with_words_count(schema.words_count)?
+            .with_word_lengths(Some(schema.word_min), Some(schema.word_max))?
+            .with_word_transforms(transforms)?
+            .with_ascii_fold(schema.ascii_fold)
+            .with_unique_prefix(schema.unique_prefix)
+            .with_separators(&schema.separators)
+            .with_separator_mode(schema.separator_mode)
+            .with_padding_digits(Some(schema.digits_before), Some(schema.digits_after))
+            .with_padding_symbols(&schema.symbols)
+            .with_padding_symbol_lengths(Some(schema.symbols_before), Some(schema.symbols_after))
+            .with_symbol_mode(schema.symbol_mode)
+            .with_substitutions(&substitutions)?
+            .with_padding_strategy(padding_strategy)?
+            .with_length_range(schema.min_length, schema.max_length)?;
+
+        Ok(settings)
+    }
+}
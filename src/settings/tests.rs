@@ -1,5 +1,7 @@
 use super::*;
-use std::collections::HashSet;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::collections::{HashMap, HashSet};
 
 #[test]
 fn test_default_settings() {
@@ -32,11 +34,11 @@ fn test_default_settings() {
 
 #[test]
 fn test_with_words_count() {
-    let _err: Result<Settings, String> =
-        Err("only positive integer is allowed for words count".to_string());
-
     // invalid value
-    assert!(matches!(Settings::default().with_words_count(0), _err));
+    assert_eq!(
+        Err(XkpasswdError::WordsCount { value: 0, min: 1 }),
+        Settings::default().with_words_count(0)
+    );
 
     let settings = Settings::default().with_words_count(1).unwrap();
     // only words_count updated
@@ -73,27 +75,33 @@ fn test_with_words_count() {
 
 #[test]
 fn test_with_word_lengths() {
-    let _err: Result<Settings, String> = Err(MIN_WORD_LENGTH_ERR.to_string());
-
     // invalid lengths
-    assert!(matches!(
+    assert_eq!(
+        Err(XkpasswdError::WordLength {
+            field: "word_min",
+            value: Settings::MIN_WORD_LENGTH - 1,
+            min: Settings::MIN_WORD_LENGTH,
+            max: Settings::MAX_WORD_LENGTH,
+        }),
         Settings::default().with_word_lengths(
             Some(Settings::MIN_WORD_LENGTH - 1),
             Some(Settings::MAX_WORD_LENGTH + 1)
-        ),
-        _err
-    ));
-
-    let _err: Result<Settings, String> = Err(MAX_WORD_LENGTH_ERR.to_string());
+        )
+    );
 
     // max word length has lower priority
-    assert!(matches!(
+    assert_eq!(
+        Err(XkpasswdError::WordLength {
+            field: "word_max",
+            value: Settings::MAX_WORD_LENGTH + 1,
+            min: Settings::MIN_WORD_LENGTH,
+            max: Settings::MAX_WORD_LENGTH,
+        }),
         Settings::default().with_word_lengths(
             Some(Settings::MIN_WORD_LENGTH),
             Some(Settings::MAX_WORD_LENGTH + 1)
-        ),
-        _err
-    ));
+        )
+    );
 
     let settings = Settings::default()
         .with_word_lengths(Some(4), Some(6))
@@ -172,6 +180,75 @@ fn test_with_separators() {
     assert_eq!("".to_string(), other_settings.separators);
 }
 
+#[test]
+fn test_with_substitutions() {
+    let rules = [(' ', "_".to_string()), ('|', "".to_string())];
+    let settings = Settings::default().with_substitutions(&rules).unwrap();
+    assert_eq!(rules.to_vec(), settings.substitutions);
+
+    // other fields remain unchanged
+    assert_eq!(
+        Settings::default(),
+        settings.with_substitutions(&[]).unwrap()
+    );
+
+    let err = settings
+        .with_substitutions(&[('|', "!".to_string()), ('|', "?".to_string())])
+        .err()
+        .unwrap();
+    assert_eq!("substitutions", err.field());
+    assert_eq!(
+        "invalid substitution '|': char substituted more than once",
+        err.to_string()
+    );
+
+    // exact padding counts chars before substitutions, which mustn't change them
+    let settings = Settings::default()
+        .with_padding_strategy(PaddingStrategy::Exact(24))
+        .unwrap();
+    assert!(settings
+        .with_substitutions(&[('|', "!".to_string())])
+        .is_ok());
+
+    let err = settings.with_substitutions(&rules).err().unwrap();
+    assert_eq!("substitutions", err.field());
+    assert_eq!(
+        "invalid substitution '|=': changing lengths can't be combined with exact padding",
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_with_length_range() {
+    let settings = Settings::default()
+        .with_length_range(Some(12), Some(20))
+        .unwrap();
+    assert_eq!((Some(12), Some(20)), settings.length_range);
+
+    // unset bounds remain unchanged
+    let settings = settings.with_length_range(None, Some(24)).unwrap();
+    assert_eq!((Some(12), Some(24)), settings.length_range);
+    assert_eq!(settings, settings.with_length_range(None, None).unwrap());
+
+    let table = [
+        (
+            Some(20),
+            Some(12),
+            "max length must be 20 or higher, got 12",
+        ),
+        (Some(30), None, "max length must be 30 or higher, got 24"),
+        (None, Some(8), "max length must be 12 or higher, got 8"),
+    ];
+
+    for (min_length, max_length, message) in table {
+        let err = settings
+            .with_length_range(min_length, max_length)
+            .err()
+            .unwrap();
+        assert_eq!(message, err.to_string());
+    }
+}
+
 #[test]
 fn test_with_padding_digits() {
     let settings = Settings::default().with_padding_digits(Some(1), Some(3));
@@ -306,13 +383,26 @@ fn test_with_padding_symbol_lengths() {
 
 #[test]
 fn test_with_padding_strategy() {
-    let _err: Result<Settings, String> = Err("invalid adaptive padding number".to_string());
-
     // invalid adaptive padding
-    assert!(matches!(
-        Settings::default().with_padding_strategy(PaddingStrategy::Adaptive(0)),
-        _err
-    ));
+    assert_eq!(
+        Err(XkpasswdError::AdaptiveLength { value: 0, min: 1 }),
+        Settings::default().with_padding_strategy(PaddingStrategy::Adaptive(0))
+    );
+
+    // exact padding counts chars before substitutions, which mustn't change them
+    let substituted = Settings::default()
+        .with_substitutions(&[('ß', "ss".to_string())])
+        .unwrap();
+    assert_eq!(
+        Err(XkpasswdError::Substitution {
+            rule: "ß=ss".to_string(),
+            reason: "changing lengths can't be combined with exact padding",
+        }),
+        substituted.with_padding_strategy(PaddingStrategy::Exact(24))
+    );
+    assert!(substituted
+        .with_padding_strategy(PaddingStrategy::Adaptive(24))
+        .is_ok());
 
     let settings = Settings::default()
         .with_padding_symbol_lengths(Some(2), Some(3))
@@ -365,18 +455,30 @@ fn test_with_padding_strategy() {
         other_settings.padding_strategy,
         PaddingStrategy::Fixed
     ));
+
+    assert_eq!(
+        Err(XkpasswdError::ExactLength { value: 0, min: 1 }),
+        Settings::default().with_padding_strategy(PaddingStrategy::Exact(0))
+    );
+
+    let other_settings = Settings::default()
+        .with_padding_symbol_lengths(Some(2), Some(3))
+        .with_padding_strategy(PaddingStrategy::Exact(24))
+        .unwrap();
+    assert_eq!(PaddingStrategy::Exact(24), other_settings.padding_strategy);
+    assert_eq!((0, 0), other_settings.padding_symbol_lengths);
 }
 
 #[test]
 fn test_with_word_transforms_single() {
     // invalid transform
-    let table = [0b00010000, 0b00100000];
+    let table = [0b00000000, 0b100_00000000];
 
     for transform in table {
-        match Settings::default().with_word_transforms(transform) {
-            Ok(_) => panic!("unexpected result"),
-            Err(msg) => assert_eq!("invalid transform", msg),
-        }
+        assert_eq!(
+            Err(XkpasswdError::WordTransforms { value: transform }),
+            Settings::default().with_word_transforms(transform)
+        );
     }
 
     let settings = Settings::default()
@@ -459,6 +561,7 @@ fn test_get_word_lengths() {
 
 #[test]
 fn test_rand_words() {
+    let mut rng = rand::thread_rng();
     let settings = Settings::default()
         .with_words_count(3)
         .unwrap()
@@ -466,14 +569,16 @@ fn test_rand_words() {
         .unwrap();
 
     // empty pool
-    assert!(settings.rand_words(&vec![] as &Vec<&str>).is_empty());
+    assert!(settings
+        .rand_words(&mut rng, &vec![] as &Vec<&str>)
+        .is_empty());
 
     // not enough pool
-    let words = settings.rand_words(&["foo", "bar"]);
+    let words = settings.rand_words(&mut rng, &["foo", "bar"]);
     assert_eq!(3, words.len());
 
     // enough pool
-    let words = settings.rand_words(&["foo", "bar", "barz"]);
+    let words = settings.rand_words(&mut rng, &["foo", "bar", "barz"]);
     assert_eq!(3, words.len());
     assert_eq!(
         HashSet::from([&"FOO".to_string(), &"BAR".to_string(), &"BARZ".to_string()]),
@@ -483,6 +588,7 @@ fn test_rand_words() {
 
 #[test]
 fn test_rand_prefix() {
+    let mut rng = rand::thread_rng();
     let empty_cases = [
         ((0, 0), (0, 0)),
         ((0, 1), (0, 0)),
@@ -494,7 +600,7 @@ fn test_rand_prefix() {
         let settings = Settings::default()
             .with_padding_digits(Some(prefix_digits), Some(suffix_digits))
             .with_padding_symbol_lengths(Some(prefix_symbols), Some(suffix_symbols));
-        let (symbols, digits) = settings.rand_prefix();
+        let (symbols, digits) = settings.rand_prefix(&mut rng);
         assert_eq!("", symbols);
         assert_eq!("", digits);
     }
@@ -505,7 +611,7 @@ fn test_rand_prefix() {
                 .with_padding_digits(Some(prefix_digits as u8), Some(2))
                 .with_padding_symbols("#")
                 .with_padding_symbol_lengths(Some(prefix_symbols as u8), Some(3));
-            let (symbols, digits) = settings.rand_prefix();
+            let (symbols, digits) = settings.rand_prefix(&mut rng);
 
             // total length of prefix
             assert_eq!(prefix_symbols, symbols.len());
@@ -522,6 +628,7 @@ fn test_rand_prefix() {
 
 #[test]
 fn test_rand_suffix() {
+    let mut rng = rand::thread_rng();
     let empty_cases = [
         ((0, 0), (0, 0)),
         ((1, 0), (0, 0)),
@@ -533,7 +640,7 @@ fn test_rand_suffix() {
         let settings = Settings::default()
             .with_padding_digits(Some(prefix_digits), Some(suffix_digits))
             .with_padding_symbol_lengths(Some(prefix_symbols), Some(suffix_symbols));
-        let (digits, symbols) = settings.rand_suffix();
+        let (digits, symbols) = settings.rand_suffix(&mut rng);
         assert_eq!("", digits);
         assert_eq!("", symbols);
     }
@@ -544,7 +651,7 @@ fn test_rand_suffix() {
                 .with_padding_digits(Some(2), Some(suffix_digits as u8))
                 .with_padding_symbols("~")
                 .with_padding_symbol_lengths(Some(3), Some(suffix_symbols as u8));
-            let (digits, symbols) = settings.rand_suffix();
+            let (digits, symbols) = settings.rand_suffix(&mut rng);
 
             // total length of suffix
             assert_eq!(suffix_digits, digits.len());
@@ -561,6 +668,7 @@ fn test_rand_suffix() {
 
 #[test]
 fn test_adjust_padding() {
+    let mut rng = rand::thread_rng();
     let pass_length = 12;
 
     // fixed padding
@@ -568,7 +676,7 @@ fn test_adjust_padding() {
         .with_padding_strategy(PaddingStrategy::Fixed)
         .unwrap();
     assert!(matches!(
-        settings.adjust_padding(pass_length),
+        settings.adjust_padding(&mut rng, pass_length),
         PaddingResult::Unchanged
     ));
 
@@ -577,187 +685,1688 @@ fn test_adjust_padding() {
         .with_padding_symbols("@")
         .with_padding_strategy(PaddingStrategy::Adaptive(15))
         .unwrap();
-    match settings.adjust_padding(pass_length) {
+    match settings.adjust_padding(&mut rng, pass_length) {
         PaddingResult::Pad(padded_symbols) => assert_eq!("@@@", padded_symbols),
         _ => panic!("invalid padding result"),
     }
 
+    // adaptive padding: multi-byte symbols
+    let settings = settings.with_padding_symbols("⟫");
+    match settings.adjust_padding(&mut rng, pass_length) {
+        PaddingResult::Pad(padded_symbols) => assert_eq!("⟫⟫⟫", padded_symbols),
+        _ => panic!("invalid padding result"),
+    }
+
     // adaptive padding: cut length
     let settings = Settings::default()
         .with_padding_strategy(PaddingStrategy::Adaptive(10))
         .unwrap();
     assert!(matches!(
-        settings.adjust_padding(pass_length),
+        settings.adjust_padding(&mut rng, pass_length),
         PaddingResult::TrimTo(10)
     ));
+
+    // exact padding: add symbols, yet never trim
+    let settings = Settings::default()
+        .with_padding_symbols("@")
+        .with_padding_strategy(PaddingStrategy::Exact(15))
+        .unwrap();
+    match settings.adjust_padding(&mut rng, pass_length) {
+        PaddingResult::Pad(padded_symbols) => assert_eq!("@@@", padded_symbols),
+        _ => panic!("invalid padding result"),
+    }
+
+    let settings = settings
+        .with_padding_strategy(PaddingStrategy::Exact(10))
+        .unwrap();
+    assert!(matches!(
+        settings.adjust_padding(&mut rng, pass_length),
+        PaddingResult::Unchanged
+    ));
+}
+
+// distinct lowercase words, with lengths spread evenly between the bounds
+fn words_pool(size: usize, (min, max): (u8, u8)) -> Vec<String> {
+    let lens = (max - min + 1) as usize;
+
+    (0..size)
+        .map(|idx| {
+            let mut rest = idx;
+            (0..min as usize + idx % lens)
+                .map(|_| {
+                    let letter = (b'a' + (rest % 26) as u8) as char;
+                    rest /= 26;
+                    letter
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn as_strs(words: &[String]) -> Vec<&str> {
+    words.iter().map(String::as_str).collect()
+}
+
+fn assert_bits(expected: f64, actual: f64) {
+    assert!(
+        (expected - actual).abs() < 1e-9,
+        "expected {} bits, got {}",
+        expected,
+        actual
+    );
 }
 
 #[test]
 fn test_calc_entropy() {
     let table = [
-        ((Preset::AppleID, 4351), (164, 203, 57), (1_000_001, 0, 0)),
-        ((Preset::WindowsNtlmV1, 1380), (92, 92, 31), (0, 0, 24)),
+        (
+            (Preset::AppleID, 4351),
+            ("163.86", "203.19", "58.24"),
+            "1.08e7 years",
+        ),
+        (
+            (Preset::WindowsNtlmV1, 1380),
+            ("91.76", "91.76", "30.90"),
+            "23 days",
+        ),
         (
             (Preset::SecurityQuestions, 6631),
-            (176, 316, 78),
-            (1_000_000_001, 0, 0),
+            ("175.74", "316.33", "77.75"),
+            "8.07e12 years",
+        ),
+        (
+            (Preset::Web16, 1113),
+            ("102.28", "102.28", "43.93"),
+            "530 years 6 months 20 days",
+        ),
+        (
+            (Preset::Web32, 2493),
+            ("176.97", "203.19", "68.69"),
+            "1.51e10 years",
+        ),
+        (
+            (Preset::Wifi, 6631),
+            ("412.94", "412.94", "115.31"),
+            "1.63e24 years",
+        ),
+        (
+            (Preset::Xkcd, 6631),
+            ("121.45", "223.73", "54.78"),
+            "980023 years 5 months",
         ),
-        ((Preset::Web16, 1113), (102, 102, 40), (34, 10, 15)),
-        ((Preset::Web32, 2493), (177, 203, 65), (1_000_000_001, 0, 0)),
-        ((Preset::Wifi, 6631), (413, 413, 116), (1_000_000_001, 0, 0)),
-        ((Preset::Xkcd, 6631), (121, 224, 55), (1_000_001, 0, 0)),
     ];
 
-    for ((preset, pool_size), (blind_min, blind_max, seen), (years, months, days)) in table {
-        let guess_time = GuessTime {
-            years,
-            months,
-            days,
-        };
-        let expected = Entropy {
-            blind_max,
-            blind_min,
-            seen,
+    for ((preset, pool_size), (blind_min, blind_max, seen), guess_time) in table {
+        let settings = Settings::from_preset(preset);
+        let pool = words_pool(pool_size, settings.word_lengths);
+        let entropy = settings.calc_entropy(&as_strs(&pool));
+
+        assert_eq!(
+            (blind_min, blind_max, seen),
+            (
+                format!("{:.2}", entropy.blind_min).as_str(),
+                format!("{:.2}", entropy.blind_max).as_str(),
+                format!("{:.2}", entropy.seen).as_str(),
+            ),
+            "checking {:?}",
+            preset
+        );
+        assert_eq!(
             guess_time,
-        };
-        let entropy = Settings::from_preset(preset).calc_entropy(pool_size);
-        assert_eq!(expected, entropy);
+            entropy.guess_time.to_string(),
+            "checking {:?}",
+            preset
+        );
     }
 }
 
 #[test]
-fn test_build_words_list() {
-    let settings = Settings::default().with_words_count(3).unwrap();
+fn test_rand_separators() {
+    let mut rng = ChaCha20Rng::seed_from_u64(42);
+    let settings = Settings::default().with_separators("!@#$%^&*");
+
+    let separators = settings.rand_separators(&mut rng, 16);
+    assert_eq!(16, separators.len());
+    assert_eq!(1, separators.iter().collect::<HashSet<&String>>().len());
+
+    let settings = settings.with_separator_mode(SeparatorMode::Independent);
+    let separators = settings.rand_separators(&mut rng, 16);
+    assert_eq!(16, separators.len());
+    assert!(separators
+        .iter()
+        .all(|separator| separator.len() == 1 && "!@#$%^&*".contains(separator.as_str())));
+    assert!(separators.iter().collect::<HashSet<&String>>().len() > 1);
+
+    assert!(settings.rand_separators(&mut rng, 0).is_empty());
+    assert_eq!(
+        vec!["", ""],
+        settings.with_separators("").rand_separators(&mut rng, 2)
+    );
+}
 
-    // empty pool
-    assert!(settings.build_words_list(&vec![] as &Vec<&str>).is_empty());
+#[test]
+fn test_rand_symbols() {
+    let mut rng = ChaCha20Rng::seed_from_u64(42);
+    let settings = Settings::default().with_padding_symbols("!@#$%^&*");
 
-    // pool size smaller than words count
-    let pool = &["foo", "bar"];
+    let symbols = settings.rand_symbols(&mut rng, 16);
+    assert_eq!(16, symbols.len());
+    assert_eq!(1, symbols.chars().collect::<HashSet<char>>().len());
 
-    for _ in 0..10 {
-        let words = settings.build_words_list(pool);
-        assert_eq!(3, words.len());
+    let settings = settings.with_symbol_mode(SymbolMode::Independent);
+    let symbols = settings.rand_symbols(&mut rng, 16);
+    assert_eq!(16, symbols.len());
+    assert!(symbols.chars().all(|char| "!@#$%^&*".contains(char)));
+    assert!(symbols.chars().collect::<HashSet<char>>().len() > 1);
 
-        let unique_words: HashSet<String> = words.iter().map(|word| word.to_lowercase()).collect();
-        assert!(unique_words.len() < 3);
-    }
+    assert_eq!("", settings.rand_symbols(&mut rng, 0));
+    assert_eq!(
+        "",
+        settings.with_padding_symbols("").rand_symbols(&mut rng, 3)
+    );
+}
 
-    // enough pool
-    let pool = &["foo", "bar", "fooz", "barz"];
+// entropy of 2 transforms for each of the words picked without duplicates from the pool
+fn words_bits(pool_size: usize, count: usize) -> f64 {
+    (0..count)
+        .map(|idx| ((pool_size - idx) as f64).log2() + 1.0)
+        .sum()
+}
 
-    for _ in 0..10 {
-        let words = settings.build_words_list(pool);
-        assert_eq!(3, words.len());
+#[test]
+fn test_calc_entropy_separator_mode() {
+    let pool = words_pool(1024, (5, 5));
+    let pool = as_strs(&pool);
+    let settings = Settings::default()
+        .with_separators("0123456789abcdef")
+        .with_padding_symbols("");
 
-        let unique_words: HashSet<String> = words.iter().map(|word| word.to_lowercase()).collect();
-        assert_eq!(3, unique_words.len());
+    // 4 bits per separator draw, digits never start with 0
+    let table = [
+        ((0, 0), SeparatorMode::Repeat, 4.0),
+        ((0, 0), SeparatorMode::Independent, 2.0 * 4.0),
+        ((2, 0), SeparatorMode::Independent, 3.0 * 4.0 + 90f64.log2()),
+        (
+            (1, 1),
+            SeparatorMode::Independent,
+            4.0 * 4.0 + 2.0 * 9f64.log2(),
+        ),
+    ];
+
+    for ((prefix, suffix), mode, bits) in table {
+        let entropy = settings
+            .with_padding_digits(Some(prefix), Some(suffix))
+            .with_separator_mode(mode)
+            .calc_entropy(&pool);
+        assert_bits(words_bits(1024, 3) + bits, entropy.seen);
     }
+
+    let settings = settings.with_padding_digits(Some(0), Some(0));
+
+    // multi-byte separators count as single chars
+    let entropy = settings.with_separators("⟪⟫é€").calc_entropy(&pool);
+    assert_bits(words_bits(1024, 3) + 2.0, entropy.seen);
+
+    // repeated separators are more likely to be picked
+    let entropy = settings.with_separators("--.").calc_entropy(&pool);
+    assert_bits(words_bits(1024, 3) + 3f64.log2() - 2.0 / 3.0, entropy.seen);
+
+    // no separator at all for a single word
+    let entropy = settings.with_words_count(1).unwrap().calc_entropy(&pool);
+    assert_bits(words_bits(1024, 1), entropy.seen);
 }
 
 #[test]
-fn test_build_transforms_list() {
-    let all_transforms = WordTransform::Lowercase
-        | WordTransform::Titlecase
-        | WordTransform::Uppercase
-        | WordTransform::InversedTitlecase;
-
+fn test_calc_entropy_symbol_mode() {
+    let pool = words_pool(1024, (5, 5));
     let settings = Settings::default()
-        .with_words_count(3)
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("0123456789abcdef")
+        .with_padding_symbol_lengths(Some(3), Some(2));
+
+    // 2 bits of separator, 4 bits per symbol draw
+    let table = [
+        (SymbolMode::Repeat, 2.0 + 2.0 * 4.0),
+        (SymbolMode::Independent, 2.0 + 5.0 * 4.0),
+    ];
+
+    for (mode, bits) in table {
+        let entropy = settings
+            .with_symbol_mode(mode)
+            .calc_entropy(&as_strs(&pool));
+        assert_bits(words_bits(1024, 3) + bits, entropy.seen);
+    }
+
+    // adaptive padding draws as many symbols as missing on average
+    let pool = words_pool(1024, (4, 6));
+    let mean_len = pool.iter().map(|word| word.len()).sum::<usize>() as f64 / 1024.0;
+    let settings = settings
+        .with_words_count(2)
         .unwrap()
-        .with_word_transforms(all_transforms)
+        .with_padding_strategy(PaddingStrategy::Adaptive(20))
         .unwrap();
 
-    let transforms_list = settings.build_transforms_list();
-    assert_eq!(3, transforms_list.len());
-
     let table = [
+        (SymbolMode::Repeat, 2.0 + 4.0),
         (
-            FieldSize::from_flag(WordTransform::AltercaseLowerFirst),
-            vec![
-                WordTransform::Lowercase,
-                WordTransform::Uppercase,
-                WordTransform::Lowercase,
-            ],
-        ),
-        (
-            FieldSize::from_flag(WordTransform::AltercaseUpperFirst),
-            vec![
-                WordTransform::Uppercase,
-                WordTransform::Lowercase,
-                WordTransform::Uppercase,
-            ],
+            SymbolMode::Independent,
+            2.0 + (20.0 - 1.0 - 2.0 * mean_len) * 4.0,
         ),
     ];
 
-    for (group_transform, expected) in table {
-        let settings = Settings::default()
-            .with_words_count(3)
-            .unwrap()
-            .with_word_transforms(all_transforms | group_transform)
-            .unwrap();
-        let transforms_list = settings.build_transforms_list();
-        assert_eq!(expected, transforms_list);
+    for (mode, bits) in table {
+        let entropy = settings
+            .with_symbol_mode(mode)
+            .calc_entropy(&as_strs(&pool));
+        assert_bits(words_bits(1024, 2) + bits, entropy.seen);
     }
 }
 
 #[test]
-fn test_rand_digits() {
-    assert_eq!("", rand_digits(0));
+fn test_calc_entropy_trimming() {
+    let pool = words_pool(1024, (4, 6));
+    let settings = Settings::default()
+        .with_words_count(2)
+        .unwrap()
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_strategy(PaddingStrategy::Adaptive(8))
+        .unwrap();
 
-    for count in 1..21 {
-        for _ in 0..100 {
-            let digits = rand_digits(count);
-            assert_eq!(count as usize, digits.len());
-        }
-    }
+    // the second word might be cut, only the first one & the separator are accounted
+    let entropy = settings.calc_entropy(&as_strs(&pool));
+    assert_bits(words_bits(1024, 1) + 2.0, entropy.seen);
 
-    for count in 21..100 {
-        for _ in 0..100 {
-            let digits = rand_digits(count);
-            assert_eq!(20, digits.len());
-        }
-    }
+    // nothing cut with enough room for the longest words, 1 symbol padded sometimes
+    let entropy = settings
+        .with_padding_strategy(PaddingStrategy::Adaptive(13))
+        .unwrap()
+        .calc_entropy(&as_strs(&pool));
+    assert!(entropy.seen > words_bits(1024, 2) + 2.0);
+    assert!(entropy.seen < words_bits(1024, 2) + 2.0 + 4f64.log2() + 4.0);
 }
 
 #[test]
-fn test_rand_chars() {
-    assert_eq!("".to_string(), rand_chars("", 1));
+fn test_calc_entropy_exact() {
+    let pool = words_pool(1024, (4, 6));
+    let settings = Settings::default()
+        .with_words_count(1)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Lowercase))
+        .unwrap()
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("")
+        .with_padding_strategy(PaddingStrategy::Exact(5))
+        .unwrap();
 
-    // single char randomize
-    for _ in 0..10 {
-        let result = rand_chars(Settings::DEFAULT_SYMBOLS, 1);
-        assert!(Settings::DEFAULT_SYMBOLS.contains(&result));
+    // only words of 5 chars fit without padding symbols
+    let entropy = settings.calc_entropy(&as_strs(&pool));
+    assert_bits(341f64.log2(), entropy.seen);
+    assert_bits(5.0 * 58f64.log2(), entropy.blind_min);
+    assert_bits(entropy.blind_min, entropy.blind_max);
+
+    // words of 4 & 5 chars fit, the shorter ones padded with a symbol
+    let entropy = settings
+        .with_padding_symbols("!#")
+        .calc_entropy(&as_strs(&pool));
+    assert_bits(683f64.log2() + 342.0 / 683.0, entropy.seen);
+
+    // no word fits, passwords fall back to their natural length
+    let entropy = settings
+        .with_padding_strategy(PaddingStrategy::Exact(3))
+        .unwrap()
+        .calc_entropy(&as_strs(&pool));
+    assert_bits(0.0, entropy.seen);
+}
+
+#[test]
+fn test_calc_entropy_length_range() {
+    let pool = words_pool(1024, (4, 6));
+    let settings = Settings::default()
+        .with_words_count(1)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Lowercase))
+        .unwrap()
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("")
+        .with_padding_symbol_lengths(Some(0), Some(0));
+
+    assert_bits(10.0, settings.calc_entropy(&as_strs(&pool)).seen);
+
+    // only words of 5 chars are kept
+    let entropy = settings
+        .with_length_range(Some(5), Some(5))
+        .unwrap()
+        .calc_entropy(&as_strs(&pool));
+    assert_bits(341f64.log2(), entropy.seen);
+    assert_bits(entropy.blind_min, entropy.blind_max);
+
+    // words of 5 & 6 chars
+    let entropy = settings
+        .with_length_range(Some(5), None)
+        .unwrap()
+        .calc_entropy(&as_strs(&pool));
+    assert_bits(682f64.log2(), entropy.seen);
+
+    // digits substituted by 2 chars make passwords longer
+    let settings = settings
+        .with_separators("")
+        .with_padding_digits(Some(0), Some(2))
+        .with_substitutions(&[('1', "11".to_string())])
+        .unwrap();
+    let lengths = entropy::length_distribution(&settings, &as_strs(&pool));
+    assert_eq!(11, lengths.len());
+    assert_bits(342.0 / 1024.0 * 0.8, lengths[6]);
+    assert_bits(341.0 / 1024.0 / 90.0, lengths[10]);
+    assert_bits(1.0, lengths.iter().sum());
+
+    assert_bits(
+        342.0 / 1024.0 * 0.8,
+        entropy::length_acceptance(
+            &settings.with_length_range(None, Some(6)).unwrap(),
+            &as_strs(&pool),
+        ),
+    );
+
+    // no password fits
+    let entropy = settings
+        .with_length_range(Some(11), None)
+        .unwrap()
+        .calc_entropy(&as_strs(&pool));
+    assert_bits(0.0, entropy.seen);
+}
+
+#[test]
+fn test_calc_entropy_transforms() {
+    let pool = words_pool(1024, (5, 5));
+    let settings = Settings::default()
+        .with_words_count(4)
+        .unwrap()
+        .with_separators("-")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("");
+
+    // alternating transforms are known in advance
+    for transform in [
+        WordTransform::AltercaseLowerFirst,
+        WordTransform::AltercaseUpperFirst,
+    ] {
+        let entropy = settings
+            .with_word_transforms(transform as FieldSize)
+            .unwrap()
+            .calc_entropy(&as_strs(&pool));
+        assert_bits(words_bits(1024, 4) - 4.0, entropy.seen);
     }
 
-    // multi char randomize
-    for _ in 0..10 {
-        for count in 2..5 {
-            let result = rand_chars(Settings::DEFAULT_SYMBOLS, count);
-            assert_eq!(count as usize, result.len());
-            assert_eq!(
-                result
-                    .chars()
-                    .nth(0)
-                    .unwrap()
-                    .to_string()
-                    .repeat(count as usize),
-                result
-            );
-        }
+    // 2 bits of randomized transforms per word
+    let entropy = settings
+        .with_word_transforms(
+            WordTransform::Lowercase
+                | WordTransform::Titlecase
+                | WordTransform::Uppercase
+                | WordTransform::InversedTitlecase,
+        )
+        .unwrap()
+        .calc_entropy(&as_strs(&pool));
+    assert_bits(words_bits(1024, 4) + 4.0, entropy.seen);
+
+    // a random capital among 5 letters
+    let entropy = settings
+        .with_word_transforms(FieldSize::from_flag(WordTransform::RandomCapital))
+        .unwrap()
+        .calc_entropy(&as_strs(&pool));
+    assert_bits(words_bits(1024, 4) - 4.0 + 4.0 * 5f64.log2(), entropy.seen);
+
+    // leetspeak & last capital are known in advance
+    for transform in [WordTransform::Leetspeak, WordTransform::LastCapital] {
+        let entropy = settings
+            .with_word_transforms(FieldSize::from_flag(transform))
+            .unwrap()
+            .calc_entropy(&as_strs(&pool));
+        assert_bits(words_bits(1024, 4) - 4.0, entropy.seen);
     }
+
+    // reversed words may look like other words of the pool
+    let settings_reversed = settings
+        .with_words_count(1)
+        .unwrap()
+        .with_word_transforms(WordTransform::Lowercase | WordTransform::Reversed)
+        .unwrap();
+    assert_bits(2.0, settings_reversed.calc_entropy(&["abc", "xyz"]).seen);
+    assert_bits(1.0, settings_reversed.calc_entropy(&["abc", "cba"]).seen);
+
+    // single letter words look the same titlecased & uppercased
+    let entropy = settings
+        .with_words_count(1)
+        .unwrap()
+        .with_word_transforms(WordTransform::Titlecase | WordTransform::Uppercase)
+        .unwrap()
+        .calc_entropy(&["a", "b"]);
+    assert_bits(1.0, entropy.seen);
+
+    // words folded into the same output can't be told apart
+    let settings = settings
+        .with_words_count(1)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Lowercase))
+        .unwrap();
+    assert_bits(1.0, settings.calc_entropy(&["été", "ete"]).seen);
+    assert_bits(
+        0.0,
+        settings
+            .with_ascii_fold(true)
+            .calc_entropy(&["été", "ete"])
+            .seen,
+    );
+
+    // not enough words to avoid duplicates
+    assert_bits(
+        4.0 * 2f64.log2(),
+        settings
+            .with_words_count(4)
+            .unwrap()
+            .calc_entropy(&["foo", "bar"])
+            .seen,
+    );
+    assert_bits(0.0, settings.calc_entropy::<[&str]>(&[]).seen);
 }
 
 #[test]
-fn test_transform_word() {
-    let table = [
-        (
-            WordTransform::Lowercase,
-            [
-                ("foo", "foo"),
-                ("Bar", "bar"),
-                ("1Fooz", "1fooz"),
+fn test_calc_entropy_unique_prefix() {
+    let pool = ["abcd", "abce", "bcde", "cdef"];
+    let settings = Settings::default()
+        .with_words_count(2)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Lowercase))
+        .unwrap()
+        .with_separators("-")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("");
+
+    assert_bits(2.0 + 3f64.log2(), settings.calc_entropy(&pool).seen);
+
+    // no prefix shared by several words, nothing more is ruled out
+    assert_bits(
+        2.0 + 3f64.log2(),
+        settings.with_unique_prefix(4).calc_entropy(&pool).seen,
+    );
+
+    // picking "abcd" rules out "abce" too, credited for every pick
+    for unique_prefix in [1, 3] {
+        assert_bits(
+            3.0,
+            settings
+                .with_unique_prefix(unique_prefix)
+                .calc_entropy(&pool)
+                .seen,
+        );
+    }
+
+    // every prefix is taken by the first word, nothing is credited to the second one
+    assert_bits(
+        2.0,
+        settings
+            .with_unique_prefix(2)
+            .calc_entropy(&["abcd", "abce", "abde", "abef"])
+            .seen,
+    );
+}
+
+#[test]
+fn test_calc_entropy_substitutions() {
+    let pool = words_pool(1024, (5, 5));
+    let pool = as_strs(&pool);
+    let settings = Settings::default()
+        .with_separators("-|")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("");
+
+    // separators substituted into the same char give a single one
+    let table = [
+        (vec![], 1.0),
+        (vec![('|', "_".to_string())], 1.0),
+        (vec![('|', "-".to_string())], 0.0),
+        (vec![('|', "-".to_string()), ('-', "|".to_string())], 1.0),
+    ];
+
+    for (rules, bits) in table {
+        let entropy = settings
+            .with_substitutions(&rules)
+            .unwrap()
+            .calc_entropy(&pool);
+        assert_bits(words_bits(1024, 3) + bits, entropy.seen);
+    }
+
+    // symbols substituted by several read more than one way, e.g. !!! from #! or !#
+    let symbols_settings = settings
+        .with_padding_symbols("!#")
+        .with_padding_symbol_lengths(Some(0), Some(3))
+        .with_symbol_mode(SymbolMode::Independent);
+    let table = [
+        (vec![], 3.0),
+        (vec![('#', "##".to_string())], 3.0),
+        (vec![('#', "!!".to_string())], 1.0),
+    ];
+
+    for (rules, bits) in table {
+        let entropy = symbols_settings
+            .with_substitutions(&rules)
+            .unwrap()
+            .calc_entropy(&pool);
+        assert_bits(words_bits(1024, 3) + 1.0 + bits, entropy.seen);
+    }
+
+    // words substituted the same way collide
+    let settings = settings
+        .with_words_count(1)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Lowercase))
+        .unwrap();
+    let entropy = settings.calc_entropy(&["abc", "abd"]);
+    assert_bits(1.0, entropy.seen);
+
+    let entropy = settings
+        .with_substitutions(&[('d', "c".to_string())])
+        .unwrap()
+        .calc_entropy(&["abc", "abd"]);
+    assert_bits(0.0, entropy.seen);
+
+    // chars brought in by substitutions widen the blind pool
+    let settings = settings.with_separators("");
+    let blind_len = settings.calc_entropy(&pool).blind_min / 26f64.log2();
+    let table = [
+        ('a', "A", 52f64.log2()),
+        ('a', "4", 36f64.log2()),
+        ('a', "_", 58f64.log2()),
+        ('a', "b", 26f64.log2()),
+    ];
+
+    for (from, to, bits) in table {
+        let entropy = settings
+            .with_substitutions(&[(from, to.to_string())])
+            .unwrap()
+            .calc_entropy(&pool);
+        assert_bits(blind_len * bits, entropy.blind_min);
+    }
+}
+
+#[test]
+fn test_words_entropies() {
+    let words_only = |settings: &Settings| {
+        settings
+            .with_separators("")
+            .with_padding_digits(Some(0), Some(0))
+            .with_padding_symbol_lengths(Some(0), Some(0))
+    };
+
+    // from a large pool, a pool smaller than the words count & with alternating transforms
+    let table = [
+        (words_pool(1024, (5, 5)), Settings::default()),
+        (words_pool(3, (5, 5)), Settings::default()),
+        (
+            words_pool(64, (4, 6)),
+            Settings::default()
+                .with_word_transforms(FieldSize::from_flag(WordTransform::AltercaseUpperFirst))
+                .unwrap(),
+        ),
+    ];
+
+    for (pool, settings) in table {
+        let pool = as_strs(&pool);
+        let settings = words_only(&settings.with_words_count(5).unwrap());
+        let entropies = settings.words_entropies(&pool);
+
+        assert_eq!(5, entropies.len());
+        for (count, bits) in (1..=5).zip(entropies) {
+            let expected = settings
+                .with_words_count(count)
+                .unwrap()
+                .calc_entropy(&pool)
+                .seen;
+            assert_bits(expected, bits);
+        }
+    }
+}
+
+#[test]
+fn test_chars_entropy() {
+    let table = [
+        ("", 0.0),
+        ("a", 0.0),
+        ("aaa", 0.0),
+        ("ab", 1.0),
+        ("⟪⟫é€", 2.0),
+        ("aab", 3f64.log2() - 2.0 / 3.0),
+    ];
+
+    for (pool, bits) in table {
+        assert_bits(bits, entropy::chars_entropy(pool, &[]));
+    }
+
+    // substituted chars merge with chars they're substituted into
+    let substitutions = [('b', "a".to_string()), ('c', "".to_string())];
+    let table = [
+        ("ab", 0.0),
+        ("abc", 3f64.log2() - 2.0 / 3.0),
+        ("cd", 1.0),
+        ("bé", 1.0),
+    ];
+
+    for (pool, bits) in table {
+        assert_bits(bits, entropy::chars_entropy(pool, &substitutions));
+    }
+}
+
+#[test]
+fn test_digits_entropy() {
+    let table = [
+        ((1, 0), 0.0),
+        ((1, 1), 9f64.log2()),
+        ((3, 3), 900f64.log2()),
+        // trimmed digits
+        ((3, 1), 9f64.log2()),
+        ((19, 19), 9f64.log2() + 18.0 * 10f64.log2()),
+        // 20 digits numbers are bound by u64::MAX
+        ((20, 20), ((u64::MAX - 10u64.pow(19)) as f64).log2()),
+        ((50, 20), ((u64::MAX - 10u64.pow(19)) as f64).log2()),
+        ((20, 19), 0.0),
+    ];
+
+    for ((count, kept), bits) in table {
+        assert_bits(bits, entropy::digits_entropy(count, kept, &[]));
+    }
+
+    // 0 & 1 both give o
+    let substitutions = [('0', "o".to_string()), ('1', "o".to_string())];
+    let table = [
+        ((1, 1), 9f64.log2()),
+        ((2, 2), 9f64.log2() + 10f64.log2() - 0.2),
+        ((20, 20), ((u64::MAX - 10u64.pow(19)) as f64).log2() - 20.0),
+    ];
+
+    for ((count, kept), bits) in table {
+        assert_bits(bits, entropy::digits_entropy(count, kept, &substitutions));
+    }
+
+    // dropped zeros make digits readable several ways, e.g. 101 & 110 both give 11
+    let substitutions = [('0', "".to_string())];
+    let table = [
+        ((1, 1), 9f64.log2()),
+        ((3, 3), 10f64.log2()),
+        ((20, 20), 0.0),
+    ];
+
+    for ((count, kept), bits) in table {
+        assert_bits(bits, entropy::digits_entropy(count, kept, &substitutions));
+    }
+}
+
+// each possible outcome of a random part, with its probability
+type Outcomes<T> = Vec<(T, f64)>;
+
+fn symbols_outcomes(settings: &Settings, count: usize) -> Outcomes<String> {
+    let chars: Vec<char> = settings.padding_symbols.chars().collect();
+    if chars.is_empty() || count == 0 {
+        return vec![("".to_string(), 1.0)];
+    }
+
+    let prob = 1.0 / chars.len() as f64;
+    match settings.symbol_mode {
+        SymbolMode::Repeat => chars
+            .iter()
+            .map(|c| (c.to_string().repeat(count), prob))
+            .collect(),
+        SymbolMode::Independent => (0..count).fold(vec![("".to_string(), 1.0)], |acc, _| {
+            acc.iter()
+                .flat_map(|(symbols, acc_prob)| {
+                    chars
+                        .iter()
+                        .map(move |c| (format!("{}{}", symbols, c), acc_prob * prob))
+                })
+                .collect()
+        }),
+    }
+}
+
+fn digits_outcomes(count: u8) -> Outcomes<String> {
+    if count == 0 {
+        return vec![("".to_string(), 1.0)];
+    }
+
+    let range = 10u64.pow(count as u32 - 1)..10u64.pow(count as u32);
+    let prob = 1.0 / (range.end - range.start) as f64;
+    range.map(|digits| (digits.to_string(), prob)).collect()
+}
+
+fn separators_outcomes(settings: &Settings, gaps: usize) -> Outcomes<Vec<char>> {
+    let chars: Vec<char> = settings.separators.chars().collect();
+    let prob = 1.0 / chars.len() as f64;
+
+    match settings.separator_mode {
+        SeparatorMode::Repeat => chars.iter().map(|&c| (vec![c; gaps], prob)).collect(),
+        SeparatorMode::Independent => (0..gaps).fold(vec![(vec![], 1.0)], |acc, _| {
+            acc.iter()
+                .flat_map(|(separators, acc_prob)| {
+                    chars.iter().map(move |&c| {
+                        let mut separators = separators.clone();
+                        separators.push(c);
+                        (separators, acc_prob * prob)
+                    })
+                })
+                .collect()
+        }),
+    }
+}
+
+fn words_outcomes(settings: &Settings, pool: &[&str]) -> Outcomes<Vec<String>> {
+    let count = settings.words_count as usize;
+    let with_replacement = pool.len() < count;
+
+    // picked words indices
+    let picks = (0..count).fold(vec![(vec![], 1.0)], |acc: Outcomes<Vec<usize>>, _| {
+        acc.iter()
+            .flat_map(|(indices, acc_prob)| {
+                let choices: Vec<usize> = (0..pool.len())
+                    .filter(|idx| with_replacement || !indices.contains(idx))
+                    .collect();
+                let prob = acc_prob / choices.len() as f64;
+
+                choices.into_iter().map(move |idx| {
+                    let mut indices = indices.clone();
+                    indices.push(idx);
+                    (indices, prob)
+                })
+            })
+            .collect()
+    });
+
+    picks
+        .iter()
+        .flat_map(|(indices, pick_prob)| {
+            indices
+                .iter()
+                .enumerate()
+                .fold(vec![(vec![], *pick_prob)], |acc, (pos, &idx)| {
+                    let transforms = settings.transform_choices(pos);
+                    let prob = 1.0 / transforms.len() as f64;
+
+                    acc.iter()
+                        .flat_map(|(words, acc_prob): &(Vec<String>, f64)| {
+                            transforms.iter().flat_map(move |&transform| {
+                                let word = if settings.ascii_fold {
+                                    fold_ascii(pool[idx])
+                                } else {
+                                    pool[idx].to_string()
+                                };
+                                let variants = transform_variants(&word, transform);
+                                let variant_prob = prob / variants.len() as f64;
+
+                                variants.into_iter().map(move |word| {
+                                    let mut words = words.clone();
+                                    words.push(word);
+                                    (words, acc_prob * variant_prob)
+                                })
+                            })
+                        })
+                        .collect()
+                })
+        })
+        .collect()
+}
+
+// probabilities of all passwords generated as assemble_passwd does, out of range ones regenerated,
+// and whether some of them got trimmed, None if there are too many to enumerate
+fn enumerate_passwds(
+    settings: &Settings,
+    pool: &[&str],
+    max_outcomes: usize,
+) -> Option<(HashMap<String, f64>, bool)> {
+    let (prefix_digits, suffix_digits) = settings.padding_digits;
+    let (prefix_symbols, suffix_symbols) = settings.padding_symbol_lengths;
+
+    let mut passwds: HashMap<String, f64> = HashMap::new();
+    let mut trimmed = false;
+    let mut outcomes = 0;
+
+    let words_outcomes = words_outcomes(settings, pool);
+
+    for (prefix_symbols, prefix_symbols_prob) in symbols_outcomes(settings, prefix_symbols as usize)
+    {
+        for (prefix_digits, prefix_digits_prob) in digits_outcomes(prefix_digits) {
+            for (words, words_prob) in &words_outcomes {
+                for (suffix_digits, suffix_digits_prob) in digits_outcomes(suffix_digits) {
+                    for (suffix_symbols, suffix_symbols_prob) in
+                        symbols_outcomes(settings, suffix_symbols as usize)
+                    {
+                        let blocks: Vec<&str> = [prefix_digits.as_str()]
+                            .into_iter()
+                            .chain(words.iter().map(String::as_str))
+                            .chain([suffix_digits.as_str()])
+                            .filter(|block| !block.is_empty())
+                            .collect();
+                        let prob = prefix_symbols_prob
+                            * prefix_digits_prob
+                            * words_prob
+                            * suffix_digits_prob
+                            * suffix_symbols_prob;
+
+                        for (separators, separators_prob) in
+                            separators_outcomes(settings, blocks.len() - 1)
+                        {
+                            let mut passwd = prefix_symbols.clone();
+                            for (idx, block) in blocks.iter().enumerate() {
+                                if idx > 0 {
+                                    passwd.push(separators[idx - 1]);
+                                }
+                                passwd.push_str(block);
+                            }
+                            passwd.push_str(&suffix_symbols);
+
+                            let len = passwd.chars().count();
+                            let padded = match settings.padding_strategy {
+                                PaddingStrategy::Adaptive(limit) if limit < len => {
+                                    trimmed = true;
+                                    vec![(passwd.chars().take(limit).collect(), 1.0)]
+                                }
+                                // words not fitting are never picked
+                                PaddingStrategy::Exact(limit)
+                                    if limit < len
+                                        || (limit > len && settings.padding_symbols.is_empty()) =>
+                                {
+                                    vec![]
+                                }
+                                PaddingStrategy::Adaptive(limit)
+                                | PaddingStrategy::Exact(limit) => {
+                                    symbols_outcomes(settings, limit - len)
+                                        .into_iter()
+                                        .map(|(symbols, prob)| (passwd.clone() + &symbols, prob))
+                                        .collect()
+                                }
+                                PaddingStrategy::Fixed => vec![(passwd, 1.0)],
+                            };
+
+                            for (passwd, padding_prob) in padded {
+                                outcomes += 1;
+                                if outcomes > max_outcomes {
+                                    return None;
+                                }
+
+                                let passwd = substitute(&passwd, &settings.substitutions);
+                                if !settings.fits_length(&passwd) {
+                                    continue;
+                                }
+
+                                *passwds.entry(passwd).or_default() +=
+                                    prob * separators_prob * padding_prob;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // fitting words & passwords are all equally likely
+    if matches!(settings.padding_strategy, PaddingStrategy::Exact(_))
+        || settings.length_range != (None, None)
+    {
+        let total: f64 = passwds.values().sum();
+        passwds.values_mut().for_each(|prob| *prob /= total);
+    }
+
+    Some((passwds, trimmed))
+}
+
+#[test]
+fn test_enumerate_passwds() {
+    let settings = Settings::default()
+        .with_words_count(2)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Uppercase))
+        .unwrap()
+        .with_separators("-")
+        .with_padding_digits(Some(0), Some(1))
+        .with_padding_symbols("")
+        .with_padding_strategy(PaddingStrategy::Adaptive(5))
+        .unwrap();
+
+    let (passwds, trimmed) = enumerate_passwds(&settings, &["a", "b"], 1000).unwrap();
+
+    assert!(!trimmed);
+    assert_eq!(18, passwds.len());
+    assert_bits(1.0 / 18.0, passwds["A-B-1"]);
+    assert_bits(1.0, passwds.values().sum());
+
+    // trailing digits cut
+    let settings = settings
+        .with_padding_strategy(PaddingStrategy::Adaptive(4))
+        .unwrap();
+    let (passwds, trimmed) = enumerate_passwds(&settings, &["a", "b"], 1000).unwrap();
+
+    assert!(trimmed);
+    assert_eq!(2, passwds.len());
+    assert_bits(0.5, passwds["B-A-"]);
+
+    assert!(enumerate_passwds(&settings, &["a", "b"], 17).is_none());
+}
+
+#[test]
+fn test_enumerate_passwds_exact() {
+    let settings = Settings::default()
+        .with_words_count(2)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Lowercase))
+        .unwrap()
+        .with_separators("-")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("")
+        .with_padding_strategy(PaddingStrategy::Exact(4))
+        .unwrap();
+
+    // only "a" & "bb" fit in 4 chars along with the separator
+    let pool = ["a", "bb", "ccc"];
+    let (passwds, trimmed) = enumerate_passwds(&settings, &pool, 1000).unwrap();
+
+    assert!(!trimmed);
+    assert_eq!(2, passwds.len());
+    assert_bits(0.5, passwds["bb-a"]);
+    assert_bits(
+        1.0,
+        entropy::seen_entropy(&settings, &pool, settings.pool_state(&pool).as_ref()),
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod entropy_props {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn settings_strategy() -> impl Strategy<Value = Settings> {
+        (
+            (
+                1u8..=3,
+                prop_oneof![
+                    (1u16..256).prop_map(|bits| (bits & 0b00111111) | (bits & 0b11000000) << 2),
+                    Just(64),
+                    Just(128)
+                ],
+                any::<bool>(),
+            ),
+            ("[-.]{1,2}", any::<bool>()),
+            (0u8..=1, 0u8..=1),
+            ("[!#]{0,2}", 0u8..=2, 0u8..=2, any::<bool>()),
+            prop_oneof![
+                Just(PaddingStrategy::Fixed),
+                (1usize..=16).prop_map(PaddingStrategy::Adaptive)
+            ],
+            // each part substituted within its own chars, so that parts stay apart
+            prop::sample::subsequence(
+                vec![
+                    ('-', "."),
+                    ('.', "_"),
+                    ('!', "#"),
+                    ('#', "!!"),
+                    ('0', "9"),
+                    ('1', "2"),
+                    ('a', "b"),
+                    ('é', "e"),
+                ],
+                0..=3,
+            ),
+        )
+            .prop_map(
+                |(
+                    (words_count, transforms, ascii_fold),
+                    (separators, independent_separators),
+                    (prefix_digits, suffix_digits),
+                    (symbols, prefix_symbols, suffix_symbols, independent_symbols),
+                    padding_strategy,
+                    substitutions,
+                )| {
+                    let substitutions: Vec<(char, String)> = substitutions
+                        .into_iter()
+                        .map(|(from, to)| (from, to.to_string()))
+                        .collect();
+
+                    let (separator_mode, symbol_mode) = (
+                        if independent_separators {
+                            SeparatorMode::Independent
+                        } else {
+                            SeparatorMode::Repeat
+                        },
+                        if independent_symbols {
+                            SymbolMode::Independent
+                        } else {
+                            SymbolMode::Repeat
+                        },
+                    );
+
+                    // padding strategy first, as adaptive padding resets symbol lengths
+                    Settings::default()
+                        .with_padding_strategy(padding_strategy)
+                        .unwrap()
+                        .with_words_count(words_count)
+                        .unwrap()
+                        .with_word_transforms(transforms)
+                        .unwrap()
+                        .with_ascii_fold(ascii_fold)
+                        .with_separators(&separators)
+                        .with_separator_mode(separator_mode)
+                        .with_padding_digits(Some(prefix_digits), Some(suffix_digits))
+                        .with_padding_symbols(&symbols)
+                        .with_padding_symbol_lengths(Some(prefix_symbols), Some(suffix_symbols))
+                        .with_symbol_mode(symbol_mode)
+                        .with_substitutions(&substitutions)
+                        .unwrap()
+                },
+            )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig {
+            cases: 128,
+            max_global_rejects: 4096,
+            ..ProptestConfig::default()
+        })]
+
+        // computed entropy matches brute force when nothing gets trimmed or folded,
+        // and never overestimates it otherwise
+        #[test]
+        fn test_seen_entropy_brute_force(
+            settings in settings_strategy(),
+            pool in prop::collection::hash_set("[abé]{1,3}", 1..=4),
+        ) {
+            let pool: Vec<&str> = pool.iter().map(String::as_str).collect();
+            let enumerated = enumerate_passwds(&settings, &pool, 200_000);
+            prop_assume!(enumerated.is_some());
+
+            let (passwds, trimmed) = enumerated.unwrap();
+            let expected: f64 = passwds.values().map(|prob| -prob * prob.log2()).sum();
+            let seen = entropy::seen_entropy(&settings, &pool, settings.pool_state(&pool).as_ref());
+
+            // reversed or substituted words may give the same output as other words
+            let collisions = settings.ascii_fold
+                || settings.word_transforms.has_flag(WordTransform::Reversed)
+                || settings.substitutions.iter().any(|(from, _)| from.is_alphabetic());
+
+            // runs of chars substituted by several may read more than one way
+            let resized = settings
+                .substitutions
+                .iter()
+                .any(|(_, to)| to.chars().count() != 1);
+
+            if trimmed || collisions || resized {
+                prop_assert!(seen <= expected + 1e-9, "{} > {} bits", seen, expected);
+            } else {
+                prop_assert!((seen - expected).abs() < 1e-9, "{} != {} bits", seen, expected);
+            }
+        }
+
+        // exact padding matches brute force when words can't collide & each word
+        // has as many transforms, and never overestimates it otherwise
+        #[test]
+        fn test_exact_entropy_brute_force(
+            settings in settings_strategy(),
+            len in 1usize..=12,
+            pool in prop::collection::hash_set("[abé]{1,3}", 1..=4),
+        ) {
+            // substitutions changing lengths can't be combined with exact padding
+            let settings = settings.with_padding_strategy(PaddingStrategy::Exact(len));
+            prop_assume!(settings.is_ok());
+
+            let settings = settings.unwrap();
+            let pool: Vec<&str> = pool.iter().map(String::as_str).collect();
+            let enumerated = enumerate_passwds(&settings, &pool, 200_000);
+            prop_assume!(enumerated.as_ref().is_some_and(|(passwds, _)| !passwds.is_empty()));
+
+            let (passwds, trimmed) = enumerated.unwrap();
+            let expected: f64 = passwds.values().map(|prob| -prob * prob.log2()).sum();
+            let seen = entropy::seen_entropy(&settings, &pool, settings.pool_state(&pool).as_ref());
+            prop_assert!(!trimmed);
+
+            let collisions = settings.ascii_fold
+                || settings.word_transforms.has_flag(WordTransform::Reversed)
+                || settings.substitutions.iter().any(|(from, _)| from.is_alphabetic());
+            let resized = settings
+                .substitutions
+                .iter()
+                .any(|(_, to)| to.chars().count() != 1);
+            // words of a single letter or with no letters to replace get fewer outputs
+            let single_transform = settings.word_transforms.count_ones() == 1
+                && !settings.word_transforms.has_flag(WordTransform::RandomCapital);
+
+            if collisions || resized || !single_transform {
+                prop_assert!(seen <= expected + 1e-9, "{} > {} bits", seen, expected);
+            } else {
+                prop_assert!((seen - expected).abs() < 1e-9, "{} != {} bits", seen, expected);
+            }
+        }
+
+        // the chance of a password to fit the length range matches brute force
+        #[test]
+        fn test_length_acceptance_brute_force(
+            settings in settings_strategy(),
+            min_length in prop::option::of(0usize..=12),
+            max_length in prop::option::of(0usize..=12),
+            pool in prop::collection::hash_set("[abé]{1,3}", 1..=4),
+        ) {
+            let settings = settings
+                .with_padding_strategy(PaddingStrategy::Fixed)
+                .unwrap();
+            let pool: Vec<&str> = pool.iter().map(String::as_str).collect();
+            let enumerated = enumerate_passwds(&settings, &pool, 200_000);
+            prop_assume!(enumerated.is_some());
+
+            let in_range = settings.with_length_range(min_length, max_length);
+            prop_assume!(in_range.is_ok());
+            let in_range = in_range.unwrap();
+
+            let (passwds, _) = enumerated.unwrap();
+            let expected: f64 = passwds
+                .iter()
+                .filter(|(passwd, _)| in_range.fits_length(passwd))
+                .map(|(_, prob)| prob)
+                .sum();
+            let acceptance = entropy::length_acceptance(&in_range, &pool);
+
+            prop_assert!((acceptance - expected).abs() < 1e-9, "{} != {}", acceptance, expected);
+        }
+    }
+}
+
+// words drawn as `Randomizer::rand_words` does, along with the pool state
+fn words_list<'a, R: RngCore + CryptoRng, P: WordsPool + ?Sized>(
+    settings: &Settings,
+    rng: &mut R,
+    pool: &'a P,
+) -> Vec<&'a str> {
+    settings.build_words_list(rng, pool, settings.pool_state(pool).as_ref())
+}
+
+#[test]
+fn test_build_words_list() {
+    let mut rng = rand::thread_rng();
+    let settings = Settings::default().with_words_count(3).unwrap();
+
+    // empty pool
+    assert!(words_list(&settings, &mut rng, &vec![] as &Vec<&str>).is_empty());
+
+    // pool size smaller than words count
+    let pool = &["foo", "bar"];
+
+    for _ in 0..10 {
+        let words = words_list(&settings, &mut rng, pool);
+        assert_eq!(3, words.len());
+
+        let unique_words: HashSet<String> = words.iter().map(|word| word.to_lowercase()).collect();
+        assert!(unique_words.len() < 3);
+    }
+
+    // enough pool
+    let pool = &["foo", "bar", "fooz", "barz"];
+
+    for _ in 0..10 {
+        let words = words_list(&settings, &mut rng, pool);
+        assert_eq!(3, words.len());
+
+        let unique_words: HashSet<String> = words.iter().map(|word| word.to_lowercase()).collect();
+        assert_eq!(3, unique_words.len());
+    }
+}
+
+#[test]
+fn test_build_words_list_exact() {
+    let mut rng = rand::thread_rng();
+    let pool = &["ab", "cd", "efg", "hij", "klmn", "straße"];
+    let settings = Settings::default()
+        .with_words_count(2)
+        .unwrap()
+        .with_separators("")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("")
+        .with_padding_strategy(PaddingStrategy::Exact(6))
+        .unwrap();
+
+    // words add up to the length exactly without padding symbols,
+    // those changing length once uppercased never being picked
+    for _ in 0..20 {
+        let words = words_list(&settings, &mut rng, pool);
+        assert_eq!(2, words.len());
+        assert_ne!(words[0], words[1]);
+        assert_eq!(6, words.concat().chars().count(), "{:?}", words);
+    }
+
+    // or up to the length, padding symbols making up the rest
+    let settings = settings.with_padding_symbols("!");
+    let mut lens: HashSet<usize> = HashSet::new();
+
+    for _ in 0..50 {
+        let len = words_list(&settings, &mut rng, pool).concat().len();
+        assert!(len <= 6);
+        lens.insert(len);
+    }
+    assert_eq!(HashSet::from([4, 5, 6]), lens);
+
+    // none fits, any words are picked
+    let settings = settings
+        .with_padding_strategy(PaddingStrategy::Exact(3))
+        .unwrap();
+    assert_eq!(2, words_list(&settings, &mut rng, pool).len());
+}
+
+#[test]
+fn test_check_pool() {
+    let pool = &["ab", "cd", "efg", "hij"];
+    let settings = Settings::default()
+        .with_words_count(2)
+        .unwrap()
+        .with_separators("-")
+        .with_padding_digits(Some(0), Some(2))
+        .with_padding_symbols("");
+
+    // other strategies always fit
+    assert_eq!(Ok(()), settings.check_pool(pool));
+    assert_eq!(
+        Ok(()),
+        settings
+            .with_padding_strategy(PaddingStrategy::Adaptive(3))
+            .unwrap()
+            .check_pool(pool)
+    );
+
+    for len in [8, 9, 10] {
+        let settings = settings
+            .with_padding_strategy(PaddingStrategy::Exact(len))
+            .unwrap();
+        assert_eq!(Ok(()), settings.check_pool(pool), "{} chars", len);
+    }
+
+    let table = [
+        (
+            settings.with_padding_strategy(PaddingStrategy::Exact(11)),
+            "no 2 word(s) fit in exactly 11 chars along with digits & separators, without padding symbols",
+        ),
+        (
+            settings
+                .with_padding_symbols("!")
+                .with_padding_strategy(PaddingStrategy::Exact(7)),
+            "no 2 word(s) fit in 7 chars along with digits & separators",
+        ),
+        (
+            settings
+                .with_padding_strategy(PaddingStrategy::Exact(2))
+                .map(|settings| settings.with_padding_symbols("!")),
+            "no 2 word(s) fit in 2 chars along with digits & separators",
+        ),
+        (
+            settings
+                .with_unique_prefix(1)
+                .with_padding_strategy(PaddingStrategy::Exact(8)),
+            "unique prefix can't be combined with exact padding",
+        ),
+    ];
+
+    for (settings, message) in table {
+        let err = settings.unwrap().check_pool(pool).unwrap_err();
+        assert_eq!(message, err.to_string());
+    }
+
+    // passwords of 8 ~ 10 chars, e.g. "ab-cd-12"
+    let settings = settings.with_length_range(Some(7), Some(9)).unwrap();
+    assert_eq!(Ok(()), settings.check_pool(pool));
+
+    let table = [
+        (
+            settings.with_length_range(None, Some(7)).unwrap(),
+            "max length must be 8 or higher to fit any password, got 7",
+        ),
+        (
+            settings.with_length_range(Some(11), Some(12)).unwrap(),
+            "min length must be 10 or lower to fit any password, got 11",
+        ),
+        (
+            // words of 2, 3 or 7 chars make no pair of 7 or 8 chars
+            settings
+                .with_separators("")
+                .with_padding_digits(Some(0), Some(0))
+                .with_substitutions(&[('a', "aaaaaa".to_string())])
+                .unwrap()
+                .with_length_range(Some(7), Some(8))
+                .unwrap(),
+            "no password length from 7 to 8",
+        ),
+    ];
+
+    for (settings, message) in table {
+        let err = settings.check_pool(pool).unwrap_err();
+        assert_eq!(message, err.to_string());
+    }
+
+    // a single long word among 2000, too rarely picked to regenerate until it is
+    let mut words = words_pool(1999, (5, 5));
+    words.push("abcdefghij".to_string());
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let settings = Settings::default()
+        .with_words_count(2)
+        .unwrap()
+        .with_separators("")
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("")
+        .with_length_range(Some(15), None)
+        .unwrap();
+    match settings.check_pool(&words[..]) {
+        Err(XkpasswdError::LengthRangeUnlikely {
+            min: Some(15),
+            max: None,
+            acceptance,
+            min_acceptance,
+        }) => {
+            assert!((acceptance - 0.001).abs() < 1e-9, "{}", acceptance);
+            assert_eq!(0.002, min_acceptance);
+        }
+        result => panic!("unexpected {:?}", result),
+    }
+    assert_eq!(Ok(()), settings.check_pool(&words[1500..]));
+
+    // uppercased, the word would outgrow the length
+    let settings = Settings::default()
+        .with_words_count(1)
+        .unwrap()
+        .with_padding_digits(Some(0), Some(0))
+        .with_padding_symbols("")
+        .with_padding_strategy(PaddingStrategy::Exact(6))
+        .unwrap();
+    assert_eq!(Ok(()), settings.check_pool(&["strase"]));
+    assert!(settings.check_pool(&["straße"]).is_err());
+}
+
+#[test]
+fn test_build_words_list_unique_prefix() {
+    let mut rng = rand::thread_rng();
+    let pool = &["apple", "apply", "apron", "bacon", "baker", "cable"];
+    let settings = Settings::default()
+        .with_words_count(3)
+        .unwrap()
+        .with_unique_prefix(2);
+
+    for _ in 0..10 {
+        let words = words_list(&settings, &mut rng, pool);
+        let prefixes: HashSet<&str> = words.iter().map(|word| &word[..2]).collect();
+        assert_eq!(3, prefixes.len());
+    }
+
+    // more words than prefixes, shared ones are allowed again once all are taken
+    let settings = settings.with_words_count(5).unwrap();
+
+    for _ in 0..10 {
+        let words = words_list(&settings, &mut rng, pool);
+        let unique_words: HashSet<&str> = words.iter().copied().collect();
+        let prefixes: HashSet<&str> = words[..3].iter().map(|word| &word[..2]).collect();
+        assert_eq!(5, unique_words.len());
+        assert_eq!(3, prefixes.len());
+    }
+
+    // prefixes compared as output, i.e. after folding accents
+    let settings = settings
+        .with_words_count(2)
+        .unwrap()
+        .with_unique_prefix(1)
+        .with_ascii_fold(true);
+
+    for _ in 0..10 {
+        let words = words_list(&settings, &mut rng, &["été", "ete", "ami"]);
+        assert!(words.contains(&"ami"));
+    }
+}
+
+#[test]
+fn test_build_transforms_list() {
+    let mut rng = rand::thread_rng();
+    let all_transforms = WordTransform::Lowercase
+        | WordTransform::Titlecase
+        | WordTransform::Uppercase
+        | WordTransform::InversedTitlecase;
+
+    let settings = Settings::default()
+        .with_words_count(3)
+        .unwrap()
+        .with_word_transforms(all_transforms)
+        .unwrap();
+
+    let transforms_list = settings.build_transforms_list(&mut rng);
+    assert_eq!(3, transforms_list.len());
+
+    let table = [
+        (
+            FieldSize::from_flag(WordTransform::AltercaseLowerFirst),
+            vec![
+                WordTransform::Lowercase,
+                WordTransform::Uppercase,
+                WordTransform::Lowercase,
+            ],
+        ),
+        (
+            FieldSize::from_flag(WordTransform::AltercaseUpperFirst),
+            vec![
+                WordTransform::Uppercase,
+                WordTransform::Lowercase,
+                WordTransform::Uppercase,
+            ],
+        ),
+    ];
+
+    for (group_transform, expected) in table {
+        let settings = Settings::default()
+            .with_words_count(3)
+            .unwrap()
+            .with_word_transforms(all_transforms | group_transform)
+            .unwrap();
+        let transforms_list = settings.build_transforms_list(&mut rng);
+        assert_eq!(expected, transforms_list);
+    }
+}
+
+#[test]
+fn test_seeded_randomizer() {
+    let settings = Settings::from_preset(Preset::AppleID);
+    let pool = ["foo", "bar", "fooz", "barz", "foobar"];
+
+    let mut rng = ChaCha20Rng::seed_from_u64(42);
+    let mut other_rng = ChaCha20Rng::seed_from_u64(42);
+
+    for _ in 0..10 {
+        assert_eq!(
+            settings.rand_words(&mut rng, &pool),
+            settings.rand_words(&mut other_rng, &pool)
+        );
+        assert_eq!(
+            settings.rand_separators(&mut rng, 3),
+            settings.rand_separators(&mut other_rng, 3)
+        );
+        assert_eq!(
+            settings.rand_prefix(&mut rng),
+            settings.rand_prefix(&mut other_rng)
+        );
+        assert_eq!(
+            settings.rand_suffix(&mut rng),
+            settings.rand_suffix(&mut other_rng)
+        );
+    }
+}
+
+#[test]
+fn test_rand_digits() {
+    let mut rng = rand::thread_rng();
+    assert_eq!("", rand_digits(&mut rng, 0));
+
+    for count in 1..21 {
+        for _ in 0..100 {
+            let digits = rand_digits(&mut rng, count);
+            assert_eq!(count as usize, digits.len());
+        }
+    }
+
+    for count in 21..100 {
+        for _ in 0..100 {
+            let digits = rand_digits(&mut rng, count);
+            assert_eq!(20, digits.len());
+        }
+    }
+}
+
+#[test]
+fn test_rand_chars() {
+    let mut rng = rand::thread_rng();
+    assert_eq!("".to_string(), rand_chars(&mut rng, "", 1));
+
+    // single char randomize
+    for _ in 0..10 {
+        let result = rand_chars(&mut rng, Settings::DEFAULT_SYMBOLS, 1);
+        assert!(Settings::DEFAULT_SYMBOLS.contains(&result));
+    }
+
+    // multi char randomize
+    for _ in 0..10 {
+        for count in 2..5 {
+            let result = rand_chars(&mut rng, Settings::DEFAULT_SYMBOLS, count);
+            assert_eq!(count, result.len());
+            assert_eq!(
+                result.chars().next().unwrap().to_string().repeat(count),
+                result
+            );
+        }
+    }
+}
+
+#[test]
+fn test_rand_chars_multi_bytes() {
+    let mut rng = rand::thread_rng();
+    let pool = "⟪⟫é€🦀";
+
+    for _ in 0..20 {
+        let result = rand_chars(&mut rng, pool, 3);
+        assert_eq!(3, result.chars().count());
+
+        let char = result.chars().next().unwrap();
+        assert!(pool.contains(char));
+        assert_eq!(char.to_string().repeat(3), result);
+    }
+
+    // all chars are reachable, not only the first bytes of the pool
+    let mut rng = ChaCha20Rng::seed_from_u64(42);
+    let picked: HashSet<String> = (0..100).map(|_| rand_chars(&mut rng, pool, 1)).collect();
+    assert_eq!(pool.chars().count(), picked.len());
+}
+
+#[test]
+fn test_substitute_chars() {
+    let settings = Settings::default()
+        .with_substitutions(&[
+            (' ', "_".to_string()),
+            ('|', "".to_string()),
+            ('a', "4".to_string()),
+            ('4', "a".to_string()),
+            ('é', "€€".to_string()),
+        ])
+        .unwrap();
+
+    let table = [
+        ("", ""),
+        ("foo", "foo"),
+        ("a b|c", "4_bc"),
+        // substituted chars aren't substituted again
+        ("a4", "4a"),
+        ("café", "c4f€€"),
+    ];
+
+    for (passwd, expected) in table {
+        assert_eq!(expected, settings.substitute_chars(passwd));
+    }
+
+    assert_eq!("a b|c", Settings::default().substitute_chars("a b|c"));
+}
+
+#[test]
+fn test_fold_ascii() {
+    let table = [
+        ("foo", "foo"),
+        ("français", "francais"),
+        ("STRAẞE", "STRASSE"),
+        ("straße", "strasse"),
+        ("Über", "Uber"),
+        ("niño", "nino"),
+        ("CORAÇÃO", "CORACAO"),
+        ("œuvre", "oeuvre"),
+        ("Æther", "AEther"),
+        ("⟪é⟫", "⟪e⟫"),
+        ("", ""),
+    ];
+
+    for (word, expected) in table {
+        assert_eq!(expected, fold_ascii(word));
+    }
+}
+
+#[test]
+fn test_rand_words_ascii_fold() {
+    let mut rng = rand::thread_rng();
+    let pool = ["français", "straße", "niño", "coração"];
+    let settings = Settings::default()
+        .with_words_count(4)
+        .unwrap()
+        .with_word_transforms(WordTransform::Titlecase | WordTransform::InversedTitlecase)
+        .unwrap();
+
+    for _ in 0..10 {
+        assert!(settings
+            .rand_words(&mut rng, &pool)
+            .iter()
+            .any(|word| !word.is_ascii()));
+
+        let words = settings.with_ascii_fold(true).rand_words(&mut rng, &pool);
+        assert_eq!(4, words.len());
+        assert!(words.iter().all(|word| word.is_ascii()));
+    }
+}
+
+#[test]
+fn test_rand_words_ascii_fold_leetspeak() {
+    let mut rng = rand::thread_rng();
+    let pool = ["élève", "eleve"];
+    let settings = Settings::default()
+        .with_words_count(2)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::Leetspeak))
+        .unwrap();
+
+    let mut words = settings.rand_words(&mut rng, &pool);
+    words.sort();
+    assert_eq!(vec!["3l3v3", "élèv3"], words);
+
+    // folded first, accented vowels get substituted too
+    let folded = settings.with_ascii_fold(true);
+    assert_eq!(vec!["3l3v3", "3l3v3"], folded.rand_words(&mut rng, &pool));
+
+    // both words collide into the same output, a single word then has no entropy
+    let seen = |settings: Settings| {
+        let settings = settings.with_words_count(1).unwrap();
+        settings.calc_entropy(&pool).seen
+    };
+    assert!((seen(settings) - seen(folded) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_transform_word() {
+    let table = [
+        (
+            WordTransform::Lowercase,
+            [
+                ("foo", "foo"),
+                ("Bar", "bar"),
+                ("1Fooz", "1fooz"),
                 ("123", "123"),
+                ("ÉLAN", "élan"),
+                ("Über", "über"),
+                ("", ""),
             ],
         ),
         (
@@ -767,6 +2376,9 @@ fn test_transform_word() {
                 ("Bar", "Bar"),
                 ("1Fooz", "1Fooz"),
                 ("123", "123"),
+                ("élan", "Élan"),
+                ("über", "Über"),
+                ("", ""),
             ],
         ),
         (
@@ -776,6 +2388,9 @@ fn test_transform_word() {
                 ("Bar", "BAR"),
                 ("1Fooz", "1FOOZ"),
                 ("123", "123"),
+                ("élan", "ÉLAN"),
+                ("über", "ÜBER"),
+                ("", ""),
             ],
         ),
         (
@@ -785,6 +2400,45 @@ fn test_transform_word() {
                 ("Bar", "bAR"),
                 ("1Fooz", "1FOOZ"),
                 ("123", "123"),
+                ("Élan", "éLAN"),
+                ("über", "üBER"),
+                ("", ""),
+            ],
+        ),
+        (
+            WordTransform::Leetspeak,
+            [
+                ("foo", "f00"),
+                ("Bar", "b4r"),
+                ("1Fooz", "1f00z"),
+                ("123", "123"),
+                ("ÉLITE", "él173"),
+                ("über", "üb3r"),
+                ("", ""),
+            ],
+        ),
+        (
+            WordTransform::LastCapital,
+            [
+                ("foo", "foO"),
+                ("Bar", "baR"),
+                ("1Fooz", "1fooZ"),
+                ("123", "123"),
+                ("ÉLAN", "élaN"),
+                ("straß", "straSS"),
+                ("", ""),
+            ],
+        ),
+        (
+            WordTransform::Reversed,
+            [
+                ("foo", "oof"),
+                ("Bar", "rab"),
+                ("1Fooz", "zoof1"),
+                ("123", "321"),
+                ("ÉLAN", "nalé"),
+                ("über", "rebü"),
+                ("", ""),
             ],
         ),
     ];
@@ -795,3 +2449,193 @@ fn test_transform_word() {
         }
     }
 }
+
+#[test]
+fn test_transform_variants() {
+    assert_eq!(
+        vec!["Été", "éTé", "étÉ"],
+        transform_variants("été", WordTransform::RandomCapital)
+    );
+    assert_eq!(
+        vec!["".to_string()],
+        transform_variants("", WordTransform::RandomCapital)
+    );
+
+    // other transforms give a single output
+    assert_eq!(
+        vec!["FOO"],
+        transform_variants("foo", WordTransform::Uppercase)
+    );
+}
+
+#[test]
+fn test_rand_words_random_capital() {
+    let mut rng = rand::thread_rng();
+    let pool = ["correct", "horse", "battery", "staple"];
+    let settings = Settings::default()
+        .with_words_count(4)
+        .unwrap()
+        .with_word_transforms(FieldSize::from_flag(WordTransform::RandomCapital))
+        .unwrap();
+
+    for _ in 0..10 {
+        for word in settings.rand_words(&mut rng, &pool) {
+            assert_eq!(1, word.chars().filter(|c| c.is_uppercase()).count());
+            assert!(pool.contains(&word.to_lowercase().as_str()));
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    for preset in [
+        Preset::Default,
+        Preset::AppleID,
+        Preset::WindowsNtlmV1,
+        Preset::SecurityQuestions,
+        Preset::Web16,
+        Preset::Web32,
+        Preset::Wifi,
+        Preset::Xkcd,
+    ] {
+        let settings = Settings::from_preset(preset)
+            .with_substitutions(&[(' ', "_".to_string()), ('=', "".to_string())])
+            .unwrap()
+            .with_length_range(Some(12), None)
+            .unwrap();
+
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(settings, serde_json::from_str::<Settings>(&json).unwrap());
+
+        let toml = toml::to_string(&settings).unwrap();
+        assert_eq!(settings, toml::from_str::<Settings>(&toml).unwrap());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_schema() {
+    let settings = Settings::from_preset(Preset::Wifi);
+    assert_eq!(
+        serde_json::json!({
+            "words_count": 6,
+            "word_min": 4,
+            "word_max": 8,
+            "transforms": ["lowercase", "uppercase"],
+            "ascii_fold": false,
+            "unique_prefix": 0,
+            "separators": "-+=.*_|~,",
+            "separator_mode": "repeat",
+            "digits_before": 4,
+            "digits_after": 4,
+            "symbols": "!@$%^&*+=:|~?",
+            "symbols_before": 0,
+            "symbols_after": 0,
+            "symbol_mode": "repeat",
+            "substitutions": [],
+            "padding": "adaptive",
+            "adaptive_length": 63,
+        }),
+        serde_json::to_value(&settings).unwrap()
+    );
+
+    // missing keys fall back to defaults
+    let settings: Settings =
+        serde_json::from_str(r#"{"words_count": 5, "transforms": ["altercase-upper-first"]}"#)
+            .unwrap();
+    assert_eq!(
+        Settings::default()
+            .with_words_count(5)
+            .unwrap()
+            .with_word_transforms(WordTransform::AltercaseUpperFirst as FieldSize)
+            .unwrap(),
+        settings
+    );
+
+    let table = [
+        (r#"{"words_count": 0}"#, "words count must be 1 or higher"),
+        (
+            r#"{"word_min": 2}"#,
+            "min word length must be between 4 and 10",
+        ),
+        (r#"{"transforms": []}"#, "invalid transform 0b00000000"),
+        (
+            r#"{"padding": "adaptive"}"#,
+            "adaptive padding length is required for adaptive & exact padding strategies",
+        ),
+        (r#"{"padding": "none"}"#, "unknown variant `none`"),
+        (
+            r#"{"min_length": 20, "max_length": 10}"#,
+            "max length must be 20 or higher, got 10",
+        ),
+        (
+            r#"{"substitutions": ["|=!", "|=?"]}"#,
+            "invalid substitution '|': char substituted more than once",
+        ),
+        (
+            r#"{"substitutions": ["|"]}"#,
+            "invalid substitution '|': expected a single char",
+        ),
+        (r#"{"foo": 1}"#, "unknown field `foo`"),
+    ];
+
+    for (json, expected) in table {
+        let err = serde_json::from_str::<Settings>(json).err().unwrap();
+        assert!(err.to_string().starts_with(expected), "{} -> {}", json, err);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_enums() {
+    use crate::prelude::{AttackerProfile, GuessTime};
+
+    assert_eq!(
+        r#""inversed-titlecase""#,
+        serde_json::to_string(&WordTransform::InversedTitlecase).unwrap()
+    );
+    assert_eq!(
+        WordTransform::RandomCapital,
+        serde_json::from_str::<WordTransform>(r#""random-capital""#).unwrap()
+    );
+    assert_eq!(
+        r#""apple-id""#,
+        serde_json::to_string(&Preset::AppleID).unwrap()
+    );
+    assert_eq!(
+        r#"{"adaptive":16}"#,
+        serde_json::to_string(&PaddingStrategy::Adaptive(16)).unwrap()
+    );
+    assert_eq!(
+        PaddingStrategy::Fixed,
+        serde_json::from_str::<PaddingStrategy>(r#""fixed""#).unwrap()
+    );
+    assert_eq!(
+        PaddingStrategy::Exact(16),
+        serde_json::from_str::<PaddingStrategy>(r#"{"exact":16}"#).unwrap()
+    );
+
+    let entropy = Entropy {
+        blind_min: 50.0,
+        blind_max: 60.0,
+        seen: 40.5,
+        attacker: Attacker::new(AttackerProfile::GpuCluster),
+        guess_time: GuessTime {
+            seconds: 1.5,
+            years: 1.0,
+            months: 2,
+            days: 3,
+        },
+    };
+    let json = serde_json::to_string(&entropy).unwrap();
+    assert_eq!(
+        concat!(
+            r#"{"blind_min":50.0,"blind_max":60.0,"seen":40.5,"#,
+            r#""attacker":{"profile":"gpu-cluster","guesses_per_sec":1000000000000.0},"#,
+            r#""guess_time":{"seconds":1.5,"years":1.0,"months":2,"days":3}}"#
+        ),
+        json
+    );
+    assert_eq!(entropy, serde_json::from_str(&json).unwrap());
+}
@@ -1,15 +1,22 @@
 mod utils;
 
+use crate::bit_flags::FieldSize;
+#[cfg(feature = "wasm_optimizer")]
+use crate::optimizer::{optimize, Constraints};
+#[cfg(feature = "wasm_optimizer")]
+use crate::policy::CharClass;
 use crate::prelude::*;
+#[cfg(feature = "wasm_readout")]
+use crate::readout::Readout;
 use crate::settings::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 use wasm_bindgen::prelude::*;
 
 use utils::*;
 
-const DEFAULT_SETTING_BUILDER_ERR: &str = "Invalid settings";
-
 #[wasm_bindgen(js_name = "Settings")]
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct WasmSettings {
     settings: Settings,
 }
@@ -22,29 +29,56 @@ impl WasmSettings {
     }
 
     #[wasm_bindgen(js_name = "withWordsCount")]
-    pub fn with_words_count(&self, words_count: u8) -> WasmSettings {
-        let settings = self
-            .settings
-            .with_words_count(words_count)
-            .expect(DEFAULT_SETTING_BUILDER_ERR);
-        WasmSettings { settings }
+    pub fn with_words_count(&self, words_count: u8) -> Result<WasmSettings, SettingsError> {
+        let settings = self.settings.with_words_count(words_count)?;
+        Ok(WasmSettings { settings })
     }
 
     #[wasm_bindgen(js_name = "withWordLengths")]
-    pub fn with_word_lengths(&self, min: Option<u8>, max: Option<u8>) -> WasmSettings {
-        let settings = self
-            .settings
-            .with_word_lengths(min, max)
-            .expect(DEFAULT_SETTING_BUILDER_ERR);
+    pub fn with_word_lengths(
+        &self,
+        min: Option<u8>,
+        max: Option<u8>,
+    ) -> Result<WasmSettings, SettingsError> {
+        let settings = self.settings.with_word_lengths(min, max)?;
+        Ok(WasmSettings { settings })
+    }
+
+    #[wasm_bindgen(js_name = "withAsciiFold")]
+    pub fn with_ascii_fold(&self, ascii_fold: bool) -> WasmSettings {
+        let settings = self.settings.with_ascii_fold(ascii_fold);
+        WasmSettings { settings }
+    }
+
+    #[wasm_bindgen(js_name = "withUniquePrefix")]
+    pub fn with_unique_prefix(&self, length: u8) -> WasmSettings {
+        let settings = self.settings.with_unique_prefix(length);
         WasmSettings { settings }
     }
 
+    /// Rules written as `FROM=TO`, e.g. `|=!`, applied to generated passwords
+    #[wasm_bindgen(js_name = "withSubstitutions")]
+    pub fn with_substitutions(&self, rules: Vec<String>) -> Result<WasmSettings, SettingsError> {
+        let rules = rules
+            .iter()
+            .map(|rule| parse_substitution(rule))
+            .collect::<Result<Vec<_>, _>>()?;
+        let settings = self.settings.with_substitutions(&rules)?;
+        Ok(WasmSettings { settings })
+    }
+
     #[wasm_bindgen(js_name = "withSeparators")]
     pub fn with_separators(&self, separators: &str) -> WasmSettings {
         let settings = self.settings.with_separators(separators);
         WasmSettings { settings }
     }
 
+    #[wasm_bindgen(js_name = "withSeparatorMode")]
+    pub fn with_separator_mode(&self, mode: SeparatorMode) -> WasmSettings {
+        let settings = self.settings.with_separator_mode(mode);
+        WasmSettings { settings }
+    }
+
     #[wasm_bindgen(js_name = "withPaddingDigits")]
     pub fn with_padding_digits(&self, prefix: Option<u8>, suffix: Option<u8>) -> WasmSettings {
         let settings = self.settings.with_padding_digits(prefix, suffix);
@@ -67,31 +101,54 @@ impl WasmSettings {
         WasmSettings { settings }
     }
 
+    #[wasm_bindgen(js_name = "withSymbolMode")]
+    pub fn with_symbol_mode(&self, mode: SymbolMode) -> WasmSettings {
+        let settings = self.settings.with_symbol_mode(mode);
+        WasmSettings { settings }
+    }
+
     #[wasm_bindgen(js_name = "withFixedPadding")]
-    pub fn with_fixed_padding(&self) -> WasmSettings {
+    pub fn with_fixed_padding(&self) -> Result<WasmSettings, SettingsError> {
         let settings = self
             .settings
-            .with_padding_strategy(PaddingStrategy::Fixed)
-            .expect(DEFAULT_SETTING_BUILDER_ERR);
-        WasmSettings { settings }
+            .with_padding_strategy(PaddingStrategy::Fixed)?;
+        Ok(WasmSettings { settings })
     }
 
     #[wasm_bindgen(js_name = "withAdaptivePadding")]
-    pub fn with_adaptive_padding(&self, length: usize) -> WasmSettings {
+    pub fn with_adaptive_padding(&self, length: usize) -> Result<WasmSettings, SettingsError> {
         let settings = self
             .settings
-            .with_padding_strategy(PaddingStrategy::Adaptive(length))
-            .expect(DEFAULT_SETTING_BUILDER_ERR);
-        WasmSettings { settings }
+            .with_padding_strategy(PaddingStrategy::Adaptive(length))?;
+        Ok(WasmSettings { settings })
     }
 
-    #[wasm_bindgen(js_name = "withWordTransforms")]
-    pub fn with_word_transforms(&self, transforms: u8) -> WasmSettings {
+    /// Picks words by length & pads symbols so that passwords are exactly as long, never trimmed
+    #[wasm_bindgen(js_name = "withExactPadding")]
+    pub fn with_exact_padding(&self, length: usize) -> Result<WasmSettings, SettingsError> {
         let settings = self
             .settings
-            .with_word_transforms(transforms)
-            .expect(DEFAULT_SETTING_BUILDER_ERR);
-        WasmSettings { settings }
+            .with_padding_strategy(PaddingStrategy::Exact(length))?;
+        Ok(WasmSettings { settings })
+    }
+
+    #[wasm_bindgen(js_name = "withLengthRange")]
+    pub fn with_length_range(
+        &self,
+        min: Option<usize>,
+        max: Option<usize>,
+    ) -> Result<WasmSettings, SettingsError> {
+        let settings = self.settings.with_length_range(min, max)?;
+        Ok(WasmSettings { settings })
+    }
+
+    #[wasm_bindgen(js_name = "withWordTransforms")]
+    pub fn with_word_transforms(
+        &self,
+        transforms: FieldSize,
+    ) -> Result<WasmSettings, SettingsError> {
+        let settings = self.settings.with_word_transforms(transforms)?;
+        Ok(WasmSettings { settings })
     }
 
     #[wasm_bindgen(js_name = "fromPreset")]
@@ -102,6 +159,84 @@ impl WasmSettings {
     }
 }
 
+#[cfg(feature = "wasm_optimizer")]
+#[wasm_bindgen(js_name = "Constraints")]
+#[derive(Debug, Default)]
+pub struct WasmConstraints {
+    constraints: Constraints,
+}
+
+#[cfg(feature = "wasm_optimizer")]
+#[wasm_bindgen(js_class = "Constraints")]
+impl WasmConstraints {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmConstraints {
+        WasmConstraints::default()
+    }
+
+    #[wasm_bindgen(js_name = "withLengths")]
+    pub fn with_lengths(
+        &self,
+        min: Option<usize>,
+        max: Option<usize>,
+    ) -> Result<WasmConstraints, SettingsError> {
+        let constraints = self.constraints.with_lengths(min, max)?;
+        Ok(WasmConstraints { constraints })
+    }
+
+    /// Adds a class to the required ones
+    #[wasm_bindgen(js_name = "withRequiredClass")]
+    pub fn with_required_class(&self, class: CharClass) -> WasmConstraints {
+        let mut classes = self.constraints.required_classes().to_vec();
+        classes.push(class);
+
+        let constraints = self.constraints.with_required_classes(&classes);
+        WasmConstraints { constraints }
+    }
+
+    #[wasm_bindgen(js_name = "withSymbols")]
+    pub fn with_symbols(&self, symbols: &str) -> Result<WasmConstraints, SettingsError> {
+        let constraints = self.constraints.with_symbols(symbols)?;
+        Ok(WasmConstraints { constraints })
+    }
+}
+
+/// Thrown to JS instead of panicking on invalid settings,
+/// distinguishable with `instanceof SettingsError`
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SettingsError {
+    field: String,
+    message: String,
+}
+
+#[wasm_bindgen]
+impl SettingsError {
+    #[wasm_bindgen(getter)]
+    pub fn field(&self) -> String {
+        self.field.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    #[wasm_bindgen(js_name = "toString")]
+    pub fn to_js_string(&self) -> String {
+        format!("SettingsError: {}", self.message)
+    }
+}
+
+impl From<XkpasswdError> for SettingsError {
+    fn from(err: XkpasswdError) -> Self {
+        SettingsError {
+            field: err.field().to_string(),
+            message: err.to_string(),
+        }
+    }
+}
+
 #[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub struct PasswdResult {
@@ -115,6 +250,62 @@ impl PasswdResult {
     pub fn passwd(&self) -> String {
         self.passwd.clone()
     }
+
+    /// Time to break the password for another attacker profile,
+    /// at the profile default speed unless given
+    #[wasm_bindgen(js_name = "guessTimeFor")]
+    pub fn guess_time_for(
+        &self,
+        profile: AttackerProfile,
+        guesses_per_sec: Option<f64>,
+    ) -> Result<GuessTime, SettingsError> {
+        let attacker = Attacker::new(profile);
+        let attacker = match guesses_per_sec {
+            Some(guesses_per_sec) => attacker.with_guesses_per_sec(guesses_per_sec)?,
+            None => attacker,
+        };
+
+        Ok(self.entropy.guess_time_for(attacker))
+    }
+
+    /// Spoken form of the password piece by piece, e.g. to read it over the phone
+    #[cfg(feature = "wasm_readout")]
+    #[wasm_bindgen(js_name = "readout")]
+    pub fn readout(&self, language: Language) -> Vec<String> {
+        Readout::for_language(language).read(&self.passwd)
+    }
+}
+
+#[cfg(feature = "wasm_optimizer")]
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct OptimizeResult {
+    settings: Settings,
+    pub entropy: Entropy,
+}
+
+#[cfg(feature = "wasm_optimizer")]
+#[wasm_bindgen]
+impl OptimizeResult {
+    #[wasm_bindgen(getter)]
+    pub fn settings(&self) -> WasmSettings {
+        WasmSettings {
+            settings: self.settings.clone(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Attacker {
+    #[wasm_bindgen(getter = profile)]
+    pub fn js_profile(&self) -> AttackerProfile {
+        self.profile()
+    }
+
+    #[wasm_bindgen(getter = guessesPerSec)]
+    pub fn js_guesses_per_sec(&self) -> f64 {
+        self.guesses_per_sec()
+    }
 }
 
 #[wasm_bindgen(js_name = "Xkpasswd")]
@@ -131,13 +322,51 @@ impl WasmXkpasswd {
         WasmXkpasswd::default()
     }
 
+    /// Throws when passwords can't be generated as the settings require from the dictionary,
+    /// same as `checkSettings`
     #[wasm_bindgen(js_name = "genPass")]
-    pub fn gen_pass(&self, js_settings: &WasmSettings) -> PasswdResult {
+    pub fn gen_pass(&self, js_settings: &WasmSettings) -> Result<PasswdResult, SettingsError> {
         let settings: Settings = js_settings.settings.clone();
 
-        let (passwd, entropy) = self.pass_generator.gen_pass(&settings);
+        // same generator as the seeded mode, sparing the bundle a second one from thread_rng
+        let mut rng = ChaCha20Rng::from_entropy();
+        let (passwd, entropy) = self.pass_generator.gen_pass_with_rng(&settings, &mut rng)?;
         console_log!("{:?} {:?}", settings, entropy);
 
-        PasswdResult { passwd, entropy }
+        Ok(PasswdResult { passwd, entropy })
+    }
+
+    #[wasm_bindgen(js_name = "genPassWithSeed")]
+    pub fn gen_pass_with_seed(
+        &self,
+        js_settings: &WasmSettings,
+        seed: u64,
+    ) -> Result<PasswdResult, SettingsError> {
+        let settings: Settings = js_settings.settings.clone();
+
+        let (passwd, entropy) = self.pass_generator.gen_pass_with_seed(&settings, seed)?;
+        console_log!("{:?} {:?} with seed {}", settings, entropy, seed);
+
+        Ok(PasswdResult { passwd, entropy })
+    }
+
+    /// Throws when passwords can't be generated as the settings require from the dictionary,
+    /// e.g. no words fitting an exact padding length
+    #[wasm_bindgen(js_name = "checkSettings")]
+    pub fn check_settings(&self, js_settings: &WasmSettings) -> Result<(), SettingsError> {
+        Ok(self.pass_generator.check_settings(&js_settings.settings)?)
+    }
+
+    /// Settings with the highest entropy whose passwords always fit the constraints
+    #[cfg(feature = "wasm_optimizer")]
+    #[wasm_bindgen(js_name = "optimize")]
+    pub fn optimize(
+        &self,
+        js_constraints: &WasmConstraints,
+    ) -> Result<OptimizeResult, SettingsError> {
+        let (settings, entropy) = optimize(&self.pass_generator, &js_constraints.constraints)?;
+        console_log!("optimized {:?} {:?}", settings, entropy);
+
+        Ok(OptimizeResult { settings, entropy })
     }
 }
@@ -26,6 +26,26 @@ const SINGLE_WORD_TRANSFORMS = [
     text: 'iNVERSED TITLECASE',
     value: xkpasswd.WordTransform.InversedTitlecase,
   },
+  {
+    name: 'leetspeak',
+    text: 'l3375p34k',
+    value: xkpasswd.WordTransform.Leetspeak,
+  },
+  {
+    name: 'random-capital',
+    text: 'random cApital',
+    value: xkpasswd.WordTransform.RandomCapital,
+  },
+  {
+    name: 'last-capital',
+    text: 'last capitaL',
+    value: xkpasswd.WordTransform.LastCapital,
+  },
+  {
+    name: 'reversed',
+    text: 'desrever',
+    value: xkpasswd.WordTransform.Reversed,
+  },
 ];
 
 const GROUP_WORD_TRANSFORMS = [
@@ -25,7 +25,7 @@ const Bits = ({ value, seen = false }: BitsProps) => {
   const safeEntropy = seen ? NOT_BAD_ENTROPY_SEEN : NOT_BAD_ENTROPY_BLIND;
   const classNames =
     value >= safeEntropy ? 'entropy entropy-good' : 'entropy entropy-bad';
-  return <span className={classNames}>{value}</span>;
+  return <span className={classNames}>{value.toFixed(2)}</span>;
 };
 
 const Entropy = ({ entropy }: Props) => {
@@ -73,25 +73,23 @@ const Entropy = ({ entropy }: Props) => {
   );
 };
 
+// months & days are meaningless at this scale
 const calcExceptionalTime = (years: number) => {
-  if (years > 1_000_000_000) {
-    return 'more than a billion years';
-  }
-
-  if (years > 1_000_000) {
-    return 'more than a million years';
-  }
-
-  if (years > 1_000) {
-    return 'more than a thousand years';
+  if (years >= 1_000_000) {
+    return `${years.toExponential(2).replace('+', '')} years`;
   }
 
   return null;
 };
 
-const GuessTime = ({ value }: { value: xktypes.GuessTime }) => {
+type GuessTimeProps = {
+  value: xktypes.GuessTime;
+  guessesPerSec: number;
+};
+
+const GuessTime = ({ value, guessesPerSec }: GuessTimeProps) => {
   const prefix = 'which takes computer ';
-  const suffix = ' to break at 1000 guesses/sec';
+  const suffix = ` to break at ${guessesPerSec} guesses/sec`;
   const exceptionalTime = calcExceptionalTime(value.years);
 
   if (exceptionalTime) {
@@ -151,7 +149,10 @@ const Ratings = ({ entropy }: { entropy: xktypes.Entropy }) => {
   ) {
     return (
       <>
-        <GuessTime value={entropy.guess_time} />
+        <GuessTime
+          value={entropy.guess_time}
+          guessesPerSec={entropy.attacker.guessesPerSec}
+        />
         {'. Not good!'}
         <img className="entropy-img rage-face" src={rageFaceImage} />
       </>
@@ -164,7 +165,10 @@ const Ratings = ({ entropy }: { entropy: xktypes.Entropy }) => {
   ) {
     return (
       <>
-        <GuessTime value={entropy.guess_time} />
+        <GuessTime
+          value={entropy.guess_time}
+          guessesPerSec={entropy.attacker.guessesPerSec}
+        />
         {'. Great!'}
         <img className="entropy-img feels-good" src={feelsGoodImage} />
       </>
@@ -173,7 +177,10 @@ const Ratings = ({ entropy }: { entropy: xktypes.Entropy }) => {
 
   return (
     <>
-      <GuessTime value={entropy.guess_time} />
+      <GuessTime
+          value={entropy.guess_time}
+          guessesPerSec={entropy.attacker.guessesPerSec}
+        />
       {'. Not bad!'}
       <img className="entropy-img not-bad" src={notBadImage} />
     </>
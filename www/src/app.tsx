@@ -19,9 +19,19 @@ const App = () => {
   const [passwd, setPasswd] = useState<string>('');
 
   const genPasswd = useCallback(() => {
-    const { passwd, entropy } = passGenerator.genPass(settings);
-    setPasswd(passwd);
-    setEntropy(entropy);
+    try {
+      const { passwd, entropy } = passGenerator.genPass(settings);
+      setPasswd(passwd);
+      setEntropy(entropy);
+    } catch (err) {
+      // keep the last password
+      if (err instanceof xkpasswd.SettingsError) {
+        console.warn(err.toString());
+        return;
+      }
+
+      throw err;
+    }
   }, [passGenerator, settings]);
 
   useEffect(genPasswd, [genPasswd, passGenerator, settings]);
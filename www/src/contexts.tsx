@@ -90,17 +90,27 @@ export const useSettings = (): UseSettingsType => {
       return;
     }
 
-    const newSettings = new xkpasswd.Settings()
-      .withWordsCount(wordsCount)
-      .withWordTransforms(wordTransforms)
-      .withSeparators(separators)
-      .withPaddingDigits(digitsBefore, digitsAfter)
-      .withPaddingSymbols(paddingSymbols)
-      .withPaddingSymbolLengths(symbolsBefore, symbolsAfter);
-    const includingPaddingStrategy = adaptivePadding
-      ? newSettings.withAdaptivePadding(adaptiveCount)
-      : newSettings.withFixedPadding();
-    updateSettings(includingPaddingStrategy);
+    try {
+      const newSettings = new xkpasswd.Settings()
+        .withWordsCount(wordsCount)
+        .withWordTransforms(wordTransforms)
+        .withSeparators(separators)
+        .withPaddingDigits(digitsBefore, digitsAfter)
+        .withPaddingSymbols(paddingSymbols)
+        .withPaddingSymbolLengths(symbolsBefore, symbolsAfter);
+      const includingPaddingStrategy = adaptivePadding
+        ? newSettings.withAdaptivePadding(adaptiveCount)
+        : newSettings.withFixedPadding();
+      updateSettings(includingPaddingStrategy);
+    } catch (err) {
+      // keep the last valid settings
+      if (err instanceof xkpasswd.SettingsError) {
+        console.warn(err.toString());
+        return;
+      }
+
+      throw err;
+    }
   }, [
     updateSettings,
     preset,